@@ -55,15 +55,18 @@ impl FgpService for EchoService {
 
     fn method_list(&self) -> Vec<MethodInfo> {
         vec![
-            MethodInfo::new("echo.echo", "Echo back the provided parameters")
-                .param(ParamInfo {
-                    name: "message".into(),
-                    param_type: "string".into(),
-                    required: false,
-                    default: None,
-                }),
+            MethodInfo::new("echo.echo", "Echo back the provided parameters").param(ParamInfo {
+                name: "message".into(),
+                param_type: "string".into(),
+                required: false,
+                default: None,
+                ..Default::default()
+            }),
             MethodInfo::new("echo.ping", "Simple ping/pong health check"),
-            MethodInfo::new("echo.error", "Returns an error (for testing error handling)"),
+            MethodInfo::new(
+                "echo.error",
+                "Returns an error (for testing error handling)",
+            ),
         ]
     }
 
@@ -92,7 +95,7 @@ fn main() -> Result<()> {
     println!("  echo '{{\"id\":\"2\",\"v\":1,\"method\":\"echo\",\"params\":{{\"message\":\"hello\"}}}}' | nc -U ~/.fgp/services/echo/daemon.sock");
     println!();
 
-    let server = FgpServer::new(EchoService, "~/.fgp/services/echo/daemon.sock")?;
+    let server = FgpServer::for_service(EchoService)?;
     server.serve()?;
 
     Ok(())
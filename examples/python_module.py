@@ -75,6 +75,10 @@ class EchoModule:
                 - name: Method name
                 - description: Human-readable description
                 - params: List of parameter info dicts (optional)
+                - schema / returns: JSON Schema dicts (optional)
+                - examples: List of {"description", "params", "result"} (optional)
+                - errors: List of error codes (optional)
+                - deprecated: bool (optional)
         """
         return [
             {
@@ -93,15 +97,27 @@ class EchoModule:
                 "name": "echo.reverse",
                 "description": "Reverse a text string",
                 "params": [
-                    {"name": "text", "type": "string", "required": True}
+                    {"name": "text", "type": "string", "required": True,
+                     "description": "Text to reverse"}
                 ]
             },
             {
                 "name": "echo.add",
                 "description": "Add two numbers",
                 "params": [
-                    {"name": "a", "type": "number", "required": True},
-                    {"name": "b", "type": "number", "required": True}
+                    {"name": "a", "type": "number", "required": True,
+                     "description": "First addend"},
+                    {"name": "b", "type": "number", "required": True,
+                     "description": "Second addend"}
+                ],
+                "returns": {
+                    "type": "object",
+                    "properties": {"result": {"type": "number"}},
+                    "required": ["result"]
+                },
+                "examples": [
+                    {"description": "Add integers", "params": {"a": 5, "b": 3},
+                     "result": {"result": 8}}
                 ]
             },
             {
@@ -4,12 +4,27 @@
 
 use anyhow::{Context, Result};
 use std::collections::HashMap;
-use std::io::{BufRead, BufReader, Write};
+use std::io::{self, Write};
+use std::net::Shutdown;
 use std::os::unix::net::UnixStream;
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
 use std::time::Duration;
 
-use crate::protocol::{Request, Response};
+use crate::logging::LogTail;
+use crate::protocol::{
+    self, params_map, ErrorInfo, FramingMode, NdjsonReader, Request, Response, ServiceError,
+    POSITIONAL_ARGS_KEY,
+};
+use crate::service::{Capabilities, HealthReport, MethodInfo};
+
+/// How much longer than its timeout the client waits for a response.
+///
+/// The daemon's budget starts when the request arrives, after the client has started
+/// waiting; without the grace the client's read would expire first and the caller
+/// would get an I/O error instead of the daemon's `TIMEOUT` response and retry hint.
+const RESPONSE_GRACE: Duration = Duration::from_millis(500);
 
 /// FGP client for calling daemon methods.
 ///
@@ -43,24 +58,204 @@ use crate::protocol::{Request, Response};
 /// let response = client.call("gmail.inbox", serde_json::json!({}))?;
 /// # Ok::<(), anyhow::Error>(())
 /// ```
+///
+/// ## Persistent Connections
+///
+/// By default every call opens a new connection. `persistent()` keeps one open and
+/// reuses it; `persistent_resilient()` additionally reconnects after a daemon restart
+/// and replays calls made with [`call_idempotent`](FgpClient::call_idempotent):
+///
+/// ```rust,no_run
+/// use fgp_daemon::FgpClient;
+///
+/// let client = FgpClient::for_service("gmail")?.persistent_resilient();
+/// let response = client.call_idempotent("gmail.inbox", serde_json::json!({}))?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+///
+/// [`FgpClientBuilder`] sets several of these options at once.
 pub struct FgpClient {
     socket_path: PathBuf,
     timeout: Duration,
+    /// Give up connecting after this long instead of blocking
+    connect_timeout: Option<Duration>,
     /// Service name for auto-start support
     auto_start_service: Option<String>,
+    /// Reused connection, when persistent mode is enabled
+    connection: Option<Mutex<Option<NdjsonReader<UnixStream>>>>,
+    /// Reconnect and replay idempotent calls when the connection is lost
+    resilient: bool,
+    /// Fail when a response's id doesn't match its request
+    check_response_ids: bool,
+    /// Retry a one-shot call once if the connection is reset before any response
+    reconnect_once: bool,
+    /// How requests and responses are delimited
+    framing: FramingMode,
+}
+
+/// Fluent configuration for [`FgpClient`].
+///
+/// Collects the client's options in one place and checks them together in
+/// [`build`](Self::build), so a bad combination fails up front instead of on the
+/// first call. [`FgpClient::new`] and [`FgpClient::for_service`] remain the
+/// shortcuts for a client with default options.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use fgp_daemon::FgpClientBuilder;
+/// use std::time::Duration;
+///
+/// let client = FgpClientBuilder::new()
+///     .timeout(Duration::from_secs(5))
+///     .persistent_resilient()
+///     .build_for_service("gmail")?;
+/// let response = client.call_idempotent("gmail.inbox", serde_json::json!({}))?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FgpClientBuilder {
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    auto_start_service: Option<String>,
+    no_auto_start: bool,
+    persistent: bool,
+    resilient: bool,
+    response_id_check: Option<bool>,
+    reconnect_once: Option<bool>,
+    framing: FramingMode,
+}
+
+impl FgpClientBuilder {
+    /// Create a builder with default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request timeout; see [`FgpClient::with_timeout`].
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Connect timeout; see [`FgpClient::with_connect_timeout`].
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Auto-start a service when its daemon isn't running; see
+    /// [`FgpClient::with_auto_start`].
+    pub fn auto_start(mut self, service_name: &str) -> Self {
+        self.auto_start_service = Some(service_name.to_string());
+        self
+    }
+
+    /// Never auto-start; see [`FgpClient::without_auto_start`].
+    pub fn no_auto_start(mut self) -> Self {
+        self.no_auto_start = true;
+        self
+    }
+
+    /// Reuse one connection; see [`FgpClient::persistent`].
+    pub fn persistent(mut self) -> Self {
+        self.persistent = true;
+        self
+    }
+
+    /// Reuse one connection across daemon restarts; see
+    /// [`FgpClient::persistent_resilient`].
+    pub fn persistent_resilient(mut self) -> Self {
+        self.persistent = true;
+        self.resilient = true;
+        self
+    }
+
+    /// Response id checking; see [`FgpClient::with_response_id_check`].
+    pub fn response_id_check(mut self, enabled: bool) -> Self {
+        self.response_id_check = Some(enabled);
+        self
+    }
+
+    /// Retry after a connection reset; see [`FgpClient::with_reconnect_once`].
+    pub fn reconnect_once(mut self, enabled: bool) -> Self {
+        self.reconnect_once = Some(enabled);
+        self
+    }
+
+    /// Message framing; see [`FgpClient::with_framing`].
+    pub fn framing(mut self, framing: FramingMode) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Create a client for the daemon at `socket_path` with these options.
+    pub fn build(self, socket_path: impl AsRef<Path>) -> Result<FgpClient> {
+        self.apply(FgpClient::new(socket_path)?)
+    }
+
+    /// Create a client for a named service, auto-starting it unless
+    /// [`no_auto_start`](Self::no_auto_start) is set; see [`FgpClient::for_service`].
+    pub fn build_for_service(self, service_name: &str) -> Result<FgpClient> {
+        self.apply(FgpClient::for_service(service_name)?)
+    }
+
+    fn apply(self, mut client: FgpClient) -> Result<FgpClient> {
+        if self.timeout == Some(Duration::ZERO) {
+            anyhow::bail!("Client timeout must be greater than zero");
+        }
+        if self.connect_timeout == Some(Duration::ZERO) {
+            anyhow::bail!("Connect timeout must be greater than zero");
+        }
+        if self.no_auto_start && self.auto_start_service.is_some() {
+            anyhow::bail!("auto_start and no_auto_start are mutually exclusive");
+        }
+
+        if let Some(timeout) = self.timeout {
+            client = client.with_timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            client = client.with_connect_timeout(timeout);
+        }
+        if let Some(service_name) = &self.auto_start_service {
+            client = client.with_auto_start(service_name);
+        }
+        if self.no_auto_start {
+            client = client.without_auto_start();
+        }
+        if let Some(enabled) = self.response_id_check {
+            client = client.with_response_id_check(enabled);
+        }
+        if let Some(enabled) = self.reconnect_once {
+            client = client.with_reconnect_once(enabled);
+        }
+        client = match (self.persistent, self.resilient) {
+            (true, true) => client.persistent_resilient(),
+            (true, false) => client.persistent(),
+            _ => client,
+        };
+        Ok(client.with_framing(self.framing))
+    }
 }
 
 impl FgpClient {
     /// Create a new FGP client.
     ///
     /// # Arguments
-    /// * `socket_path` - Path to the daemon's UNIX socket (supports `~` expansion)
+    /// * `socket_path` - Path to the daemon's UNIX socket (supports `~` expansion), or
+    ///   `@name` for a daemon on the abstract socket `name` (Linux only)
     pub fn new(socket_path: impl AsRef<Path>) -> Result<Self> {
         let socket_path = expand_path(socket_path.as_ref())?;
         Ok(Self {
             socket_path,
             timeout: Duration::from_secs(30),
+            connect_timeout: None,
             auto_start_service: None,
+            connection: None,
+            resilient: false,
+            check_response_ids: true,
+            reconnect_once: true,
+            framing: FramingMode::Ndjson,
         })
     }
 
@@ -87,16 +282,59 @@ impl FgpClient {
         Ok(Self {
             socket_path,
             timeout: Duration::from_secs(30),
+            connect_timeout: None,
             auto_start_service: Some(service_name.to_string()),
+            connection: None,
+            resilient: false,
+            check_response_ids: true,
+            reconnect_once: true,
+            framing: FramingMode::Ndjson,
         })
     }
 
+    /// Create a client for the socket given by the environment.
+    ///
+    /// Uses `FGP_SOCKET` if set. Otherwise `FGP_SERVICE` names the service, as with
+    /// [`for_service`](Self::for_service) (including auto-start). Fails if neither is
+    /// set. Lets the same binary run where sockets live somewhere other than
+    /// `~/.fgp/services`, e.g. in containers.
+    pub fn from_env() -> Result<Self> {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.is_empty());
+        if let Some(path) = var(crate::lifecycle::SOCKET_ENV) {
+            return Self::new(path);
+        }
+        match var(crate::lifecycle::SERVICE_ENV) {
+            Some(service_name) => Self::for_service(&service_name),
+            None => anyhow::bail!(
+                "Neither {} nor {} is set",
+                crate::lifecycle::SOCKET_ENV,
+                crate::lifecycle::SERVICE_ENV
+            ),
+        }
+    }
+
     /// Set the request timeout.
+    ///
+    /// The timeout is also sent as the request's `timeout_ms` so the daemon can
+    /// give up on work the client is no longer waiting for. The client waits a little
+    /// longer than the timeout, so a slow call normally ends with the daemon's
+    /// `TIMEOUT` error rather than a read timeout.
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.timeout = timeout;
         self
     }
 
+    /// Bound how long connecting to the daemon may take.
+    ///
+    /// Without it, connecting blocks until the daemon accepts, which can hang when
+    /// the daemon is still starting up or isn't accepting connections. With it, a
+    /// connect that doesn't complete in time fails with a `TIMEOUT` [`ServiceError`].
+    /// This also bounds the reconnect after an auto-start.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
     /// Enable auto-start for a specific service.
     ///
     /// When auto-start is enabled and the daemon is not running, the client
@@ -117,24 +355,239 @@ impl FgpClient {
         self
     }
 
+    /// Enable or disable checking that each response's `id` matches its request.
+    ///
+    /// Enabled by default. A mismatch means the connection is out of step with the
+    /// daemon, so the call fails and a persistent connection is dropped. Error
+    /// responses with id `"null"` (the server could not read the request) are accepted.
+    pub fn with_response_id_check(mut self, enabled: bool) -> Self {
+        self.check_response_ids = enabled;
+        self
+    }
+
+    /// Enable or disable retrying a call once when the connection is reset early.
+    ///
+    /// Enabled by default. Applies to calls on a fresh connection (not
+    /// [`persistent`](Self::persistent) ones): if the daemon resets the connection
+    /// before sending any response bytes, as it can while restarting, the call is
+    /// made once more on a new connection. The daemon most likely never processed the
+    /// request, but disable this if even a rare duplicate call is unacceptable.
+    pub fn with_reconnect_once(mut self, enabled: bool) -> Self {
+        self.reconnect_once = enabled;
+        self
+    }
+
+    /// Delimit messages with `framing` instead of NDJSON lines.
+    ///
+    /// Must match the daemon's [`FgpServer::with_framing`](crate::FgpServer::with_framing).
+    pub fn with_framing(mut self, framing: FramingMode) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Keep a single connection open and reuse it for every call.
+    ///
+    /// The connection is opened on the first call. If it breaks, the call fails and
+    /// the next call opens a new one.
+    pub fn persistent(mut self) -> Self {
+        self.connection = Some(Mutex::new(None));
+        self
+    }
+
+    /// Like [`persistent`](Self::persistent), but survives daemon restarts.
+    ///
+    /// When the connection is lost (`UnexpectedEof`, `BrokenPipe`, ...), the client
+    /// reconnects (auto-starting the daemon if configured) and retries the pending
+    /// request once. Only calls made with [`call_idempotent`](Self::call_idempotent),
+    /// `health` and `methods` are replayed; other calls return the error, since the
+    /// daemon may already have applied them.
+    pub fn persistent_resilient(self) -> Self {
+        let mut client = self.persistent();
+        client.resilient = true;
+        client
+    }
+
     /// Call a daemon method.
     ///
+    /// Request params are always a JSON object: an object `params` is sent as is and
+    /// `null` as `{}`. Any other value is wrapped as `{"value": ...}`; use
+    /// [`call_positional`](Self::call_positional) for array arguments.
+    ///
     /// # Arguments
     /// * `method` - Method name (e.g., "gmail.list")
     /// * `params` - Method parameters as JSON value
     pub fn call(&self, method: &str, params: serde_json::Value) -> Result<Response> {
-        let params_map: HashMap<String, serde_json::Value> = match params {
-            serde_json::Value::Object(map) => map.into_iter().collect(),
-            serde_json::Value::Null => HashMap::new(),
-            _ => {
-                let mut map = HashMap::new();
-                map.insert("value".into(), params);
-                map
+        self.send_request(&self.build_request(method, params), false)
+    }
+
+    /// Call a method, also returning the round-trip time measured by the client.
+    ///
+    /// The time covers connecting (unless the client is persistent), sending the
+    /// request and reading the response; compare with `meta.server_ms` to see how much
+    /// of it was spent in the daemon.
+    pub fn timed_call(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<(Response, Duration)> {
+        let start = std::time::Instant::now();
+        let response = self.call(method, params)?;
+        Ok((response, start.elapsed()))
+    }
+
+    /// Call a method and return its `result`.
+    ///
+    /// An error response becomes an `Err` holding a [`ServiceError`], so its message
+    /// reads `CODE: message` and the code can be recovered with `downcast_ref`.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use fgp_daemon::FgpClient;
+    ///
+    /// let client = FgpClient::for_service("gmail")?;
+    /// let inbox = client.call_value("gmail.list", serde_json::json!({"limit": 5}))?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn call_value(&self, method: &str, params: serde_json::Value) -> Result<serde_json::Value> {
+        let response = self.call(method, params)?;
+        if response.ok {
+            return Ok(response.result.unwrap_or_default());
+        }
+        let error = response.error.unwrap_or_default();
+        Err(ServiceError {
+            code: error.code,
+            message: error.message,
+            details: error.details,
+        }
+        .into())
+    }
+
+    /// Call a method with positional arguments, sent as `{"args": [...]}`.
+    pub fn call_positional(&self, method: &str, args: Vec<serde_json::Value>) -> Result<Response> {
+        let params = HashMap::from([(
+            POSITIONAL_ARGS_KEY.to_string(),
+            serde_json::Value::Array(args),
+        )]);
+        self.call_raw(method, params)
+    }
+
+    /// Call a method that is safe to run twice.
+    ///
+    /// Behaves like [`call`](Self::call), except that a
+    /// [`persistent_resilient`](Self::persistent_resilient) client may replay it
+    /// after reconnecting.
+    pub fn call_idempotent(&self, method: &str, params: serde_json::Value) -> Result<Response> {
+        self.send_request(&self.build_request(method, params), true)
+    }
+
+    /// Call an idempotent method, retrying while the server marks the error retriable.
+    ///
+    /// Makes up to `max_attempts` calls in total. Retries only happen when the error
+    /// carries a retry hint (see [`ErrorInfo::retry_hint`]), such as `TIMEOUT`, and
+    /// wait for the hint's `retry_after_ms` first if it has one.
+    /// A timed-out call may still complete on the server, so only use this for
+    /// methods that are safe to run more than once.
+    pub fn call_with_retry(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+        max_attempts: u32,
+    ) -> Result<Response> {
+        let mut attempt = 1;
+        loop {
+            let response = self.call_idempotent(method, params.clone())?;
+            let hint = response
+                .error
+                .as_ref()
+                .and_then(ErrorInfo::retry_hint)
+                .filter(|hint| hint.retriable);
+            let Some(hint) = hint.filter(|_| attempt < max_attempts) else {
+                return Ok(response);
+            };
+            if let Some(ms) = hint.retry_after_ms {
+                thread::sleep(Duration::from_millis(ms));
             }
-        };
+            tracing::debug!("Retrying {} (attempt {})", method, attempt + 1);
+            attempt += 1;
+        }
+    }
+
+    /// Send several requests on one connection before reading any response.
+    ///
+    /// All requests are written back to back and the responses are returned in request
+    /// order, matched by `id` (ids must be unique). This saves a round trip per request
+    /// for batch workloads. The persistent connection is used when enabled; pipelined
+    /// requests are never replayed.
+    pub fn pipeline(&self, requests: Vec<Request>) -> Result<Vec<Response>> {
+        let mut index = HashMap::with_capacity(requests.len());
+        for (i, request) in requests.iter().enumerate() {
+            request.validate()?;
+            if index.insert(request.id.clone(), i).is_some() {
+                anyhow::bail!("Duplicate request id in pipeline: {}", request.id);
+            }
+        }
+
+        let mut guard = self
+            .connection
+            .as_ref()
+            .map(|c| c.lock().unwrap_or_else(|e| e.into_inner()));
+        let mut own_connection = None;
+        let connection = guard.as_deref_mut().unwrap_or(&mut own_connection);
+
+        let result = self.pipeline_on(connection, &requests, &index);
+        if result.is_err() {
+            // Responses may still be in flight; don't reuse the stream
+            *connection = None;
+        }
+        result
+    }
+
+    /// Call a method that streams its result and collect every chunk's items.
+    ///
+    /// Daemons stream methods through `FgpService::dispatch_stream`. A streamed
+    /// result arrives as frames flagged
+    /// [`partial`](protocol::ResponseMeta::partial), each holding a JSON array of
+    /// items, and ends with one unflagged frame. The items of all frames are
+    /// deserialized into `T` and concatenated. Fails if the method answers with a
+    /// single, unstreamed frame. An error frame becomes an `Err` holding a
+    /// [`ServiceError`], as with [`call_value`](Self::call_value).
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use fgp_daemon::FgpClient;
+    ///
+    /// let client = FgpClient::for_service("gmail")?;
+    /// let ids: Vec<String> = client.call_collect_stream("gmail.export_ids", serde_json::json!({}))?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn call_collect_stream<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: serde_json::Value,
+    ) -> Result<Vec<T>> {
+        let request = self.build_request(method, params);
+        request.validate()?;
 
-        let request = Request::new(method, params_map);
-        self.send_request(&request)
+        let mut guard = self
+            .connection
+            .as_ref()
+            .map(|c| c.lock().unwrap_or_else(|e| e.into_inner()));
+        let mut own_connection = None;
+        let connection = guard.as_deref_mut().unwrap_or(&mut own_connection);
+
+        let result = self.collect_stream_on(connection, &request);
+        if result.is_err() {
+            // Frames may still be in flight; don't reuse the stream
+            *connection = None;
+        }
+        result
+    }
+
+    /// Build a request from JSON params.
+    fn build_request(&self, method: &str, params: serde_json::Value) -> Request {
+        Request::new(method, params_map(params)).with_timeout(self.timeout)
     }
 
     /// Call a method with raw params HashMap.
@@ -143,18 +596,74 @@ impl FgpClient {
         method: &str,
         params: HashMap<String, serde_json::Value>,
     ) -> Result<Response> {
-        let request = Request::new(method, params);
-        self.send_request(&request)
+        let request = Request::new(method, params).with_timeout(self.timeout);
+        self.send_request(&request, false)
+    }
+
+    /// Call the `ping` method and return the round-trip time seen by the client.
+    ///
+    /// Cheaper than [`health`](Self::health); use it as the keep-alive for persistent
+    /// connections.
+    pub fn ping(&self) -> Result<Duration> {
+        let start = std::time::Instant::now();
+        let result = into_result(
+            self.call_idempotent("ping", serde_json::Value::Null)?,
+            "ping",
+        )?;
+        let elapsed = start.elapsed();
+        if result.get("pong") != Some(&serde_json::Value::Bool(true)) {
+            anyhow::bail!("Invalid ping response: {}", result);
+        }
+        Ok(elapsed)
     }
 
     /// Call the `health` method.
+    ///
+    /// This is a cheap liveness check: the daemon doesn't run its dependency checks.
+    /// Use [`deep_health`](Self::deep_health) for those.
     pub fn health(&self) -> Result<Response> {
-        self.call("health", serde_json::Value::Null)
+        self.call_idempotent("health", serde_json::Value::Null)
+    }
+
+    /// Call the `health` method with `deep: true`, running the service's dependency
+    /// checks (readiness rather than liveness).
+    pub fn deep_health(&self) -> Result<Response> {
+        self.call_idempotent("health", serde_json::json!({"deep": true}))
+    }
+
+    /// Call the `health` method and parse the result.
+    pub fn health_report(&self) -> Result<HealthReport> {
+        let result = into_result(self.health()?, "health")?;
+        serde_json::from_value(result).context("Invalid health response")
+    }
+
+    /// Call the `health` method with `deep: true` and parse the result.
+    pub fn deep_health_report(&self) -> Result<HealthReport> {
+        let result = into_result(self.deep_health()?, "health")?;
+        serde_json::from_value(result).context("Invalid health response")
     }
 
     /// Call the `methods` method.
     pub fn methods(&self) -> Result<Response> {
-        self.call("methods", serde_json::Value::Null)
+        self.call_idempotent("methods", serde_json::Value::Null)
+    }
+
+    /// Fetch the daemon's methods from the `methods` response.
+    pub fn method_list(&self) -> Result<Vec<MethodInfo>> {
+        let result = into_result(self.methods()?, "methods")?;
+        let methods = result.get("methods").cloned().unwrap_or_default();
+        serde_json::from_value(methods).context("Invalid methods in methods response")
+    }
+
+    /// Fetch the daemon's capabilities from the `methods` response.
+    pub fn capabilities(&self) -> Result<Capabilities> {
+        let result = into_result(self.methods()?, "methods")?;
+        match result.get("capabilities") {
+            Some(capabilities) => serde_json::from_value(capabilities.clone())
+                .context("Invalid capabilities in methods response"),
+            // Daemons built before capabilities were reported
+            None => Ok(Capabilities::default()),
+        }
     }
 
     /// Call the `stop` method.
@@ -162,15 +671,221 @@ impl FgpClient {
         self.call("stop", serde_json::Value::Null)
     }
 
+    /// Call the `reload` method.
+    ///
+    /// The daemon must enable it with `FgpServer::with_reload_method(true)`.
+    pub fn reload(&self) -> Result<Response> {
+        self.call("reload", serde_json::Value::Null)
+    }
+
+    /// Fetch the last `lines` lines of the daemon log via the `logs` method.
+    ///
+    /// The daemon must enable it with `FgpServer::with_logs_method(true)`.
+    pub fn tail_logs(&self, lines: usize) -> Result<LogTail> {
+        self.logs(serde_json::json!({"lines": lines}))
+    }
+
+    /// Fetch log lines written since `offset` (from a previous [`LogTail`]).
+    ///
+    /// Returns at most 10,000 lines; call repeatedly with the returned offset to
+    /// follow the log and pick up the rest.
+    pub fn follow_logs(&self, offset: u64) -> Result<LogTail> {
+        self.logs(serde_json::json!({"offset": offset, "lines": 10_000}))
+    }
+
+    fn logs(&self, params: serde_json::Value) -> Result<LogTail> {
+        let result = into_result(self.call("logs", params)?, "logs")?;
+        Ok(LogTail {
+            lines: serde_json::from_value(result["lines"].clone())
+                .context("Invalid logs response")?,
+            offset: result["offset"].as_u64().unwrap_or(0),
+        })
+    }
+
     /// Check if the daemon is running.
     pub fn is_running(&self) -> bool {
         self.health().is_ok()
     }
 
     /// Send a request and receive a response.
-    fn send_request(&self, request: &Request) -> Result<Response> {
+    ///
+    /// Malformed requests are rejected locally (see [`Request::validate`]).
+    fn send_request(&self, request: &Request, idempotent: bool) -> Result<Response> {
+        request.validate()?;
+        let Some(connection) = &self.connection else {
+            let stream = self.connect()?;
+            return match self.send_request_on_stream(stream, request) {
+                Err(e) if self.reconnect_once && e.is::<ResetBeforeResponse>() => {
+                    tracing::debug!("Connection reset, retrying request {}", request.id);
+                    self.send_request_on_stream(self.connect()?, request)
+                }
+                result => result,
+            };
+        };
+
+        let mut connection = connection.lock().unwrap_or_else(|e| e.into_inner());
+        match self.exchange(&mut connection, request) {
+            Ok(response) => Ok(response),
+            Err(e) if is_connection_lost(&e) => {
+                *connection = None;
+                if !(self.resilient && idempotent) {
+                    return Err(e).context("Connection to daemon lost");
+                }
+                tracing::debug!("Connection to daemon lost, retrying request {}", request.id);
+                self.exchange(&mut connection, request).inspect_err(|_| {
+                    // As below: the new stream may also be desynced
+                    *connection = None;
+                })
+            }
+            Err(e) => {
+                // The stream may still carry a late response; don't reuse it.
+                *connection = None;
+                Err(e)
+            }
+        }
+    }
+
+    /// Send a request on the persistent connection, opening it if needed.
+    fn exchange(
+        &self,
+        connection: &mut Option<NdjsonReader<UnixStream>>,
+        request: &Request,
+    ) -> Result<Response> {
+        let reader = self.open(connection)?;
+
+        let request_json = serde_json::to_string(request)?;
+        self.framing.write_frame(reader.get_mut(), &request_json)?;
+        reader.get_mut().flush()?;
+
+        let response = read_response(reader)?;
+        self.check_response_id(request, &response)?;
+        if response.meta.connection_close {
+            // The server is closing this connection; the next call reconnects
+            *connection = None;
+        }
+        Ok(response)
+    }
+
+    /// Return the open connection, connecting first if there is none.
+    fn open<'a>(
+        &self,
+        connection: &'a mut Option<NdjsonReader<UnixStream>>,
+    ) -> Result<&'a mut NdjsonReader<UnixStream>> {
+        match connection {
+            Some(reader) => Ok(reader),
+            None => {
+                let stream = self.connect()?;
+                stream.set_read_timeout(Some(self.timeout + RESPONSE_GRACE))?;
+                stream.set_write_timeout(Some(self.timeout))?;
+                Ok(connection.insert(NdjsonReader::new(stream).with_framing(self.framing)))
+            }
+        }
+    }
+
+    /// Send a request and read its frames until the last one of the stream.
+    fn collect_stream_on<T: serde::de::DeserializeOwned>(
+        &self,
+        connection: &mut Option<NdjsonReader<UnixStream>>,
+        request: &Request,
+    ) -> Result<Vec<T>> {
+        let reader = self.open(connection)?;
+        self.framing
+            .write_frame(reader.get_mut(), &serde_json::to_string(request)?)?;
+        reader.get_mut().flush()?;
+
+        let mut items = Vec::new();
+        for chunk in 0.. {
+            let response = read_response(reader)?;
+            self.check_response_id(request, &response)?;
+            if !response.ok {
+                let error = response.error.unwrap_or_default();
+                return Err(ServiceError {
+                    code: error.code,
+                    message: error.message,
+                    details: error.details,
+                }
+                .into());
+            }
+            if chunk == 0 && !response.meta.partial {
+                anyhow::bail!("Method '{}' did not stream its result", request.method);
+            }
+            let chunk_items: Vec<T> =
+                serde_json::from_value(response.result.unwrap_or(serde_json::json!([])))
+                    .with_context(|| {
+                        format!(
+                            "Invalid chunk {} of streamed {} result",
+                            chunk, request.method
+                        )
+                    })?;
+            items.extend(chunk_items);
+            if !response.meta.partial {
+                if response.meta.connection_close {
+                    *connection = None;
+                }
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    /// Write every request, then read one response per request.
+    fn pipeline_on(
+        &self,
+        connection: &mut Option<NdjsonReader<UnixStream>>,
+        requests: &[Request],
+        index: &HashMap<String, usize>,
+    ) -> Result<Vec<Response>> {
+        let reader = self.open(connection)?;
+        let mut writer = reader.get_ref().try_clone()?;
+        let framing = self.framing;
+
+        let mut responses: Vec<Option<Response>> = vec![None; requests.len()];
+        let mut close = false;
+        thread::scope(|scope| {
+            // Write on a separate thread so a full socket buffer can't deadlock
+            // against responses we haven't read yet
+            let writing = scope.spawn(move || -> Result<()> {
+                for request in requests {
+                    framing.write_frame(&mut writer, &serde_json::to_string(request)?)?;
+                }
+                writer.flush()?;
+                Ok(())
+            });
+
+            let read = (0..requests.len()).try_for_each(|_| {
+                let response = read_response(reader)?;
+                close |= response.meta.connection_close;
+                let slot = index
+                    .get(&response.id)
+                    .and_then(|&i| responses.get_mut(i))
+                    .filter(|slot| slot.is_none())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("Unexpected response id in pipeline: {}", response.id)
+                    })?;
+                *slot = Some(response);
+                Ok::<(), anyhow::Error>(())
+            });
+            if read.is_err() {
+                // Unblock the writer
+                let _ = reader.get_ref().shutdown(Shutdown::Both);
+            }
+
+            let written = writing
+                .join()
+                .unwrap_or_else(|_| Err(anyhow::anyhow!("Pipeline writer panicked")));
+            read.and(written)
+        })?;
+
+        if close {
+            *connection = None;
+        }
+        Ok(responses.into_iter().flatten().collect())
+    }
+
+    /// Connect to the daemon socket, auto-starting the service if configured.
+    fn connect(&self) -> Result<UnixStream> {
         // Try to connect to socket
-        let stream = match UnixStream::connect(&self.socket_path) {
+        let stream = match self.connect_socket() {
             Ok(stream) => stream,
             Err(e) => {
                 // Connection failed - try auto-start if configured
@@ -186,7 +901,7 @@ impl FgpClient {
                     })?;
 
                     // Retry connection
-                    UnixStream::connect(&self.socket_path).with_context(|| {
+                    self.connect_socket().with_context(|| {
                         format!(
                             "Cannot connect to daemon at {:?} after auto-start",
                             self.socket_path
@@ -200,32 +915,127 @@ impl FgpClient {
             }
         };
 
-        self.send_request_on_stream(stream, request)
+        Ok(stream)
+    }
+
+    /// Connect to the socket once, within the connect timeout if one is set.
+    fn connect_socket(&self) -> Result<UnixStream> {
+        let Some(timeout) = self.connect_timeout else {
+            return Ok(crate::lifecycle::connect_socket(&self.socket_path)?);
+        };
+        crate::lifecycle::connect_socket_timeout(&self.socket_path, timeout).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::TimedOut {
+                ServiceError::new(
+                    protocol::error_codes::TIMEOUT,
+                    format!(
+                        "Timed out after {}ms connecting to daemon at {:?}",
+                        timeout.as_millis(),
+                        self.socket_path
+                    ),
+                )
+                .into()
+            } else {
+                e.into()
+            }
+        })
     }
 
     /// Send request on an already-connected stream.
-    fn send_request_on_stream(
-        &self,
-        mut stream: UnixStream,
-        request: &Request,
-    ) -> Result<Response> {
-        stream.set_read_timeout(Some(self.timeout))?;
+    fn send_request_on_stream(&self, stream: UnixStream, request: &Request) -> Result<Response> {
+        stream.set_read_timeout(Some(self.timeout + RESPONSE_GRACE))?;
         stream.set_write_timeout(Some(self.timeout))?;
 
-        // Send request
-        let request_line = request.to_ndjson_line()?;
-        stream.write_all(request_line.as_bytes())?;
-        stream.flush()?;
+        let mut reader = NdjsonReader::new(CountingReader {
+            inner: &stream,
+            bytes: 0,
+        })
+        .with_framing(self.framing);
+        let response = match protocol::exchange(&mut reader, &mut &stream, request) {
+            Ok(response) => response,
+            Err(e) if reader.get_ref().bytes == 0 && is_connection_reset(&e) => {
+                return Err(e.context(ResetBeforeResponse));
+            }
+            Err(e) => return Err(e),
+        };
+        self.check_response_id(request, &response)?;
+        Ok(response)
+    }
+
+    /// Verify a response belongs to the request, if checking is enabled.
+    fn check_response_id(&self, request: &Request, response: &Response) -> Result<()> {
+        let unreadable_request = !response.ok && response.id == "null";
+        if self.check_response_ids && response.id != request.id && !unreadable_request {
+            anyhow::bail!(
+                "Response id mismatch: expected '{}', got '{}'",
+                request.id,
+                response.id
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Error context for a connection reset before any response bytes arrived.
+#[derive(Debug)]
+struct ResetBeforeResponse;
+
+impl std::fmt::Display for ResetBeforeResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Connection reset before any response")
+    }
+}
 
-        // Read response
-        let mut reader = BufReader::new(&stream);
-        let mut response_line = String::new();
-        reader.read_line(&mut response_line)?;
+/// A reader that counts the bytes read through it.
+struct CountingReader<R> {
+    inner: R,
+    bytes: u64,
+}
 
-        Response::from_ndjson_line(&response_line)
+impl<R: io::Read> io::Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bytes += n as u64;
+        Ok(n)
     }
 }
 
+/// Whether an error is an `ECONNRESET` from the socket.
+fn is_connection_reset(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<io::Error>()
+        .is_some_and(|e| e.kind() == io::ErrorKind::ConnectionReset)
+}
+
+/// Read one response line, treating EOF as a lost connection.
+fn read_response<R: io::Read>(reader: &mut NdjsonReader<R>) -> Result<Response> {
+    reader
+        .read_response()?
+        .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof).into())
+}
+
+/// Turn an error response into an `Err`, returning the result otherwise.
+fn into_result(response: Response, method: &str) -> Result<serde_json::Value> {
+    if !response.ok {
+        let error = response.error.unwrap_or_default();
+        anyhow::bail!("{} failed: {} {}", method, error.code, error.message);
+    }
+    Ok(response.result.unwrap_or_default())
+}
+
+/// Whether an error means the daemon closed the connection (e.g. it restarted).
+fn is_connection_lost(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<io::Error>().is_some_and(|e| {
+        matches!(
+            e.kind(),
+            io::ErrorKind::UnexpectedEof
+                | io::ErrorKind::BrokenPipe
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::NotConnected
+        )
+    })
+}
+
 /// Expand `~` in path to home directory.
 fn expand_path(path: &Path) -> Result<PathBuf> {
     let path_str = path.to_string_lossy();
@@ -280,6 +1090,64 @@ pub fn is_running(service_name: &str) -> bool {
     crate::lifecycle::is_service_running(service_name)
 }
 
+/// List the methods of every installed service, e.g. to build one tool catalog
+/// across daemons.
+///
+/// Services come from [`list_services`](crate::lifecycle::list_services) and are
+/// queried concurrently; with `auto_start`, daemons that aren't running are started
+/// first. A service that can't be started or reached gets an `Err` entry instead of
+/// failing the whole discovery. Only listing the services directory can fail.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use fgp_daemon::client::discover_all;
+///
+/// for (service, methods) in discover_all(true)? {
+///     match methods {
+///         Ok(methods) => println!("{}: {} methods", service, methods.len()),
+///         Err(e) => eprintln!("{}: {:#}", service, e),
+///     }
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn discover_all(auto_start: bool) -> Result<Vec<(String, Result<Vec<MethodInfo>>)>> {
+    let services = crate::lifecycle::list_services()?;
+    Ok(thread::scope(|scope| {
+        let lookups: Vec<_> = services
+            .into_iter()
+            .map(|service| {
+                let lookup = scope.spawn({
+                    let service = service.clone();
+                    move || discover(&service, auto_start)
+                });
+                (service, lookup)
+            })
+            .collect();
+        lookups
+            .into_iter()
+            .map(|(service, lookup)| {
+                let methods = lookup
+                    .join()
+                    .unwrap_or_else(|_| Err(anyhow::anyhow!("Discovery thread panicked")));
+                (service, methods)
+            })
+            .collect()
+    }))
+}
+
+/// The methods of one installed service, for [`discover_all`].
+fn discover(service_name: &str, auto_start: bool) -> Result<Vec<MethodInfo>> {
+    let client = if auto_start {
+        FgpClient::for_service(service_name)?
+    } else {
+        FgpClient::new(crate::lifecycle::service_socket_path(service_name))?
+    };
+    client
+        .method_list()
+        .with_context(|| format!("Failed to list methods of service '{}'", service_name))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,298 @@
+//! Running several services in one daemon.
+//!
+//! [`CompositeService`] wraps several [`FgpService`] implementations behind one
+//! umbrella name and routes each call to the service whose namespace it names. It
+//! trades the isolation of one process per service for fewer processes.
+//!
+//! Methods are exposed as `<umbrella>.<service>.<method>`: the umbrella prefix is
+//! stripped before dispatch, so each inner service sees the same fully-qualified
+//! `<service>.<method>` names as when it runs on its own.
+//!
+//! # Example
+//!
+//! ```rust
+//! use fgp_daemon::composite::CompositeService;
+//! use fgp_daemon::testing::FgpTestHarness;
+//! use fgp_daemon::FgpService;
+//! use serde_json::{json, Value};
+//! use std::collections::HashMap;
+//!
+//! struct Gmail;
+//!
+//! impl FgpService for Gmail {
+//!     fn name(&self) -> &str { "gmail" }
+//!     fn version(&self) -> &str { "1.0.0" }
+//!
+//!     fn dispatch(&self, method: &str, _: HashMap<String, Value>) -> anyhow::Result<Value> {
+//!         match method {
+//!             "gmail.list" => Ok(json!({"emails": []})),
+//!             _ => anyhow::bail!("Unknown method: {}", method),
+//!         }
+//!     }
+//! }
+//!
+//! let google = CompositeService::new("google", "1.0.0").with_service(Gmail);
+//! let harness = FgpTestHarness::new(google);
+//!
+//! let response = harness.call("google.gmail.list", json!({}));
+//! assert_eq!(response.result.unwrap()["emails"], json!([]));
+//! ```
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::protocol::{error_codes, ServiceError};
+use crate::service::{
+    Capabilities, ConnectionState, FgpService, HealthStatus, MethodInfo, RawJson, RequestContext,
+    ResultChunks, StopReason,
+};
+
+/// An [`FgpService`] that routes calls to inner services by method namespace.
+///
+/// `method_list`, `health_check`, `capabilities` and `error_codes` merge the inner
+/// services' outputs: methods are listed as `<umbrella>.<service>.<method>`, health
+/// checks and capability features are keyed `<service>.<name>`, and error codes are
+/// the union of the declared ones. `supported_versions` is the versions they all share,
+/// and likewise a capability flag is set only when every inner service sets it, with
+/// the smallest `max_request_bytes` any of them declares.
+/// `on_start`, `on_stop` and `reload` run for every inner service in the order they
+/// were added. Each inner service keeps its own connection state.
+pub struct CompositeService {
+    name: String,
+    version: String,
+    services: Vec<Box<dyn FgpService>>,
+}
+
+/// Connection state of a composite: one entry per inner service.
+struct CompositeState(Vec<Option<ConnectionState>>);
+
+impl CompositeService {
+    /// Create an empty composite named `name` (used in the socket path and logging).
+    pub fn new(name: impl Into<String>, version: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            services: Vec::new(),
+        }
+    }
+
+    /// Add a service, routed by its [`name`](FgpService::name).
+    ///
+    /// If two services share a name, calls go to the one added first.
+    pub fn with_service(mut self, service: impl FgpService + 'static) -> Self {
+        self.services.push(Box::new(service));
+        self
+    }
+
+    /// The index of the inner service a method belongs to, and the method name to
+    /// dispatch to it.
+    fn route(&self, method: &str) -> Result<(usize, String)> {
+        let method = method
+            .strip_prefix(self.name.as_str())
+            .and_then(|rest| rest.strip_prefix('.'))
+            .unwrap_or(method);
+        self.services
+            .iter()
+            .position(|service| {
+                method
+                    .strip_prefix(service.name())
+                    .is_some_and(|rest| rest.starts_with('.'))
+            })
+            .map(|index| (index, method.to_string()))
+            .ok_or_else(|| {
+                ServiceError::new(
+                    error_codes::UNKNOWN_METHOD,
+                    format!("No service in '{}' handles method: {}", self.name, method),
+                )
+                .into()
+            })
+    }
+
+    /// The context for a request to the inner service at `index`, with its own
+    /// connection state.
+    fn inner_context(&self, index: usize, ctx: &RequestContext) -> RequestContext {
+        let state = ctx.state::<CompositeState>();
+        RequestContext {
+            connection_state: state.and_then(|state| state.0[index].clone()),
+            ..ctx.clone()
+        }
+    }
+
+    /// `<umbrella>.<service>.<method>` for a method name from an inner `method_list`.
+    fn qualify(&self, service: &dyn FgpService, method: &str) -> String {
+        if method.contains('.') {
+            format!("{}.{}", self.name, method)
+        } else {
+            format!("{}.{}.{}", self.name, service.name(), method)
+        }
+    }
+}
+
+impl FgpService for CompositeService {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn version(&self) -> &str {
+        &self.version
+    }
+
+    fn dispatch(&self, method: &str, params: HashMap<String, Value>) -> Result<Value> {
+        let (index, method) = self.route(method)?;
+        self.services[index].dispatch(&method, params)
+    }
+
+    fn dispatch_with_context(
+        &self,
+        method: &str,
+        params: HashMap<String, Value>,
+        ctx: &RequestContext,
+    ) -> Result<Value> {
+        let (index, method) = self.route(method)?;
+        self.services[index].dispatch_with_context(&method, params, &self.inner_context(index, ctx))
+    }
+
+    fn dispatch_raw(
+        &self,
+        method: &str,
+        params: HashMap<String, Value>,
+        ctx: &RequestContext,
+    ) -> Result<RawJson> {
+        let (index, method) = self.route(method)?;
+        self.services[index].dispatch_raw(&method, params, &self.inner_context(index, ctx))
+    }
+
+    fn streams(&self, method: &str) -> bool {
+        self.route(method)
+            .is_ok_and(|(index, method)| self.services[index].streams(&method))
+    }
+
+    fn dispatch_stream(
+        &self,
+        method: &str,
+        params: HashMap<String, Value>,
+        ctx: &RequestContext,
+    ) -> Result<ResultChunks> {
+        let (index, method) = self.route(method)?;
+        self.services[index].dispatch_stream(&method, params, &self.inner_context(index, ctx))
+    }
+
+    fn supported_versions(&self) -> Vec<u8> {
+        let mut services = self.services.iter();
+        let Some(first) = services.next() else {
+            return vec![crate::PROTOCOL_VERSION];
+        };
+        let mut versions = first.supported_versions();
+        for service in services {
+            let supported = service.supported_versions();
+            versions.retain(|v| supported.contains(v));
+        }
+        versions
+    }
+
+    fn method_list(&self) -> Vec<MethodInfo> {
+        let mut methods = Vec::new();
+        for service in &self.services {
+            for mut method in service.method_list() {
+                method.name = self.qualify(service.as_ref(), &method.name);
+                method.replaced_by = method
+                    .replaced_by
+                    .map(|replacement| self.qualify(service.as_ref(), &replacement));
+                methods.push(method);
+            }
+        }
+        methods
+    }
+
+    fn new_connection_state(&self) -> Option<ConnectionState> {
+        let states: Vec<_> = self
+            .services
+            .iter()
+            .map(|service| service.new_connection_state())
+            .collect();
+        if states.iter().all(Option::is_none) {
+            return None;
+        }
+        Some(std::sync::Arc::new(CompositeState(states)))
+    }
+
+    fn on_start(&self) -> Result<()> {
+        for service in &self.services {
+            service
+                .on_start()
+                .with_context(|| format!("Failed to start service '{}'", service.name()))?;
+        }
+        Ok(())
+    }
+
+    fn on_stop(&self) -> Result<()> {
+        self.on_stop_with_reason(StopReason::MethodRequest)
+    }
+
+    /// Stops every inner service, even if an earlier one fails; returns the first error.
+    fn on_stop_with_reason(&self, reason: StopReason) -> Result<()> {
+        let mut result = Ok(());
+        for service in &self.services {
+            let stopped = service
+                .on_stop_with_reason(reason)
+                .with_context(|| format!("Failed to stop service '{}'", service.name()));
+            if result.is_ok() {
+                result = stopped;
+            }
+        }
+        result
+    }
+
+    fn reload(&self) -> Result<()> {
+        for service in &self.services {
+            service
+                .reload()
+                .with_context(|| format!("Failed to reload service '{}'", service.name()))?;
+        }
+        Ok(())
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        let inner: Vec<Capabilities> = self.services.iter().map(|s| s.capabilities()).collect();
+        let all = |flag: fn(&Capabilities) -> bool| !inner.is_empty() && inner.iter().all(flag);
+        Capabilities {
+            streaming: all(|c| c.streaming),
+            batching: all(|c| c.batching),
+            compression: all(|c| c.compression),
+            max_request_bytes: inner.iter().filter_map(|c| c.max_request_bytes).min(),
+            features: self
+                .services
+                .iter()
+                .zip(&inner)
+                .flat_map(|(service, capabilities)| {
+                    capabilities.features.iter().map(|(name, value)| {
+                        (format!("{}.{}", service.name(), name), value.clone())
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    fn error_codes(&self) -> Vec<&str> {
+        let mut codes = Vec::new();
+        for code in self.services.iter().flat_map(|s| s.error_codes()) {
+            if !codes.contains(&code) {
+                codes.push(code);
+            }
+        }
+        codes
+    }
+
+    fn health_check(&self) -> HashMap<String, HealthStatus> {
+        self.services
+            .iter()
+            .flat_map(|service| {
+                service
+                    .health_check()
+                    .into_iter()
+                    .map(|(check, status)| (format!("{}.{}", service.name(), check), status))
+            })
+            .collect()
+    }
+}
@@ -48,26 +48,35 @@
 //! ```
 
 pub mod client;
+pub mod composite;
 pub mod lifecycle;
 pub mod logging;
 pub mod protocol;
 pub mod schema;
 pub mod server;
 pub mod service;
+pub mod testing;
 
 #[cfg(feature = "python")]
 pub mod python;
 
 // Re-exports for convenience
-pub use client::FgpClient;
-pub use schema::{to_anthropic, to_mcp, to_openai, McpTool, SchemaBuilder};
+pub use client::{FgpClient, FgpClientBuilder};
+pub use composite::CompositeService;
 pub use lifecycle::{
-    cleanup_socket, daemonize, fgp_services_dir, is_service_running, service_pid_path,
-    service_socket_path, start_service, start_service_with_timeout, stop_service, write_pid_file,
+    cleanup_socket, daemonize, fgp_services_dir, is_service_running, list_services,
+    service_pid_path, service_ready_path, service_socket_path, service_state, service_state_path,
+    start_service, start_service_with_launcher, start_service_with_timeout, stop_service,
+    stop_service_with_timeout, write_pid_file, DaemonizeConfig, ServiceState,
 };
-pub use protocol::{ErrorInfo, Request, Response, ResponseMeta};
-pub use server::FgpServer;
+pub use protocol::{ErrorInfo, FramingMode, Request, Response, ResponseMeta, ServiceError};
+pub use schema::{
+    to_anthropic, to_anthropic_with_examples, to_json_schema_document, to_mcp, to_mcp_response,
+    to_openai, to_openai_with_examples, to_typescript, McpTool, McpToolsList, SchemaBuilder,
+};
+pub use server::{FgpServer, FgpServerBuilder};
 pub use service::FgpService;
+pub use testing::FgpTestHarness;
 
 #[cfg(feature = "python")]
 pub use python::PythonModule;
@@ -75,5 +84,11 @@ pub use python::PythonModule;
 /// Protocol version constant
 pub const PROTOCOL_VERSION: u8 = 1;
 
+/// Version of the fgp-daemon SDK this daemon was built with
+pub const SDK_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Git commit of the SDK build, if `FGP_GIT_SHA` was set at compile time
+pub const GIT_SHA: Option<&str> = option_env!("FGP_GIT_SHA");
+
 /// Default socket base path
 pub const DEFAULT_SOCKET_BASE: &str = "~/.fgp/services";
@@ -4,15 +4,19 @@
 //! and on-demand service starting.
 
 use anyhow::{bail, Context, Result};
+use std::ffi::OsStr;
 use std::fs;
-use std::io::Write;
+use std::io::{self, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, Stdio};
 use std::time::{Duration, Instant};
 
 /// Daemonize the current process.
 ///
 /// This forks the process, detaches from the terminal, and runs in the background.
+/// Uses [`DaemonizeConfig`] defaults (umask `0o077`, output discarded); use
+/// [`DaemonizeConfig::start`] for more control.
 ///
 /// # Arguments
 /// * `pid_file` - Path to write the daemon's PID (supports `~` expansion)
@@ -28,20 +32,136 @@ use std::time::{Duration, Instant};
 /// # Ok::<(), anyhow::Error>(())
 /// ```
 pub fn daemonize(pid_file: impl AsRef<Path>, working_dir: Option<&Path>) -> Result<()> {
-    let pid_path = expand_path(pid_file.as_ref())?;
+    let mut config = DaemonizeConfig::new(pid_file);
+    if let Some(dir) = working_dir {
+        config = config.working_dir(dir);
+    }
+    config.start()
+}
 
-    // Create parent directory if needed
-    if let Some(parent) = pid_path.parent() {
-        fs::create_dir_all(parent)?;
+/// Options for [`daemonize`], including umask, output redirection and privilege dropping.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use fgp_daemon::lifecycle::DaemonizeConfig;
+///
+/// DaemonizeConfig::for_service("my-service")
+///     .user("fgp")
+///     .group("fgp")
+///     .start()?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct DaemonizeConfig {
+    pid_file: PathBuf,
+    working_dir: PathBuf,
+    umask: u32,
+    output_file: Option<PathBuf>,
+    user: Option<String>,
+    group: Option<String>,
+}
+
+impl DaemonizeConfig {
+    /// Default umask: files the daemon creates are private to its user.
+    pub const DEFAULT_UMASK: u32 = 0o077;
+
+    /// Config writing the PID to `pid_file` (supports `~` expansion).
+    pub fn new(pid_file: impl AsRef<Path>) -> Self {
+        Self {
+            pid_file: pid_file.as_ref().to_path_buf(),
+            working_dir: PathBuf::from("/"),
+            umask: Self::DEFAULT_UMASK,
+            output_file: None,
+            user: None,
+            group: None,
+        }
     }
 
-    let daemonize = daemonize::Daemonize::new()
-        .pid_file(&pid_path)
-        .working_directory(working_dir.unwrap_or(Path::new("/")));
+    /// Config using the service's standard PID file, with stdout/stderr appended to
+    /// its log file.
+    pub fn for_service(service_name: &str) -> Self {
+        Self::new(service_pid_path(service_name))
+            .redirect_output(crate::logging::log_file_path(service_name))
+    }
 
-    daemonize.start().context("Failed to daemonize process")?;
+    /// Working directory for the daemon (defaults to `/`).
+    pub fn working_dir(mut self, dir: impl AsRef<Path>) -> Self {
+        self.working_dir = dir.as_ref().to_path_buf();
+        self
+    }
 
-    Ok(())
+    /// File mode creation mask (defaults to [`DEFAULT_UMASK`](Self::DEFAULT_UMASK)).
+    pub fn umask(mut self, umask: u32) -> Self {
+        self.umask = umask;
+        self
+    }
+
+    /// Append stdout and stderr to `path` instead of discarding them.
+    pub fn redirect_output(mut self, path: impl AsRef<Path>) -> Self {
+        self.output_file = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Switch to this user (name or numeric uid) after forking. Requires root.
+    pub fn user(mut self, user: impl Into<String>) -> Self {
+        self.user = Some(user.into());
+        self
+    }
+
+    /// Switch to this group (name or numeric gid) after forking. Requires root.
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
+    /// Fork, detach and apply the configuration. Returns in the daemon process.
+    pub fn start(self) -> Result<()> {
+        let pid_path = expand_path(&self.pid_file)?;
+
+        // Create parent directory if needed
+        if let Some(parent) = pid_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut daemonize = daemonize::Daemonize::new()
+            .pid_file(&pid_path)
+            .working_directory(&self.working_dir)
+            .umask(self.umask);
+
+        if let Some(output_file) = &self.output_file {
+            let output_path = expand_path(output_file)?;
+            if let Some(parent) = output_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let stdout = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&output_path)
+                .with_context(|| format!("Failed to open output file {:?}", output_path))?;
+            let stderr = stdout.try_clone()?;
+            daemonize = daemonize.stdout(stdout).stderr(stderr);
+        }
+
+        if let Some(user) = &self.user {
+            daemonize = match user.parse::<u32>() {
+                Ok(uid) => daemonize.user(uid),
+                Err(_) => daemonize.user(user.as_str()),
+            };
+            // Let the unprivileged daemon remove its own PID file
+            daemonize = daemonize.chown_pid_file(true);
+        }
+        if let Some(group) = &self.group {
+            daemonize = match group.parse::<u32>() {
+                Ok(gid) => daemonize.group(gid),
+                Err(_) => daemonize.group(group.as_str()),
+            };
+        }
+
+        daemonize.start().context("Failed to daemonize process")?;
+
+        Ok(())
+    }
 }
 
 /// Write a PID file for the current process.
@@ -129,6 +249,109 @@ pub fn cleanup_files(socket_path: impl AsRef<Path>, pid_file: Option<&Path>) ->
     Ok(())
 }
 
+/// The abstract socket name for an `@name` socket path, or `None` for a filesystem path.
+///
+/// Abstract sockets (Linux only) live outside the filesystem and disappear when the
+/// listener closes, so they never leave stale files behind.
+pub(crate) fn abstract_socket_name(path: &Path) -> Option<&[u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().strip_prefix(b"@")
+}
+
+/// Connect to a socket path, including `@name` abstract sockets.
+pub(crate) fn connect_socket(path: &Path) -> io::Result<UnixStream> {
+    match abstract_socket_name(path) {
+        Some(name) => UnixStream::connect_addr(&abstract_socket_addr(name)?),
+        None => UnixStream::connect(path),
+    }
+}
+
+/// How long a liveness probe waits for a daemon to accept a connection.
+const PROBE_CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Connect to a socket path like [`connect_socket`], giving up after `timeout`.
+///
+/// The connect is non-blocking and polled, so a daemon that isn't accepting (for
+/// example one still starting up, or with a full listen backlog) fails with
+/// [`io::ErrorKind::TimedOut`] instead of blocking. The returned stream is blocking.
+pub(crate) fn connect_socket_timeout(path: &Path, timeout: Duration) -> io::Result<UnixStream> {
+    use socket2::{Domain, SockAddr, Socket, Type};
+    use std::os::fd::{AsRawFd, OwnedFd};
+    use std::os::unix::ffi::OsStrExt;
+
+    let address = match abstract_socket_name(path) {
+        Some(name) => {
+            abstract_socket_addr(name)?;
+            SockAddr::unix(OsStr::from_bytes(&[b"\0", name].concat()))?
+        }
+        None => SockAddr::unix(path)?,
+    };
+    let deadline = Instant::now() + timeout;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("Connection to {:?} timed out", path),
+            ));
+        }
+        let socket = Socket::new(Domain::UNIX, Type::STREAM, None)?;
+        socket.set_nonblocking(true)?;
+        match socket.connect(&address) {
+            Ok(()) => {}
+            // Linux refuses UNIX connects while the listen backlog is full; retry
+            // until the deadline
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                std::thread::sleep(remaining.min(Duration::from_millis(10)));
+                continue;
+            }
+            Err(e) if e.raw_os_error() == Some(libc::EINPROGRESS) => {
+                let mut fd = libc::pollfd {
+                    fd: socket.as_raw_fd(),
+                    events: libc::POLLOUT,
+                    revents: 0,
+                };
+                let millis = remaining.as_millis().clamp(1, i32::MAX as u128) as i32;
+                // SAFETY: `fd` is a valid pollfd and the count matches
+                match unsafe { libc::poll(&mut fd, 1, millis) } {
+                    -1 => return Err(io::Error::last_os_error()),
+                    0 => continue,
+                    _ => {
+                        if let Some(e) = socket.take_error()? {
+                            return Err(e);
+                        }
+                    }
+                }
+            }
+            Err(e) => return Err(e),
+        }
+        socket.set_nonblocking(false)?;
+        return Ok(UnixStream::from(OwnedFd::from(socket)));
+    }
+}
+
+/// Bind a listener to a socket path, including `@name` abstract sockets.
+pub(crate) fn bind_socket(path: &Path) -> io::Result<UnixListener> {
+    match abstract_socket_name(path) {
+        Some(name) => UnixListener::bind_addr(&abstract_socket_addr(name)?),
+        None => UnixListener::bind(path),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn abstract_socket_addr(name: &[u8]) -> io::Result<std::os::unix::net::SocketAddr> {
+    use std::os::linux::net::SocketAddrExt;
+    std::os::unix::net::SocketAddr::from_abstract_name(name)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn abstract_socket_addr(_name: &[u8]) -> io::Result<std::os::unix::net::SocketAddr> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Abstract UNIX sockets (@name) are only supported on Linux",
+    ))
+}
+
 /// Expand `~` in path to home directory.
 fn expand_path(path: &Path) -> Result<PathBuf> {
     let path_str = path.to_string_lossy();
@@ -179,6 +402,25 @@ pub fn service_socket_path(service_name: &str) -> PathBuf {
         .join("daemon.sock")
 }
 
+/// Environment variable holding a daemon's socket path, read by
+/// [`FgpServer::from_env`](crate::FgpServer::from_env) and
+/// [`FgpClient::from_env`](crate::FgpClient::from_env).
+pub const SOCKET_ENV: &str = "FGP_SOCKET";
+
+/// Environment variable naming a service whose standard socket path to use when
+/// [`SOCKET_ENV`] isn't set.
+pub const SERVICE_ENV: &str = "FGP_SERVICE";
+
+/// Socket path from the environment: `FGP_SOCKET` if set, otherwise the standard path
+/// of the service named by `FGP_SERVICE`. Empty values count as unset.
+pub fn socket_path_from_env() -> Option<PathBuf> {
+    let var = |name| std::env::var(name).ok().filter(|v| !v.is_empty());
+    match var(SOCKET_ENV) {
+        Some(path) => Some(PathBuf::from(path)),
+        None => var(SERVICE_ENV).map(|name| service_socket_path(&name)),
+    }
+}
+
 /// Standard PID file path for a service.
 pub fn service_pid_path(service_name: &str) -> PathBuf {
     let base = shellexpand::tilde("~/.fgp/services");
@@ -187,18 +429,122 @@ pub fn service_pid_path(service_name: &str) -> PathBuf {
         .join("daemon.pid")
 }
 
+/// Standard readiness marker path for a service.
+///
+/// Written by [`FgpServer::with_ready_file`](crate::FgpServer::with_ready_file) once the
+/// daemon is accepting connections.
+pub fn service_ready_path(service_name: &str) -> PathBuf {
+    let base = shellexpand::tilde("~/.fgp/services");
+    PathBuf::from(base.as_ref())
+        .join(service_name)
+        .join("daemon.ready")
+}
+
+/// Standard start-history file path for a service.
+///
+/// Written by [`FgpServer::with_state_file`](crate::FgpServer::with_state_file) each time
+/// the daemon starts; see [`record_start`].
+pub fn service_state_path(service_name: &str) -> PathBuf {
+    let base = shellexpand::tilde("~/.fgp/services");
+    PathBuf::from(base.as_ref())
+        .join(service_name)
+        .join("state.json")
+}
+
+/// Start history kept across daemon restarts, so crash loops show up in `health`.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RestartState {
+    /// Starts after the first one
+    pub restart_count: u64,
+    /// When the daemon first started (RFC 3339)
+    pub first_started_at: String,
+}
+
+/// Read the start history at `path`; `None` if missing or unreadable.
+pub fn read_restart_state(path: impl AsRef<Path>) -> Option<RestartState> {
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Record a daemon start in the history at `path` and return the updated history.
+///
+/// The first start (or one after the file was removed) sets `first_started_at` and a
+/// `restart_count` of 0; every later start increments the count. The file is replaced
+/// atomically, so a crash mid-write never leaves it truncated. Supervisors that
+/// restart the daemon themselves can call this too.
+pub fn record_start(path: impl AsRef<Path>) -> Result<RestartState> {
+    let path = path.as_ref();
+    let state = match read_restart_state(path) {
+        Some(state) => RestartState {
+            restart_count: state.restart_count + 1,
+            ..state
+        },
+        None => RestartState {
+            restart_count: 0,
+            first_started_at: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        },
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, serde_json::to_vec(&state)?)
+        .with_context(|| format!("Failed to write state file: {}", tmp.display()))?;
+    fs::rename(&tmp, path)
+        .with_context(|| format!("Failed to write state file: {}", path.display()))?;
+    Ok(state)
+}
+
 /// Get the FGP services base directory.
 pub fn fgp_services_dir() -> PathBuf {
     let base = shellexpand::tilde("~/.fgp/services");
     PathBuf::from(base.as_ref())
 }
 
+/// Names of the installed services: the directories under [`fgp_services_dir`] that
+/// have a `manifest.json`, sorted.
+///
+/// Returns an empty list if the services directory doesn't exist.
+pub fn list_services() -> Result<Vec<String>> {
+    let dir = fgp_services_dir();
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(e)
+                .with_context(|| format!("Failed to read services directory: {}", dir.display()))
+        }
+    };
+
+    let mut services = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        if !entry.path().join("manifest.json").is_file() {
+            continue;
+        }
+        if let Some(name) = entry.file_name().to_str() {
+            services.push(name.to_string());
+        }
+    }
+    services.sort();
+    Ok(services)
+}
+
 /// Start a daemon service on-demand.
 ///
 /// This function:
 /// 1. Reads the service manifest from `~/.fgp/services/{service}/manifest.json`
 /// 2. Spawns the daemon entrypoint process
-/// 3. Waits for the socket to appear (with timeout)
+/// 3. Waits for the daemon to become ready (with timeout)
+///
+/// A daemon whose manifest sets `"ready_file": true` under `daemon` must write
+/// [`service_ready_path`] (see
+/// [`FgpServer::with_ready_file`](crate::FgpServer::with_ready_file)), and only that
+/// counts as ready: its socket can accept connections before the server has applied
+/// permissions and written its PID file. Other daemons are ready once the readiness
+/// file appears or the socket accepts a connection.
 ///
 /// # Arguments
 /// * `service_name` - Name of the service to start (e.g., "gmail", "browser")
@@ -213,15 +559,85 @@ pub fn fgp_services_dir() -> PathBuf {
 /// # Ok::<(), anyhow::Error>(())
 /// ```
 pub fn start_service(service_name: &str) -> Result<()> {
-    start_service_with_timeout(service_name, Duration::from_secs(5))
+    start_service_with_timeout(service_name, DEFAULT_START_TIMEOUT)
 }
 
+/// How long [`start_service`] waits for the daemon to become ready.
+const DEFAULT_START_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Start a daemon service with a custom timeout.
 ///
 /// # Arguments
 /// * `service_name` - Name of the service to start
 /// * `timeout` - Maximum time to wait for socket to appear
 pub fn start_service_with_timeout(service_name: &str, timeout: Duration) -> Result<()> {
+    start_service_inner(service_name, timeout, &default_launcher)
+}
+
+/// Start a daemon service, spawning it with a custom launcher.
+///
+/// The launcher is called with the entrypoint path and the service directory, after
+/// the manifest has been read and the entrypoint validated, and returns the spawned
+/// process. Use it to stub the spawn in tests or to wrap the entrypoint (e.g. in
+/// `nice` or `firejail`); [`startup_command`] sets up the working directory and
+/// startup log like the default launcher does.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use fgp_daemon::lifecycle::{start_service_with_launcher, startup_command};
+///
+/// start_service_with_launcher("gmail", |entrypoint, service_dir| {
+///     Ok(startup_command("nice", service_dir)?
+///         .args(["-n", "10"])
+///         .arg(entrypoint)
+///         .spawn()?)
+/// })?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn start_service_with_launcher(
+    service_name: &str,
+    launcher: impl Fn(&Path, &Path) -> Result<Child>,
+) -> Result<()> {
+    start_service_inner(service_name, DEFAULT_START_TIMEOUT, &launcher)
+}
+
+/// The launcher used by [`start_service`]: runs the entrypoint via [`startup_command`].
+pub fn default_launcher(entrypoint: &Path, service_dir: &Path) -> Result<Child> {
+    startup_command(entrypoint, service_dir)?
+        .spawn()
+        .context("Failed to start daemon")
+}
+
+/// A command for `program` set up the way daemons are started.
+///
+/// Runs in `service_dir` with stdin closed and stdout/stderr captured to the startup
+/// log (`<service_dir>/logs/startup.log`, see
+/// [`startup_log_path`](crate::logging::startup_log_path)), not the caller's
+/// terminal, so startup failures can be reported.
+pub fn startup_command(program: impl AsRef<OsStr>, service_dir: &Path) -> Result<Command> {
+    let startup_log = crate::logging::startup_log_in(service_dir);
+    if let Some(parent) = startup_log.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let stdout = fs::File::create(&startup_log)
+        .with_context(|| format!("Failed to create {}", startup_log.display()))?;
+    let stderr = stdout.try_clone()?;
+
+    let mut command = Command::new(program);
+    command
+        .current_dir(service_dir)
+        .stdin(Stdio::null())
+        .stdout(stdout)
+        .stderr(stderr);
+    Ok(command)
+}
+
+fn start_service_inner(
+    service_name: &str,
+    timeout: Duration,
+    launcher: &dyn Fn(&Path, &Path) -> Result<Child>,
+) -> Result<()> {
     let service_dir = fgp_services_dir().join(service_name);
 
     // Check if service is installed
@@ -236,13 +652,21 @@ pub fn start_service_with_timeout(service_name: &str, timeout: Duration) -> Resu
     // Check if already running
     let socket_path = service_socket_path(service_name);
     if socket_path.exists() {
-        // Try to connect to see if it's actually running
-        if std::os::unix::net::UnixStream::connect(&socket_path).is_ok() {
-            tracing::debug!("Service '{}' is already running", service_name);
-            return Ok(());
-        } else {
-            // Stale socket, remove it
-            let _ = fs::remove_file(&socket_path);
+        // Try to connect to see if it's actually running. A daemon too busy to accept
+        // in time is still running, so only a refused connect means a stale socket.
+        match connect_socket_timeout(&socket_path, PROBE_CONNECT_TIMEOUT) {
+            Ok(_) => {
+                tracing::debug!("Service '{}' is already running", service_name);
+                return Ok(());
+            }
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {
+                tracing::debug!("Service '{}' is running but busy", service_name);
+                return Ok(());
+            }
+            Err(_) => {
+                // Stale socket, remove it
+                let _ = fs::remove_file(&socket_path);
+            }
         }
     }
 
@@ -260,41 +684,80 @@ pub fn start_service_with_timeout(service_name: &str, timeout: Duration) -> Resu
     if !entrypoint_path.exists() {
         bail!("Daemon entrypoint not found: {}", entrypoint_path.display());
     }
+    let uses_ready_file = manifest["daemon"]["ready_file"].as_bool().unwrap_or(false);
 
     // Security: Validate entrypoint is executable
     validate_entrypoint(&entrypoint_path)?;
 
     tracing::info!("Starting service '{}'...", service_name);
 
-    // Start as background process with stdout/stderr suppressed
-    // to prevent output from corrupting TUI or other callers
-    let _child = Command::new(&entrypoint_path)
-        .current_dir(&service_dir)
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()
-        .context("Failed to start daemon")?;
+    // Remove any leftover readiness marker so we only observe the new daemon's signal
+    let ready_path = service_ready_path(service_name);
+    let _ = fs::remove_file(&ready_path);
 
-    // Wait for socket to appear with timeout
+    // Clear the last run's output, which a custom launcher may not overwrite
+    let startup_log = crate::logging::startup_log_path(service_name);
+    let _ = fs::remove_file(&startup_log);
+    let mut child = launcher(&entrypoint_path, &service_dir)?;
+
+    // Wait for the readiness file or, for daemons that don't declare one, for the
+    // socket to accept connections
     let start = Instant::now();
     while start.elapsed() < timeout {
-        if socket_path.exists() {
+        if ready_path.exists() {
+            tracing::info!("Service '{}' signalled ready", service_name);
+            return Ok(());
+        }
+        if !uses_ready_file && socket_path.exists() {
             // Verify we can connect
-            if std::os::unix::net::UnixStream::connect(&socket_path).is_ok() {
+            if connect_socket_timeout(&socket_path, PROBE_CONNECT_TIMEOUT).is_ok() {
                 tracing::info!("Service '{}' started successfully", service_name);
                 return Ok(());
             }
         }
+        // A failed exit means the daemon died during startup. (A zero exit may just
+        // be the parent of a self-daemonizing process, so keep waiting then.)
+        if let Ok(Some(status)) = child.try_wait() {
+            if !status.success() {
+                bail!(
+                    "Service '{}' exited during startup ({}){}",
+                    service_name,
+                    status,
+                    startup_output(&startup_log)
+                );
+            }
+        }
         std::thread::sleep(Duration::from_millis(50));
     }
 
     bail!(
-        "Service '{}' started but socket not ready within {:?}",
+        "Service '{}' started but {} within {:?}{}",
         service_name,
-        timeout
+        if uses_ready_file {
+            "did not signal ready"
+        } else {
+            "socket not ready"
+        },
+        timeout,
+        startup_output(&startup_log)
     )
 }
 
+/// Number of trailing startup output lines included in start errors.
+const STARTUP_OUTPUT_LINES: usize = 20;
+
+/// Format the tail of a daemon's startup output for an error message.
+fn startup_output(startup_log: &Path) -> String {
+    match crate::logging::tail_file(startup_log, STARTUP_OUTPUT_LINES, None) {
+        Ok(tail) if !tail.lines.is_empty() => format!(
+            "\n--- last output ({}) ---\n{}",
+            startup_log.display(),
+            tail.lines.join("\n")
+        ),
+        _ => String::new(),
+    }
+}
+
 /// Stop a daemon service.
 ///
 /// Sends SIGTERM to the daemon process and cleans up socket/PID files.
@@ -320,14 +783,7 @@ pub fn stop_service(service_name: &str) -> Result<()> {
         if is_process_running(pid) {
             tracing::info!("Stopping service '{}' (PID: {})...", service_name, pid);
 
-            let expected = read_entrypoint_name(service_name)?;
-            if !pid_matches_process(pid, expected.as_deref()) {
-                bail!(
-                    "Refusing to stop PID {}: process does not match expected entrypoint '{}'",
-                    pid,
-                    expected.unwrap_or_else(|| "unknown".to_string())
-                );
-            }
+            check_service_pid(service_name, pid)?;
 
             // Send SIGTERM
             unsafe {
@@ -342,8 +798,106 @@ pub fn stop_service(service_name: &str) -> Result<()> {
     // Clean up files
     let _ = fs::remove_file(&socket_path);
     let _ = fs::remove_file(&pid_path);
+    let _ = fs::remove_file(service_ready_path(service_name));
+
+    tracing::info!("Service '{}' stopped", service_name);
+    Ok(())
+}
+
+/// Stop a daemon service, force-killing it if it doesn't exit within `grace`.
+///
+/// Asks the daemon to stop over its socket (falling back to SIGTERM), then polls the
+/// PID from its PID file until the process exits. If it is still alive after `grace`,
+/// it is sent SIGKILL. Socket and PID files are cleaned up afterwards. A PID is only
+/// signalled once it has been checked against the service's entrypoint.
+///
+/// Returns `true` if SIGKILL was needed. Without a running PID there is nothing to
+/// wait for, so only the stop request is sent.
+///
+/// # Arguments
+/// * `service_name` - Name of the service to stop
+/// * `grace` - How long to wait for a graceful exit
+pub fn stop_service_with_timeout(service_name: &str, grace: Duration) -> Result<bool> {
+    let socket_path = service_socket_path(service_name);
+    let pid_path = service_pid_path(service_name);
+
+    let pid = read_pid_file(&pid_path).filter(|&pid| is_process_running(pid));
+
+    // A hung daemon mustn't hold up the stop request past the grace period
+    let stop_requested = socket_path.exists()
+        && crate::client::FgpClient::new(&socket_path)
+            .and_then(|client| client.with_timeout(grace.max(STOP_POLL_INTERVAL)).stop())
+            .is_ok_and(|response| response.ok);
+
+    let Some(pid) = pid else {
+        if !stop_requested {
+            let _ = fs::remove_file(&socket_path);
+            let _ = fs::remove_file(&pid_path);
+        }
+        return Ok(false);
+    };
+
+    if !stop_requested {
+        tracing::info!("Stopping service '{}' (PID: {})...", service_name, pid);
+        check_service_pid(service_name, pid)?;
+        unsafe {
+            libc::kill(pid as i32, libc::SIGTERM);
+        }
+    }
+
+    let forced = !wait_for_exit(pid, grace);
+    if forced {
+        tracing::warn!(
+            "Service '{}' (PID: {}) did not exit within {:?}, sending SIGKILL",
+            service_name,
+            pid,
+            grace
+        );
+        check_service_pid(service_name, pid)?;
+        unsafe {
+            libc::kill(pid as i32, libc::SIGKILL);
+        }
+        wait_for_exit(pid, SIGKILL_WAIT);
+    }
+
+    let _ = fs::remove_file(&socket_path);
+    let _ = fs::remove_file(&pid_path);
+    let _ = fs::remove_file(service_ready_path(service_name));
 
     tracing::info!("Service '{}' stopped", service_name);
+    Ok(forced)
+}
+
+/// How often [`stop_service_with_timeout`] checks whether the daemon has exited.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// How long to wait for the process to disappear after SIGKILL.
+const SIGKILL_WAIT: Duration = Duration::from_secs(1);
+
+/// Poll until `pid` exits, returning whether it did within `timeout`.
+fn wait_for_exit(pid: u32, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if !is_process_running(pid) {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(STOP_POLL_INTERVAL);
+    }
+}
+
+/// Refuse to signal a PID that doesn't belong to the service's entrypoint.
+fn check_service_pid(service_name: &str, pid: u32) -> Result<()> {
+    let expected = read_entrypoint_name(service_name)?;
+    if !pid_matches_process(pid, expected.as_deref()) {
+        bail!(
+            "Refusing to stop PID {}: process does not match expected entrypoint '{}'",
+            pid,
+            expected.unwrap_or_else(|| "unknown".to_string())
+        );
+    }
     Ok(())
 }
 
@@ -390,14 +944,49 @@ fn pid_matches_process(pid: u32, expected_name: Option<&str>) -> bool {
 
 /// Check if a service is currently running.
 ///
+/// True when [`service_state`] is [`ServiceState::Running`].
+///
 /// # Arguments
 /// * `service_name` - Name of the service to check
 pub fn is_service_running(service_name: &str) -> bool {
-    let socket_path = service_socket_path(service_name);
-    if socket_path.exists() {
-        std::os::unix::net::UnixStream::connect(&socket_path).is_ok()
-    } else {
-        false
+    service_state(service_name) == ServiceState::Running
+}
+
+/// State of a service, from its socket and PID file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServiceState {
+    /// The socket accepts connections
+    Running,
+    /// The daemon isn't answering but isn't known to have died: the socket file was
+    /// left behind, or the PID file's process is alive (hung, still starting, or a zombie)
+    Stale,
+    /// Nothing answers and the PID file names a process that has exited
+    Crashed,
+    /// No socket and no PID file
+    Stopped,
+}
+
+/// Determine a service's state.
+///
+/// Unlike a plain socket probe, this tells a cleanly stopped service apart from one
+/// that died without cleaning up ([`Crashed`](ServiceState::Crashed)) or one that
+/// left files behind or stopped answering ([`Stale`](ServiceState::Stale)).
+pub fn service_state(service_name: &str) -> ServiceState {
+    state_from_files(
+        &service_socket_path(service_name),
+        &service_pid_path(service_name),
+    )
+}
+
+fn state_from_files(socket_path: &Path, pid_path: &Path) -> ServiceState {
+    if socket_path.exists() && connect_socket_timeout(socket_path, PROBE_CONNECT_TIMEOUT).is_ok() {
+        return ServiceState::Running;
+    }
+    match read_pid_file(pid_path) {
+        Some(pid) if is_process_running(pid) => ServiceState::Stale,
+        Some(_) => ServiceState::Crashed,
+        None if socket_path.exists() => ServiceState::Stale,
+        None => ServiceState::Stopped,
     }
 }
 
@@ -411,6 +1000,36 @@ mod tests {
         assert!(!expanded.to_string_lossy().contains('~'));
     }
 
+    #[test]
+    fn test_daemonize_config_for_service() {
+        let config = DaemonizeConfig::for_service("gmail").umask(0o027);
+
+        assert_eq!(config.pid_file, service_pid_path("gmail"));
+        assert_eq!(
+            config.output_file,
+            Some(crate::logging::log_file_path("gmail"))
+        );
+        assert_eq!(config.umask, 0o027);
+        assert_eq!(config.working_dir, PathBuf::from("/"));
+    }
+
+    #[test]
+    fn test_startup_output_excerpt() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("startup.log");
+
+        assert_eq!(startup_output(&log), "");
+
+        fs::write(
+            &log,
+            "Traceback (most recent call last):\nImportError: boom\n",
+        )
+        .unwrap();
+        let output = startup_output(&log);
+        assert!(output.contains("last output"));
+        assert!(output.ends_with("ImportError: boom"));
+    }
+
     #[test]
     fn test_service_paths() {
         let socket = service_socket_path("gmail");
@@ -418,5 +1037,68 @@ mod tests {
 
         assert!(socket.to_string_lossy().contains("gmail/daemon.sock"));
         assert!(pid.to_string_lossy().contains("gmail/daemon.pid"));
+        assert!(service_ready_path("gmail")
+            .to_string_lossy()
+            .contains("gmail/daemon.ready"));
+    }
+
+    #[test]
+    fn test_record_start() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("svc").join("state.json");
+
+        assert_eq!(read_restart_state(&path), None);
+        let first = record_start(&path).unwrap();
+        assert_eq!(first.restart_count, 0);
+
+        let second = record_start(&path).unwrap();
+        assert_eq!(second.restart_count, 1);
+        assert_eq!(second.first_started_at, first.first_started_at);
+        assert_eq!(read_restart_state(&path), Some(second));
+
+        // A corrupt file starts the history over
+        fs::write(&path, "{").unwrap();
+        assert_eq!(record_start(&path).unwrap().restart_count, 0);
+    }
+
+    #[test]
+    fn test_state_from_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("daemon.sock");
+        let pid = dir.path().join("daemon.pid");
+
+        assert_eq!(state_from_files(&socket, &pid), ServiceState::Stopped);
+
+        // Socket left behind by a daemon that is gone
+        let listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+        assert_eq!(state_from_files(&socket, &pid), ServiceState::Running);
+        drop(listener);
+        assert_eq!(state_from_files(&socket, &pid), ServiceState::Stale);
+        fs::remove_file(&socket).unwrap();
+
+        // A listener that never accepts, with its backlog already full, doesn't block
+        {
+            use socket2::{Domain, SockAddr, Socket, Type};
+
+            let listener = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
+            listener.bind(&SockAddr::unix(&socket).unwrap()).unwrap();
+            listener.listen(0).unwrap();
+            let _pending = UnixStream::connect(&socket).unwrap();
+            let start = Instant::now();
+            assert_eq!(state_from_files(&socket, &pid), ServiceState::Stale);
+            assert!(start.elapsed() < Duration::from_secs(5));
+        }
+
+        // PID file for a live process that isn't answering
+        fs::write(&pid, std::process::id().to_string()).unwrap();
+        assert_eq!(state_from_files(&socket, &pid), ServiceState::Stale);
+
+        // PID file for a process that has exited
+        let mut child = Command::new("true").spawn().unwrap();
+        let exited = child.id();
+        child.wait().unwrap();
+        fs::write(&pid, exited.to_string()).unwrap();
+        fs::remove_file(&socket).unwrap();
+        assert_eq!(state_from_files(&socket, &pid), ServiceState::Crashed);
     }
 }
@@ -8,7 +8,8 @@
 //! use fgp_daemon::logging::init_logging;
 //!
 //! fn main() -> anyhow::Result<()> {
-//!     init_logging("my-service")?;
+//!     // Keep the guard alive until exit so buffered lines are flushed
+//!     let _log_guard = init_logging("my-service")?;
 //!
 //!     tracing::info!("Daemon started");
 //!     // ... daemon code ...
@@ -18,7 +19,40 @@
 
 use anyhow::{Context, Result};
 use std::fs::{self, File};
-use std::path::PathBuf;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Maximum number of bytes read from the end of a log file by [`tail_file`].
+pub const MAX_TAIL_BYTES: u64 = 1024 * 1024;
+
+/// Lines read from the end of a log file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogTail {
+    /// Complete lines, oldest first
+    pub lines: Vec<String>,
+    /// Byte offset just past the last complete line; pass it back to follow the log
+    pub offset: u64,
+}
+
+/// Flushes buffered log lines when dropped.
+///
+/// Returned by the `init_logging*` functions. Keep it alive for as long as the daemon
+/// logs: dropping it early may lose lines that are still buffered, and with
+/// `init_logging_with_rotation` stops logging altogether. Hand it to
+/// [`FgpServer::with_log_guard`](crate::FgpServer::with_log_guard) to have it dropped
+/// once the server has shut down.
+#[must_use = "dropping the guard flushes and may stop logging"]
+#[derive(Default)]
+pub struct LogGuard {
+    #[cfg(feature = "log-rotation")]
+    _worker: Option<tracing_appender::non_blocking::WorkerGuard>,
+}
+
+impl std::fmt::Debug for LogGuard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogGuard").finish_non_exhaustive()
+    }
+}
 
 /// Get the standard log directory for a service.
 pub fn log_dir(service_name: &str) -> PathBuf {
@@ -32,32 +66,133 @@ pub fn log_file_path(service_name: &str) -> PathBuf {
     log_dir(service_name).join("daemon.log")
 }
 
+/// Get the file capturing a daemon's stdout/stderr when started by
+/// [`start_service`](crate::lifecycle::start_service).
+pub fn startup_log_path(service_name: &str) -> PathBuf {
+    startup_log_in(&crate::lifecycle::fgp_services_dir().join(service_name))
+}
+
+/// The startup log of the service installed in `service_dir`.
+pub(crate) fn startup_log_in(service_dir: &Path) -> PathBuf {
+    service_dir.join("logs").join("startup.log")
+}
+
+/// Read the last `max_lines` complete lines of a log file.
+///
+/// At most [`MAX_TAIL_BYTES`] are read from the end of the file. When `offset` is
+/// given (from a previous [`LogTail`]), the first `max_lines` lines written after it
+/// are returned instead, reading at most [`MAX_TAIL_BYTES`] from there; the returned
+/// offset is just past the last line returned, so following the log never skips
+/// lines. If the file has shrunk since (truncation or rotation), reading restarts
+/// from the tail. A trailing line without a newline is left for the next call.
+pub fn tail_file(path: &Path, max_lines: usize, offset: Option<u64>) -> Result<LogTail> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let (start, end) = match offset {
+        Some(offset) if offset <= len => (offset, len.min(offset + MAX_TAIL_BYTES)),
+        _ => (len.saturating_sub(MAX_TAIL_BYTES), len),
+    };
+
+    file.seek(SeekFrom::Start(start))?;
+    let mut buf = Vec::with_capacity((end - start) as usize);
+    file.take(end - start).read_to_end(&mut buf)?;
+
+    if offset == Some(start) {
+        return Ok(follow_lines(&buf, start, end < len, max_lines));
+    }
+
+    // Only hand out complete lines so the returned offset is always a line boundary.
+    let complete = buf.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+    let text = String::from_utf8_lossy(&buf[..complete]);
+    let mut lines: Vec<&str> = text.lines().collect();
+
+    // Starting mid-file means the first line is partial.
+    if start > 0 && !lines.is_empty() {
+        lines.remove(0);
+    }
+
+    let skip = lines.len().saturating_sub(max_lines);
+    Ok(LogTail {
+        lines: lines[skip..].iter().map(|l| l.to_string()).collect(),
+        offset: start + complete as u64,
+    })
+}
+
+/// The first `max_lines` complete lines of `buf`, read from `start` in the file.
+///
+/// `more` says the file continues past `buf`. A line too long to fit in `buf` is then
+/// returned in pieces rather than holding up the caller forever.
+fn follow_lines(buf: &[u8], start: u64, more: bool, max_lines: usize) -> LogTail {
+    let mut lines = Vec::new();
+    let mut consumed = 0;
+    for (i, _) in buf.iter().enumerate().filter(|&(_, &b)| b == b'\n') {
+        if lines.len() == max_lines {
+            break;
+        }
+        let line = &buf[consumed..i];
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        lines.push(String::from_utf8_lossy(line).into_owned());
+        consumed = i + 1;
+    }
+    if lines.is_empty() && more && max_lines > 0 {
+        lines.push(String::from_utf8_lossy(buf).into_owned());
+        consumed = buf.len();
+    }
+    LogTail {
+        lines,
+        offset: start + consumed as u64,
+    }
+}
+
 /// Initialize file logging for a daemon.
 ///
-/// Sets up a tracing subscriber that writes JSON-formatted logs to:
+/// Sets up a tracing subscriber that writes plain-text logs to:
 /// `~/.fgp/services/<service_name>/logs/daemon.log`
 ///
+/// Use [`init_logging_json`] for JSON lines.
+///
 /// # Arguments
 /// * `service_name` - The name of the service (used for log directory)
 ///
 /// # Example
 /// ```rust,no_run
 /// use fgp_daemon::logging::init_logging;
-/// init_logging("gmail")?;
+/// let _log_guard = init_logging("gmail")?;
 /// # Ok::<(), anyhow::Error>(())
 /// ```
-pub fn init_logging(service_name: &str) -> Result<()> {
-    let log_dir = log_dir(service_name);
-    fs::create_dir_all(&log_dir).context("Failed to create log directory")?;
-
-    let log_path = log_dir.join("daemon.log");
-    let file = File::create(&log_path).context("Failed to create log file")?;
+pub fn init_logging(service_name: &str) -> Result<LogGuard> {
+    init_logging_with_filter(service_name, "info")
+}
 
+/// Initialize file logging with a custom default filter.
+///
+/// Like [`init_logging`], but `default_filter` (an
+/// [`EnvFilter`](tracing_subscriber::EnvFilter) directive such as `"warn"` or
+/// `"warn,my_service::sync=debug"`) is used instead of `"info"` when `RUST_LOG` is
+/// unset. `RUST_LOG` still takes precedence when set.
+///
+/// # Example
+/// ```rust,no_run
+/// use fgp_daemon::logging::init_logging_with_filter;
+/// let _log_guard = init_logging_with_filter("gmail", "warn,gmail::sync=debug")?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn init_logging_with_filter(service_name: &str, default_filter: &str) -> Result<LogGuard> {
     // Use tracing_subscriber to write to file
     use tracing_subscriber::prelude::*;
     use tracing_subscriber::{fmt, EnvFilter};
 
-    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let filter = match EnvFilter::try_from_default_env() {
+        Ok(filter) => filter,
+        Err(_) => EnvFilter::try_new(default_filter)
+            .with_context(|| format!("Invalid log filter '{}'", default_filter))?,
+    };
+
+    let log_dir = log_dir(service_name);
+    fs::create_dir_all(&log_dir).context("Failed to create log directory")?;
+
+    let log_path = log_dir.join("daemon.log");
+    let file = File::create(&log_path).context("Failed to create log file")?;
 
     let subscriber = tracing_subscriber::registry().with(filter).with(
         fmt::layer()
@@ -71,35 +206,487 @@ pub fn init_logging(service_name: &str) -> Result<()> {
 
     tracing::subscriber::set_global_default(subscriber).context("Failed to set subscriber")?;
 
-    Ok(())
+    Ok(LogGuard::default())
 }
 
 /// Initialize file logging with rotation (daily).
 ///
 /// Similar to `init_logging` but rotates log files daily.
 /// Older logs are kept as `daemon.log.YYYY-MM-DD`.
+///
+/// Lines are written by a background thread; the returned guard must be kept alive,
+/// and dropping it flushes what is still queued.
 #[cfg(feature = "log-rotation")]
-pub fn init_logging_with_rotation(service_name: &str) -> Result<()> {
+pub fn init_logging_with_rotation(service_name: &str) -> Result<LogGuard> {
     use tracing_appender::rolling::{RollingFileAppender, Rotation};
 
     let log_dir = log_dir(service_name);
     fs::create_dir_all(&log_dir).context("Failed to create log directory")?;
 
     let file_appender = RollingFileAppender::new(Rotation::DAILY, &log_dir, "daemon.log");
+    let (writer, worker) = tracing_appender::non_blocking(file_appender);
+
+    use tracing_subscriber::prelude::*;
+    use tracing_subscriber::{fmt, EnvFilter};
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+    let subscriber = tracing_subscriber::registry().with(filter).with(
+        fmt::layer()
+            .with_writer(writer)
+            .with_ansi(false)
+            .with_target(true),
+    );
+
+    tracing::subscriber::set_global_default(subscriber).context("Failed to set subscriber")?;
+
+    Ok(LogGuard {
+        _worker: Some(worker),
+    })
+}
+
+/// Initialize file logging with one JSON object per line.
+///
+/// Writes to the same file as [`init_logging`], for log shippers that need structured
+/// input. Each line looks like:
+///
+/// ```json
+/// {"timestamp":"2026-01-14T10:00:00.000000Z","level":"DEBUG","target":"fgp_daemon::server",
+///  "fields":{"message":"Request complete","method":"gmail.list","id":"abc","server_ms":1.2},
+///  "spans":[{"name":"connection"}]}
+/// ```
+///
+/// `spans` lists the enclosing spans, outermost first, and is omitted outside spans.
+/// See [`JsonFormat`] to use the format with a different writer.
+pub fn init_logging_json(service_name: &str) -> Result<LogGuard> {
+    use tracing_subscriber::prelude::*;
+    use tracing_subscriber::{fmt, EnvFilter};
+
+    let log_dir = log_dir(service_name);
+    fs::create_dir_all(&log_dir).context("Failed to create log directory")?;
+
+    let log_path = log_dir.join("daemon.log");
+    let file = File::create(&log_path).context("Failed to create log file")?;
+
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+
+    let subscriber = tracing_subscriber::registry().with(filter).with(
+        fmt::layer()
+            .with_writer(file)
+            .fmt_fields(JsonFields)
+            .event_format(JsonFormat),
+    );
+
+    tracing::subscriber::set_global_default(subscriber).context("Failed to set subscriber")?;
+
+    Ok(LogGuard::default())
+}
+
+/// Event format writing one JSON object per line (see [`init_logging_json`]).
+///
+/// Pair it with [`JsonFields`] so span fields are recorded as JSON too:
+///
+/// ```rust
+/// use fgp_daemon::logging::{JsonFields, JsonFormat};
+/// use tracing_subscriber::prelude::*;
+///
+/// let subscriber = tracing_subscriber::registry().with(
+///     tracing_subscriber::fmt::layer()
+///         .with_writer(std::io::stderr)
+///         .fmt_fields(JsonFields)
+///         .event_format(JsonFormat),
+/// );
+/// tracing::subscriber::set_global_default(subscriber)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonFormat;
+
+/// Field formatter recording span fields as a JSON object, for use with [`JsonFormat`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonFields;
+
+impl<S, N> tracing_subscriber::fmt::FormatEvent<S, N> for JsonFormat
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    N: for<'a> tracing_subscriber::fmt::FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &tracing_subscriber::fmt::FmtContext<'_, S, N>,
+        mut writer: tracing_subscriber::fmt::format::Writer<'_>,
+        event: &tracing::Event<'_>,
+    ) -> std::fmt::Result {
+        use tracing_subscriber::fmt::FormattedFields;
+
+        let metadata = event.metadata();
+        let mut fields = JsonVisitor::default();
+        event.record(&mut fields);
+
+        let mut line = serde_json::Map::new();
+        line.insert(
+            "timestamp".into(),
+            chrono::Utc::now()
+                .to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+                .into(),
+        );
+        line.insert("level".into(), metadata.level().as_str().into());
+        line.insert("target".into(), metadata.target().into());
+        line.insert("fields".into(), serde_json::Value::Object(fields.0));
+
+        if let Some(scope) = ctx.event_scope() {
+            let spans: Vec<serde_json::Value> = scope
+                .from_root()
+                .map(|span| {
+                    let mut entry = serde_json::Map::new();
+                    entry.insert("name".into(), span.name().into());
+                    if let Some(fields) = span.extensions().get::<FormattedFields<N>>() {
+                        if let Ok(serde_json::Value::Object(fields)) =
+                            serde_json::from_str(&fields.fields)
+                        {
+                            entry.extend(fields);
+                        }
+                    }
+                    serde_json::Value::Object(entry)
+                })
+                .collect();
+            line.insert("spans".into(), spans.into());
+        }
+
+        writeln!(writer, "{}", serde_json::Value::Object(line))
+    }
+}
+
+impl<'writer> tracing_subscriber::fmt::FormatFields<'writer> for JsonFields {
+    fn format_fields<R: tracing_subscriber::field::RecordFields>(
+        &self,
+        mut writer: tracing_subscriber::fmt::format::Writer<'writer>,
+        fields: R,
+    ) -> std::fmt::Result {
+        let mut visitor = JsonVisitor::default();
+        fields.record(&mut visitor);
+        write!(writer, "{}", serde_json::Value::Object(visitor.0))
+    }
+
+    fn add_fields(
+        &self,
+        current: &'writer mut tracing_subscriber::fmt::FormattedFields<Self>,
+        fields: &tracing::span::Record<'_>,
+    ) -> std::fmt::Result {
+        let mut visitor = match serde_json::from_str(&current.fields) {
+            Ok(serde_json::Value::Object(existing)) => JsonVisitor(existing),
+            _ => JsonVisitor::default(),
+        };
+        fields.record(&mut visitor);
+        current.fields = serde_json::Value::Object(visitor.0).to_string();
+        Ok(())
+    }
+}
+
+/// Collects tracing fields into a JSON object, keeping numbers and booleans typed.
+#[derive(Default)]
+struct JsonVisitor(serde_json::Map<String, serde_json::Value>);
+
+impl tracing::field::Visit for JsonVisitor {
+    fn record_f64(&mut self, field: &tracing::field::Field, value: f64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &tracing::field::Field, value: i64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &tracing::field::Field, value: u64) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &tracing::field::Field, value: bool) {
+        self.0.insert(field.name().into(), value.into());
+    }
+
+    fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+        self.0.insert(field.name().into(), value.into());
+    }
 
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        self.0
+            .insert(field.name().into(), format!("{:?}", value).into());
+    }
+}
+
+/// Initialize file logging with size-based rotation.
+///
+/// Like [`init_logging`], but appends to `daemon.log` and rolls it over once a write
+/// would take it past `max_bytes`. Rolled files are kept as `daemon.log.1` (newest)
+/// to `daemon.log.<keep_files>`; older ones are deleted.
+///
+/// # Example
+/// ```rust,no_run
+/// use fgp_daemon::logging::init_logging_with_size_rotation;
+/// // Keep at most ~60 MB of logs: the live file plus five 10 MB archives
+/// let _log_guard = init_logging_with_size_rotation("gmail", 10 * 1024 * 1024, 5)?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn init_logging_with_size_rotation(
+    service_name: &str,
+    max_bytes: u64,
+    keep_files: usize,
+) -> Result<LogGuard> {
+    use std::sync::Arc;
     use tracing_subscriber::prelude::*;
     use tracing_subscriber::{fmt, EnvFilter};
 
+    let log_dir = log_dir(service_name);
+    fs::create_dir_all(&log_dir).context("Failed to create log directory")?;
+
+    let writer = SizeRotatingFile::new(log_dir.join("daemon.log"), max_bytes, keep_files)?;
+
     let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
 
     let subscriber = tracing_subscriber::registry().with(filter).with(
         fmt::layer()
-            .with_writer(file_appender)
+            .with_writer(Arc::new(writer))
             .with_ansi(false)
             .with_target(true),
     );
 
     tracing::subscriber::set_global_default(subscriber).context("Failed to set subscriber")?;
 
-    Ok(())
+    Ok(LogGuard::default())
+}
+
+/// Log file that rolls over when it would grow past a size limit.
+///
+/// Used by [`init_logging_with_size_rotation`]; wrap it in an `Arc` to use it as the
+/// writer of a custom subscriber. Each write goes to a single file, so a log line
+/// written in one call is never split across files.
+#[derive(Debug)]
+pub struct SizeRotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    keep_files: usize,
+    /// The open log file and its current length
+    current: std::sync::Mutex<(File, u64)>,
+}
+
+impl SizeRotatingFile {
+    /// Open `path` for appending, rolling over once it would exceed `max_bytes`.
+    pub fn new(path: impl Into<PathBuf>, max_bytes: u64, keep_files: usize) -> Result<Self> {
+        let path = path.into();
+        let file = open_append(&path).context("Failed to open log file")?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_bytes,
+            keep_files,
+            current: std::sync::Mutex::new((file, len)),
+        })
+    }
+
+    /// Path of archive `index` (1 is the newest).
+    fn archive_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+
+    /// Shift the archives up by one, move the live file to `.1` and start a new one.
+    fn rotate(&self) -> io::Result<File> {
+        // Drop archives beyond the limit, including ones left by a larger earlier limit
+        let mut index = self.keep_files.max(1);
+        while fs::remove_file(self.archive_path(index)).is_ok() {
+            index += 1;
+        }
+        for index in (1..self.keep_files).rev() {
+            ignore_not_found(fs::rename(
+                self.archive_path(index),
+                self.archive_path(index + 1),
+            ))?;
+        }
+        if self.keep_files > 0 {
+            fs::rename(&self.path, self.archive_path(1))?;
+        } else {
+            ignore_not_found(fs::remove_file(&self.path))?;
+        }
+        open_append(&self.path)
+    }
+}
+
+impl Write for &SizeRotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut current = self.current.lock().unwrap_or_else(|e| e.into_inner());
+        let (file, len) = &mut *current;
+        if *len > 0 && *len + buf.len() as u64 > self.max_bytes {
+            *file = self.rotate()?;
+            *len = 0;
+        }
+        let written = file.write(buf)?;
+        *len += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.current
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .0
+            .flush()
+    }
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    fs::OpenOptions::new().create(true).append(true).open(path)
+}
+
+fn ignore_not_found(result: io::Result<()>) -> io::Result<()> {
+    match result {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_file_follow() {
+        let path = std::env::temp_dir().join(format!("fgp-tail-{}.log", std::process::id()));
+        let mut file = File::create(&path).unwrap();
+        writeln!(file, "one\ntwo\nthree").unwrap();
+
+        let tail = tail_file(&path, 2, None).unwrap();
+        assert_eq!(tail.lines, vec!["two", "three"]);
+
+        write!(file, "four\nfi").unwrap();
+        let next = tail_file(&path, 100, Some(tail.offset)).unwrap();
+        assert_eq!(next.lines, vec!["four"]);
+
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_tail_file_follow_backlog() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.log");
+        let mut file = File::create(&path).unwrap();
+        writeln!(file, "start").unwrap();
+        let tail = tail_file(&path, 10, None).unwrap();
+
+        // More lines than one poll returns pile up between polls
+        for i in 0..25 {
+            writeln!(file, "line {}", i).unwrap();
+        }
+        let mut offset = tail.offset;
+        let mut lines = Vec::new();
+        loop {
+            let next = tail_file(&path, 10, Some(offset)).unwrap();
+            if next.lines.is_empty() {
+                break;
+            }
+            assert!(next.lines.len() <= 10);
+            lines.extend(next.lines);
+            offset = next.offset;
+        }
+        let expected: Vec<String> = (0..25).map(|i| format!("line {}", i)).collect();
+        assert_eq!(lines, expected);
+        assert_eq!(offset, fs::metadata(&path).unwrap().len());
+    }
+
+    #[test]
+    fn test_size_rotating_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.log");
+        let log = SizeRotatingFile::new(&path, 10, 2).unwrap();
+
+        for line in [
+            "aaaa\n", "bbbb\n", "cccc\n", "dddd\n", "eeee\n", "ffff\n", "gggg\n",
+        ] {
+            (&log).write_all(line.as_bytes()).unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "gggg\n");
+        assert_eq!(
+            fs::read_to_string(log.archive_path(1)).unwrap(),
+            "eeee\nffff\n"
+        );
+        assert_eq!(
+            fs::read_to_string(log.archive_path(2)).unwrap(),
+            "cccc\ndddd\n"
+        );
+        assert!(!log.archive_path(3).exists());
+
+        // A smaller limit prunes the extra archives on the next rollover
+        drop(log);
+        let log = SizeRotatingFile::new(&path, 5, 1).unwrap();
+        (&log).write_all(b"hhhh\n").unwrap();
+        assert_eq!(fs::read_to_string(log.archive_path(1)).unwrap(), "gggg\n");
+        assert!(!log.archive_path(2).exists());
+    }
+
+    #[test]
+    fn test_json_format() {
+        use std::sync::{Arc, Mutex};
+        use tracing_subscriber::prelude::*;
+
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Buffer {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::registry().with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(move || writer.clone())
+                .fmt_fields(JsonFields)
+                .event_format(JsonFormat),
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("outside");
+            let span = tracing::info_span!("connection", peer = 7, method = tracing::field::Empty);
+            let _entered = span.enter();
+            span.record("method", "gmail.list");
+            tracing::warn!(id = "abc", server_ms = 1.5, ok = false, "Request complete");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+
+        assert_eq!(lines[0]["level"], "INFO");
+        assert_eq!(
+            lines[0]["fields"],
+            serde_json::json!({"message": "outside"})
+        );
+        assert!(lines[0].get("spans").is_none());
+
+        assert_eq!(lines[1]["level"], "WARN");
+        assert_eq!(lines[1]["target"], module_path!());
+        assert_eq!(
+            lines[1]["fields"],
+            serde_json::json!({
+                "message": "Request complete",
+                "id": "abc",
+                "server_ms": 1.5,
+                "ok": false,
+            })
+        );
+        assert_eq!(
+            lines[1]["spans"],
+            serde_json::json!([{"name": "connection", "peer": 7, "method": "gmail.list"}])
+        );
+    }
 }
@@ -1,11 +1,14 @@
 //! FGP protocol types for NDJSON communication over UNIX socket.
 //!
 //! This module defines the core request/response types for the Fast Gateway Protocol.
-//! All messages are serialized as single-line JSON (NDJSON format).
+//! All messages are serialized as single-line JSON (NDJSON format), or optionally
+//! length-prefixed (see [`FramingMode`]).
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::PROTOCOL_VERSION;
 
@@ -15,6 +18,8 @@ use crate::PROTOCOL_VERSION;
 /// ```json
 /// {"id":"abc123","v":1,"method":"gmail.list","params":{"limit":10}}
 /// ```
+///
+/// Optional fields can be left out with `..Default::default()`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Request {
     /// Unique request ID (UUID recommended)
@@ -26,6 +31,19 @@ pub struct Request {
     /// Method parameters (flexible key-value map)
     #[serde(default)]
     pub params: HashMap<String, serde_json::Value>,
+    /// Relative time budget in milliseconds, measured from arrival at the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Absolute deadline as Unix epoch milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deadline_ms: Option<u64>,
+    /// Free-form trace/correlation ID, echoed back in [`ResponseMeta`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+    /// Fields the protocol doesn't define (e.g. a misspelled `parms`), kept so the
+    /// server can report them
+    #[serde(flatten, skip_serializing_if = "HashMap::is_empty")]
+    pub extra: HashMap<String, serde_json::Value>,
 }
 
 /// NDJSON response from daemon to client.
@@ -72,13 +90,124 @@ pub struct ErrorInfo {
     pub details: Option<serde_json::Value>,
 }
 
+impl ErrorInfo {
+    /// Retry hints from `details`, if the server supplied any.
+    ///
+    /// The server attaches `{"timeout_ms": N, "retriable": true}` to `TIMEOUT` errors
+    /// and `{"retry_after_ms": N, "retriable": true}` to requests refused while it shuts
+    /// down.
+    pub fn retry_hint(&self) -> Option<RetryHint> {
+        let details = self.details.as_ref()?;
+        Some(RetryHint {
+            retriable: details.get("retriable")?.as_bool()?,
+            timeout_ms: details.get("timeout_ms").and_then(|v| v.as_u64()),
+            retry_after_ms: details.get("retry_after_ms").and_then(|v| v.as_u64()),
+        })
+    }
+}
+
+impl From<&anyhow::Error> for ErrorInfo {
+    /// Error info for a failed dispatch, keeping the code of a [`ServiceError`].
+    fn from(error: &anyhow::Error) -> Self {
+        match error.downcast_ref::<ServiceError>() {
+            Some(e) => Self {
+                code: e.code.clone(),
+                message: e.message.clone(),
+                details: e.details.clone(),
+            },
+            None => Self {
+                code: error_codes::INTERNAL_ERROR.to_string(),
+                message: error.to_string(),
+                details: None,
+            },
+        }
+    }
+}
+
+/// Retry hints carried in an error's `details`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryHint {
+    /// Whether retrying the request is advisable
+    pub retriable: bool,
+    /// Time limit the request ran into, in milliseconds
+    pub timeout_ms: Option<u64>,
+    /// How long to wait before retrying, in milliseconds
+    pub retry_after_ms: Option<u64>,
+}
+
+/// Dispatch error carrying an FGP error code.
+///
+/// Return it from [`FgpService::dispatch`](crate::FgpService::dispatch) (e.g. via
+/// `Err(ServiceError::new(..).into())`) to choose the response's error code; any other
+/// error is reported as `INTERNAL_ERROR`.
+#[derive(Debug, Clone)]
+pub struct ServiceError {
+    /// Error code (UPPER_SNAKE_CASE)
+    pub code: String,
+    /// Human-readable error message
+    pub message: String,
+    /// Additional error details (optional)
+    pub details: Option<serde_json::Value>,
+}
+
+impl ServiceError {
+    /// Create an error with a code and message.
+    pub fn new(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    /// Attach error details.
+    pub fn with_details(mut self, details: serde_json::Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+}
+
+impl std::fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
 /// Response metadata.
+///
+/// Optional fields can be left out with `..Default::default()`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResponseMeta {
-    /// Server execution time in milliseconds
+    /// Server execution time in milliseconds, rounded to microseconds by the
+    /// [`Response`] constructors
     pub server_ms: f64,
     /// Protocol version
     pub protocol_v: u8,
+    /// Trace/correlation ID echoed from the request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_id: Option<String>,
+    /// The server closes the connection after this response; reconnect for more requests
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub connection_close: bool,
+    /// Non-fatal notices about the request, such as use of a deprecated method
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Size of the request line in bytes, excluding the newline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub req_bytes: Option<u64>,
+    /// Size of this response line in bytes (including this field), excluding the newline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resp_bytes: Option<u64>,
+    /// The result was served from the server's response cache
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cached: bool,
+    /// More frames for this request follow: the result is one chunk (a JSON array)
+    /// of a streamed result, which ends with the first frame without this flag
+    /// (see `FgpService::dispatch_stream`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
 }
 
 impl Request {
@@ -89,6 +218,10 @@ impl Request {
             v: PROTOCOL_VERSION,
             method: method.into(),
             params,
+            timeout_ms: None,
+            deadline_ms: None,
+            trace_id: None,
+            extra: HashMap::new(),
         }
     }
 
@@ -97,9 +230,78 @@ impl Request {
         Self::new(method, HashMap::new())
     }
 
+    /// Set a relative time budget for the request.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout_ms = Some(timeout.as_millis() as u64);
+        self
+    }
+
+    /// Set an absolute deadline (Unix epoch milliseconds) for the request.
+    pub fn with_deadline_ms(mut self, deadline_ms: u64) -> Self {
+        self.deadline_ms = Some(deadline_ms);
+        self
+    }
+
+    /// Attach a trace/correlation ID to the request.
+    pub fn with_trace_id(mut self, trace_id: impl Into<String>) -> Self {
+        self.trace_id = Some(trace_id.into());
+        self
+    }
+
+    /// Remaining time budget, combining `timeout_ms` and `deadline_ms`.
+    ///
+    /// Returns `None` when the request carries no deadline, and `Some(Duration::ZERO)`
+    /// when the deadline has already passed.
+    pub fn time_budget(&self) -> Option<Duration> {
+        let from_deadline = self.deadline_ms.map(|deadline| {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0);
+            Duration::from_millis(deadline.saturating_sub(now))
+        });
+        let from_timeout = self.timeout_ms.map(Duration::from_millis);
+
+        match (from_deadline, from_timeout) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Check that the request is well-formed before sending it.
+    ///
+    /// The id and method must be non-empty and `v` a protocol version this SDK speaks
+    /// (1 to [`PROTOCOL_VERSION`]). Fails with an `INVALID_REQUEST` [`ServiceError`],
+    /// the error the daemon would answer with, without the round trip.
+    pub fn validate(&self) -> Result<()> {
+        let problem = if self.id.is_empty() {
+            "Request id is empty".to_string()
+        } else if self.method.trim().is_empty() {
+            "Request method is empty".to_string()
+        } else if !(1..=PROTOCOL_VERSION).contains(&self.v) {
+            format!("Unsupported protocol version: {}", self.v)
+        } else {
+            return Ok(());
+        };
+        Err(ServiceError::new(error_codes::INVALID_REQUEST, problem).into())
+    }
+
     /// Parse request from NDJSON line.
     pub fn from_ndjson_line(line: &str) -> Result<Self> {
-        serde_json::from_str(line).context("Failed to parse request JSON")
+        serde_json::from_str(strip_line_ending(line)).context("Failed to parse request JSON")
+    }
+
+    /// Best-effort extraction of the `id` from a line that failed to parse as a request.
+    ///
+    /// Succeeds when the line is valid JSON with a string or numeric `id`, even if other
+    /// fields (`method`, `v`, ...) are missing or have the wrong type.
+    pub fn recover_id(line: &str) -> Option<String> {
+        let value: serde_json::Value = serde_json::from_str(strip_line_ending(line)).ok()?;
+        match value.get("id")? {
+            serde_json::Value::String(id) => Some(id.clone()),
+            serde_json::Value::Number(id) => Some(id.to_string()),
+            _ => None,
+        }
     }
 
     /// Serialize request to NDJSON line.
@@ -109,6 +311,30 @@ impl Request {
     }
 }
 
+/// A request with a fresh id and no method or params.
+impl Default for Request {
+    fn default() -> Self {
+        Self::new(String::new(), HashMap::new())
+    }
+}
+
+/// Metadata for the current protocol version, with no timing or flags set.
+impl Default for ResponseMeta {
+    fn default() -> Self {
+        Self {
+            server_ms: 0.0,
+            protocol_v: PROTOCOL_VERSION,
+            trace_id: None,
+            connection_close: false,
+            warnings: vec![],
+            req_bytes: None,
+            resp_bytes: None,
+            cached: false,
+            partial: false,
+        }
+    }
+}
+
 impl Response {
     /// Create a success response.
     pub fn success(id: impl Into<String>, result: serde_json::Value, server_ms: f64) -> Self {
@@ -118,8 +344,8 @@ impl Response {
             result: Some(result),
             error: None,
             meta: ResponseMeta {
-                server_ms,
-                protocol_v: PROTOCOL_VERSION,
+                server_ms: round_ms(server_ms),
+                ..Default::default()
             },
         }
     }
@@ -141,8 +367,8 @@ impl Response {
                 details: None,
             }),
             meta: ResponseMeta {
-                server_ms,
-                protocol_v: PROTOCOL_VERSION,
+                server_ms: round_ms(server_ms),
+                ..Default::default()
             },
         }
     }
@@ -165,15 +391,21 @@ impl Response {
                 details: Some(details),
             }),
             meta: ResponseMeta {
-                server_ms,
-                protocol_v: PROTOCOL_VERSION,
+                server_ms: round_ms(server_ms),
+                ..Default::default()
             },
         }
     }
 
+    /// Echo a trace/correlation ID in the response metadata.
+    pub fn with_trace_id(mut self, trace_id: Option<String>) -> Self {
+        self.meta.trace_id = trace_id;
+        self
+    }
+
     /// Parse response from NDJSON line.
     pub fn from_ndjson_line(line: &str) -> Result<Self> {
-        serde_json::from_str(line).context("Failed to parse response JSON")
+        serde_json::from_str(strip_line_ending(line)).context("Failed to parse response JSON")
     }
 
     /// Serialize response to NDJSON line.
@@ -183,6 +415,304 @@ impl Response {
     }
 }
 
+/// Strip a trailing `\n` or `\r\n` so CRLF-terminated lines parse like LF ones.
+fn strip_line_ending(line: &str) -> &str {
+    let line = line.strip_suffix('\n').unwrap_or(line);
+    line.strip_suffix('\r').unwrap_or(line)
+}
+
+/// Round a duration in milliseconds to whole microseconds, so response lines carry
+/// `12.333` rather than `12.333333333333334`.
+fn round_ms(ms: f64) -> f64 {
+    (ms * 1000.0).round() / 1000.0
+}
+
+/// How messages are delimited on a connection.
+///
+/// Both ends must use the same mode; there is no negotiation. NDJSON stays the
+/// default so a daemon can be driven by hand with `nc -U`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FramingMode {
+    /// One JSON document per `\n`-terminated line
+    #[default]
+    Ndjson,
+    /// A big-endian `u32` byte length followed by that many bytes of JSON, which
+    /// avoids scanning large payloads for line breaks
+    LengthPrefixed,
+}
+
+impl FramingMode {
+    /// Write one JSON document (without a line terminator) as a frame.
+    pub fn write_frame<W: Write>(self, writer: &mut W, json: &str) -> io::Result<()> {
+        match self {
+            Self::Ndjson => {
+                writer.write_all(json.as_bytes())?;
+                writer.write_all(b"\n")
+            }
+            Self::LengthPrefixed => {
+                let len = u32::try_from(json.len()).map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "Frame exceeds u32::MAX bytes")
+                })?;
+                writer.write_all(&len.to_be_bytes())?;
+                writer.write_all(json.as_bytes())
+            }
+        }
+    }
+}
+
+/// Default cap on a single NDJSON line, including its line terminator (16 MiB).
+pub const MAX_LINE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Line reader for NDJSON streams, shared by the server and client.
+///
+/// With [`FramingMode::LengthPrefixed`] it reads length-prefixed frames instead, under
+/// the same cap and error rules. In NDJSON mode it skips blank lines, strips `\n` /
+/// `\r\n` terminators, treats a final line without a terminator as complete, and
+/// rejects lines longer than the cap with [`io::ErrorKind::InvalidData`] (the
+/// oversized line is consumed, so reading can continue). Invalid UTF-8 is reported the
+/// same way. `Ok(None)` means EOF.
+///
+/// # Example
+///
+/// ```rust
+/// use fgp_daemon::protocol::NdjsonReader;
+///
+/// let input = "{\"id\":\"1\",\"v\":1,\"method\":\"health\"}\r\n\n";
+/// let mut reader = NdjsonReader::new(input.as_bytes());
+/// assert_eq!(reader.read_request()?.unwrap().method, "health");
+/// assert!(reader.read_request()?.is_none());
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub struct NdjsonReader<R> {
+    reader: BufReader<R>,
+    max_line_bytes: usize,
+    framing: FramingMode,
+}
+
+impl<R: Read> NdjsonReader<R> {
+    /// Wrap a reader, capping lines at [`MAX_LINE_BYTES`].
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            max_line_bytes: MAX_LINE_BYTES,
+            framing: FramingMode::Ndjson,
+        }
+    }
+
+    /// Read messages framed with `framing` instead of NDJSON lines.
+    pub fn with_framing(mut self, framing: FramingMode) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// The framing this reader expects.
+    pub fn framing(&self) -> FramingMode {
+        self.framing
+    }
+
+    /// Set the maximum line length in bytes, including the line terminator.
+    pub fn with_max_line_bytes(mut self, max_line_bytes: usize) -> Self {
+        self.max_line_bytes = max_line_bytes;
+        self
+    }
+
+    /// Get a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        self.reader.get_ref()
+    }
+
+    /// Get a mutable reference to the underlying reader (e.g. to write to a socket).
+    pub fn get_mut(&mut self) -> &mut R {
+        self.reader.get_mut()
+    }
+
+    /// Read the next non-blank line without its terminator (or the next frame, with
+    /// length-prefixed framing).
+    pub fn next_line(&mut self) -> io::Result<Option<String>> {
+        if self.framing == FramingMode::LengthPrefixed {
+            return self.read_frame();
+        }
+        loop {
+            let Some(mut line) = self.read_raw_line()? else {
+                return Ok(None);
+            };
+            if line.last() == Some(&b'\n') {
+                line.pop();
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+            }
+
+            let line = String::from_utf8(line)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            if !line.trim().is_empty() {
+                return Ok(Some(line));
+            }
+        }
+    }
+
+    /// Read and parse the next request.
+    pub fn read_request(&mut self) -> Result<Option<Request>> {
+        self.next_line()?
+            .map(|line| Request::from_ndjson_line(&line))
+            .transpose()
+    }
+
+    /// Read and parse the next response.
+    pub fn read_response(&mut self) -> Result<Option<Response>> {
+        self.next_line()?
+            .map(|line| Response::from_ndjson_line(&line))
+            .transpose()
+    }
+
+    /// Read up to and including the next `\n`, enforcing the line cap.
+    fn read_raw_line(&mut self) -> io::Result<Option<Vec<u8>>> {
+        let mut line = Vec::new();
+        let mut oversized = false;
+        loop {
+            let available = match self.reader.fill_buf() {
+                Ok(available) => available,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if available.is_empty() {
+                if line.is_empty() && !oversized {
+                    return Ok(None);
+                }
+                break;
+            }
+
+            let (used, done) = match available.iter().position(|&b| b == b'\n') {
+                Some(i) => (i + 1, true),
+                None => (available.len(), false),
+            };
+            if !oversized && line.len() + used > self.max_line_bytes {
+                oversized = true;
+                line = Vec::new();
+            }
+            if !oversized {
+                line.extend_from_slice(&available[..used]);
+            }
+            self.reader.consume(used);
+            if done {
+                break;
+            }
+        }
+
+        if oversized {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("NDJSON line exceeds {} bytes", self.max_line_bytes),
+            ));
+        }
+        Ok(Some(line))
+    }
+}
+
+impl<R: Read> NdjsonReader<R> {
+    /// Read the next length-prefixed frame, enforcing the size cap.
+    fn read_frame(&mut self) -> io::Result<Option<String>> {
+        let mut prefix = [0u8; 4];
+        let mut filled = 0;
+        while filled < prefix.len() {
+            match self.reader.read(&mut prefix[filled..]) {
+                Ok(0) if filled == 0 => return Ok(None),
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let len = u32::from_be_bytes(prefix) as usize;
+        if len > self.max_line_bytes {
+            // Skip the payload so the next frame can be read
+            let skipped = io::copy(&mut (&mut self.reader).take(len as u64), &mut io::sink())?;
+            if skipped < len as u64 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Frame exceeds {} bytes", self.max_line_bytes),
+            ));
+        }
+
+        let mut payload = vec![0u8; len];
+        self.reader.read_exact(&mut payload)?;
+        String::from_utf8(payload)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl<R: Read> Iterator for NdjsonReader<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_line().transpose()
+    }
+}
+
+/// Send a request and read its response over any NDJSON stream.
+///
+/// The request is written with the reader's [`framing`](NdjsonReader::framing). Uses
+/// the same framing as [`FgpClient`](crate::FgpClient) by default, so a daemon can be driven
+/// over a pipe (stdio, SSH) as well as a socket. The response id is not checked
+/// against the request's. EOF before a response is an
+/// [`io::ErrorKind::UnexpectedEof`] error.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use fgp_daemon::protocol::{exchange, NdjsonReader, Request};
+/// use std::process::{Command, Stdio};
+///
+/// let mut child = Command::new("ssh")
+///     .args(["host", "socat", "-", "UNIX-CONNECT:.fgp/services/gmail/daemon.sock"])
+///     .stdin(Stdio::piped())
+///     .stdout(Stdio::piped())
+///     .spawn()?;
+/// let mut writer = child.stdin.take().unwrap();
+/// let mut reader = NdjsonReader::new(child.stdout.take().unwrap());
+///
+/// let response = exchange(&mut reader, &mut writer, &Request::simple("health"))?;
+/// assert!(response.ok);
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn exchange<R: Read, W: Write>(
+    reader: &mut NdjsonReader<R>,
+    writer: &mut W,
+    request: &Request,
+) -> Result<Response> {
+    reader
+        .framing()
+        .write_frame(writer, &serde_json::to_string(request)?)?;
+    writer.flush()?;
+    reader
+        .read_response()?
+        .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof).into())
+}
+
+/// Params key holding the argument array of a positional call
+/// (see [`FgpClient::call_positional`](crate::FgpClient::call_positional)).
+pub const POSITIONAL_ARGS_KEY: &str = "args";
+
+/// Convert JSON params into a request params map.
+///
+/// Objects map directly, `null` becomes empty, and any other value is wrapped as
+/// `{"value": ...}`.
+pub(crate) fn params_map(params: serde_json::Value) -> HashMap<String, serde_json::Value> {
+    match params {
+        serde_json::Value::Object(map) => map.into_iter().collect(),
+        serde_json::Value::Null => HashMap::new(),
+        _ => {
+            let mut map = HashMap::new();
+            map.insert("value".into(), params);
+            map
+        }
+    }
+}
+
 /// Standard error codes as constants.
 pub mod error_codes {
     pub const INVALID_REQUEST: &str = "INVALID_REQUEST";
@@ -193,12 +723,138 @@ pub mod error_codes {
     pub const UNAUTHORIZED: &str = "UNAUTHORIZED";
     pub const TIMEOUT: &str = "TIMEOUT";
     pub const SERVICE_UNAVAILABLE: &str = "SERVICE_UNAVAILABLE";
+
+    /// Every standard code above.
+    pub const STANDARD: &[&str] = &[
+        INVALID_REQUEST,
+        UNKNOWN_METHOD,
+        INVALID_PARAMS,
+        INTERNAL_ERROR,
+        NOT_FOUND,
+        UNAUTHORIZED,
+        TIMEOUT,
+        SERVICE_UNAVAILABLE,
+    ];
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_request_validate() {
+        assert!(Request::simple("health").validate().is_ok());
+
+        let invalid = [
+            Request {
+                id: String::new(),
+                ..Request::simple("health")
+            },
+            Request::simple(" "),
+            Request {
+                v: 0,
+                ..Request::simple("health")
+            },
+            Request {
+                v: PROTOCOL_VERSION + 1,
+                ..Request::simple("health")
+            },
+        ];
+        for request in invalid {
+            let err = request.validate().unwrap_err();
+            let error = err.downcast_ref::<ServiceError>().unwrap();
+            assert_eq!(error.code, error_codes::INVALID_REQUEST);
+        }
+    }
+
+    #[test]
+    fn test_crlf_lines() {
+        let req =
+            Request::from_ndjson_line("{\"id\":\"1\",\"v\":1,\"method\":\"health\"}\r\n").unwrap();
+        assert_eq!(req.method, "health");
+        assert_eq!(
+            Request::recover_id("{\"id\":\"2\"}\r").as_deref(),
+            Some("2")
+        );
+
+        let line = Response::success("3", serde_json::json!({}), 1.0)
+            .to_ndjson_line()
+            .unwrap()
+            .replace('\n', "\r\n");
+        assert_eq!(Response::from_ndjson_line(&line).unwrap().id, "3");
+    }
+
+    #[test]
+    fn test_ndjson_reader_framing() {
+        let input = b"\n  \n{\"a\":1}\r\n{\"b\":2}\n\n{\"c\":3}";
+        let lines: Vec<String> = NdjsonReader::new(&input[..])
+            .collect::<io::Result<_>>()
+            .unwrap();
+        assert_eq!(lines, vec![r#"{"a":1}"#, r#"{"b":2}"#, r#"{"c":3}"#]);
+    }
+
+    #[test]
+    fn test_ndjson_reader_line_cap() {
+        let input = format!("{}\nshort\n{}", "x".repeat(100), "y".repeat(100));
+        let mut reader = NdjsonReader::new(input.as_bytes()).with_max_line_bytes(16);
+
+        let err = reader.next_line().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(reader.next_line().unwrap().as_deref(), Some("short"));
+        assert!(reader.next_line().is_err());
+        assert!(reader.next_line().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_length_prefixed_frames() {
+        let mut input = Vec::new();
+        for json in ["{\"id\":\"1\"}", "line\nbreak", "0123456789abcdef!"] {
+            FramingMode::LengthPrefixed
+                .write_frame(&mut input, json)
+                .unwrap();
+        }
+        assert_eq!(&input[..4], &[0, 0, 0, 10]);
+
+        let mut reader = NdjsonReader::new(&input[..])
+            .with_framing(FramingMode::LengthPrefixed)
+            .with_max_line_bytes(16);
+        assert_eq!(
+            reader.next_line().unwrap().as_deref(),
+            Some("{\"id\":\"1\"}")
+        );
+        assert_eq!(reader.next_line().unwrap().as_deref(), Some("line\nbreak"));
+        let err = reader.next_line().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(reader.next_line().unwrap().is_none());
+
+        // Truncated length prefix
+        let mut reader = NdjsonReader::new(&[0u8, 0][..]).with_framing(FramingMode::LengthPrefixed);
+        assert_eq!(
+            reader.next_line().unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn test_exchange_over_buffers() {
+        let request = Request::simple("health");
+        let input = Response::success(&request.id, serde_json::json!({"ok": true}), 1.0)
+            .to_ndjson_line()
+            .unwrap();
+        let mut reader = NdjsonReader::new(input.as_bytes());
+        let mut written = Vec::new();
+
+        let response = exchange(&mut reader, &mut written, &request).unwrap();
+        assert_eq!(response.id, request.id);
+        assert_eq!(written, request.to_ndjson_line().unwrap().into_bytes());
+
+        let err = exchange(&mut reader, &mut written, &request).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<io::Error>().map(io::Error::kind),
+            Some(io::ErrorKind::UnexpectedEof)
+        );
+    }
+
     #[test]
     fn test_request_serialization() {
         let req = Request::simple("health");
@@ -207,6 +863,37 @@ mod tests {
         assert!(line.contains("\"method\":\"health\""));
     }
 
+    #[test]
+    fn test_request_time_budget() {
+        let req = Request::simple("health");
+        assert!(req.time_budget().is_none());
+        assert!(!req.to_ndjson_line().unwrap().contains("timeout_ms"));
+
+        let req = Request::simple("health").with_timeout(Duration::from_millis(2000));
+        assert_eq!(req.time_budget(), Some(Duration::from_millis(2000)));
+
+        // A deadline in the past leaves no budget, regardless of timeout_ms
+        let req = Request::simple("health")
+            .with_timeout(Duration::from_millis(2000))
+            .with_deadline_ms(1);
+        assert_eq!(req.time_budget(), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_trace_id_round_trip() {
+        let req = Request::simple("health");
+        assert!(!req.to_ndjson_line().unwrap().contains("trace_id"));
+
+        let req = Request::simple("health").with_trace_id("trace-abc");
+        let parsed = Request::from_ndjson_line(&req.to_ndjson_line().unwrap()).unwrap();
+        assert_eq!(parsed.trace_id.as_deref(), Some("trace-abc"));
+
+        let resp =
+            Response::success("1", serde_json::json!({}), 1.0).with_trace_id(parsed.trace_id);
+        let line = resp.to_ndjson_line().unwrap();
+        assert!(line.contains("\"trace_id\":\"trace-abc\""));
+    }
+
     #[test]
     fn test_response_success() {
         let resp = Response::success("123", serde_json::json!({"status": "ok"}), 12.5);
@@ -215,11 +902,38 @@ mod tests {
         assert_eq!(resp.meta.protocol_v, PROTOCOL_VERSION);
     }
 
+    #[test]
+    fn test_server_ms_rounded() {
+        let resp = Response::success("123", serde_json::json!(null), 37.0 / 3.0);
+        assert_eq!(resp.meta.server_ms, 12.333);
+        let line = resp.to_ndjson_line().unwrap();
+        assert!(line.contains("\"server_ms\":12.333,"));
+
+        let resp = Response::error("123", error_codes::NOT_FOUND, "Missing", 0.0004);
+        assert_eq!(resp.meta.server_ms, 0.0);
+    }
+
     #[test]
     fn test_response_error() {
         let resp = Response::error("123", error_codes::NOT_FOUND, "User not found", 5.0);
         assert!(!resp.ok);
         assert!(resp.result.is_none());
         assert_eq!(resp.error.as_ref().unwrap().code, "NOT_FOUND");
+        assert!(resp.error.unwrap().retry_hint().is_none());
+    }
+
+    #[test]
+    fn test_error_retry_hint() {
+        let resp = Response::error_with_details(
+            "123",
+            error_codes::TIMEOUT,
+            "Too slow",
+            serde_json::json!({"timeout_ms": 250, "retriable": true}),
+            250.0,
+        );
+        let hint = resp.error.unwrap().retry_hint().unwrap();
+        assert!(hint.retriable);
+        assert_eq!(hint.timeout_ms, Some(250));
+        assert_eq!(hint.retry_after_ms, None);
     }
 }
@@ -20,7 +20,14 @@
 //!
 //!     def method_list(self) -> list:  # Optional
 //!         """Return list of method info dicts."""
-//!         return [{"name": "my-service.echo", "description": "Echo params back"}]
+//!         return [{
+//!             "name": "my-service.echo",
+//!             "description": "Echo params back",
+//!             "params": [{"name": "message", "type": "string", "description": "Text to echo"}],
+//!             # Optional: "schema" / "returns" (JSON Schema dicts), "examples"
+//!             # ([{"description", "params", "result"}]), "errors" (codes), "deprecated",
+//!             # "replaced_by", "strict_params"
+//!         }]
 //!
 //!     def on_start(self):  # Optional
 //!         """Called when daemon starts."""
@@ -45,16 +52,33 @@
 //! let server = FgpServer::new(module, "~/.fgp/services/gmail/daemon.sock")?;
 //! server.serve()?;
 //! ```
+//!
+//! # Concurrency
+//!
+//! Every call into Python holds the GIL, so dispatches from concurrent connections run
+//! one at a time. Use [`PythonModule::with_max_pending`] to refuse excess requests with
+//! a retriable `SERVICE_UNAVAILABLE` instead of letting them queue. The number of
+//! pending dispatches is reported by `health_check` as `dispatch_queue`.
+//!
+//! A file defining several service classes can be loaded at once with
+//! [`PythonModule::load_all`]; each instance is served on its own socket.
 
 use anyhow::{bail, Context, Result};
 use pyo3::prelude::*;
-use pyo3::types::{PyDict, PyList, PyTuple};
+use pyo3::types::{PyDict, PyList, PyModule, PyTuple, PyType};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 use tracing::{debug, warn};
 
-use crate::service::{FgpService, HealthStatus, MethodInfo, ParamInfo};
+use crate::protocol::{error_codes, ServiceError};
+use crate::service::{FgpService, HealthStatus, MethodExample, MethodInfo, ParamInfo};
+
+/// Retry hint given to dispatches refused because the queue is full: about the time a
+/// typical call takes to release the GIL.
+const BUSY_RETRY_AFTER: Duration = Duration::from_millis(100);
 
 /// A Python module that implements the FGP service interface.
 ///
@@ -66,6 +90,10 @@ pub struct PythonModule {
     name: String,
     /// Cached service version
     version: String,
+    /// Dispatches waiting for or holding the GIL
+    pending: AtomicUsize,
+    /// Limit on `pending` before new dispatches are refused
+    max_pending: Option<usize>,
 }
 
 // SAFETY: PythonModule is Send because we acquire the GIL for all Python operations.
@@ -88,55 +116,94 @@ impl PythonModule {
         let module_path = expand_path(module_path.as_ref())?;
 
         Python::with_gil(|py| {
-            // Add module directory to Python path
-            let sys = py.import("sys")?;
-            let path_attr = sys.getattr("path")?;
-            let path: &Bound<'_, PyList> = path_attr
-                .downcast()
-                .map_err(|e| anyhow::anyhow!("sys.path is not a list: {}", e))?;
-
-            if let Some(parent) = module_path.parent() {
-                path.insert(0, parent.to_string_lossy().as_ref())?;
-            }
-
-            // Get module name from file
-            let module_name = module_path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .ok_or_else(|| anyhow::anyhow!("Invalid module path"))?;
-
-            // Import the module
-            let module = py
-                .import(module_name)
-                .with_context(|| format!("Failed to import Python module: {}", module_name))?;
+            let module = import_module(py, &module_path)?;
 
             // Get the class
             let class = module
                 .getattr(class_name)
                 .with_context(|| format!("Failed to find class '{}' in module", class_name))?;
 
-            // Instantiate the class
-            let instance = class
-                .call0()
-                .with_context(|| format!("Failed to instantiate '{}'", class_name))?;
-
-            // Get name and version
-            let name: String = instance.getattr("name")?.extract()?;
-            let version: String = instance.getattr("version")?.extract()?;
-
-            debug!(
-                module = %module_name,
-                class = %class_name,
-                name = %name,
-                version = %version,
-                "Loaded Python module"
-            );
+            Self::instantiate(&class, class_name)
+        })
+    }
+
+    /// Load every service class defined in a Python file.
+    ///
+    /// A class counts as a service when it is defined in the file itself (not imported)
+    /// and has `name`, `version` and `dispatch` attributes. Classes are instantiated in
+    /// definition order. Serve each returned module on its own socket to run several
+    /// services from one process.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// for module in PythonModule::load_all("~/.fgp/modules/google/google.py")? {
+    ///     let server = FgpServer::for_service(module)?;
+    ///     std::thread::spawn(move || server.serve());
+    /// }
+    /// ```
+    pub fn load_all(module_path: impl AsRef<Path>) -> Result<Vec<Self>> {
+        let module_path = expand_path(module_path.as_ref())?;
 
-            Ok(Self {
-                instance: instance.unbind(),
-                name,
-                version,
-            })
+        Python::with_gil(|py| {
+            let module = import_module(py, &module_path)?;
+            let module_name: String = module.getattr("__name__")?.extract()?;
+
+            let mut modules = Vec::new();
+            for (key, value) in module.dict().iter() {
+                if !value.is_instance_of::<PyType>() {
+                    continue;
+                }
+
+                let defined_here = value
+                    .getattr("__module__")
+                    .and_then(|m| m.extract::<String>())
+                    .map(|m| m == module_name)
+                    .unwrap_or(false);
+                let is_service = ["name", "version", "dispatch"]
+                    .iter()
+                    .all(|attr| value.hasattr(*attr).unwrap_or(false));
+
+                if defined_here && is_service {
+                    let class_name: String = key.extract()?;
+                    modules.push(Self::instantiate(&value, &class_name)?);
+                }
+            }
+
+            if modules.is_empty() {
+                bail!(
+                    "No service classes found in Python module: {}",
+                    module_path.display()
+                );
+            }
+
+            Ok(modules)
+        })
+    }
+
+    /// Instantiate a service class and cache its name and version.
+    fn instantiate(class: &Bound<'_, PyAny>, class_name: &str) -> Result<Self> {
+        let instance = class
+            .call0()
+            .with_context(|| format!("Failed to instantiate '{}'", class_name))?;
+
+        // Get name and version
+        let name: String = instance.getattr("name")?.extract()?;
+        let version: String = instance.getattr("version")?.extract()?;
+
+        debug!(
+            class = %class_name,
+            name = %name,
+            version = %version,
+            "Loaded Python module"
+        );
+
+        Ok(Self {
+            instance: instance.unbind(),
+            name,
+            version,
+            pending: AtomicUsize::new(0),
+            max_pending: None,
         })
     }
 
@@ -163,6 +230,33 @@ impl PythonModule {
         // Default class name is "Module"
         Self::load(&init_path, "Module")
     }
+
+    /// Refuse dispatches with `SERVICE_UNAVAILABLE` once `max` are already pending.
+    ///
+    /// Every dispatch holds the GIL, so requests on concurrent connections run one at a
+    /// time. Without a limit, a burst queues on the GIL with unbounded latency; with one,
+    /// excess requests fail fast with `{"retriable": true, "retry_after_ms": 100}` in the
+    /// error details, so clients can back off (see
+    /// [`FgpClient::call_with_retry`](crate::FgpClient::call_with_retry)). `max` is
+    /// clamped to at least 1.
+    pub fn with_max_pending(mut self, max: usize) -> Self {
+        self.max_pending = Some(max.max(1));
+        self
+    }
+
+    /// Number of dispatches currently waiting for or holding the GIL.
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+}
+
+/// Counts a dispatch in `PythonModule::pending` until dropped.
+struct PendingGuard<'a>(&'a AtomicUsize);
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
 }
 
 impl FgpService for PythonModule {
@@ -175,6 +269,22 @@ impl FgpService for PythonModule {
     }
 
     fn dispatch(&self, method: &str, params: HashMap<String, Value>) -> Result<Value> {
+        let pending = self.pending.fetch_add(1, Ordering::SeqCst) + 1;
+        let _guard = PendingGuard(&self.pending);
+        if let Some(max) = self.max_pending {
+            if pending > max {
+                return Err(ServiceError::new(
+                    error_codes::SERVICE_UNAVAILABLE,
+                    format!("Python dispatch queue is full ({} pending)", max),
+                )
+                .with_details(serde_json::json!({
+                    "retriable": true,
+                    "retry_after_ms": BUSY_RETRY_AFTER.as_millis() as u64,
+                }))
+                .into());
+            }
+        }
+
         Python::with_gil(|py| {
             let instance = self.instance.bind(py);
 
@@ -247,8 +357,26 @@ impl FgpService for PythonModule {
         })
     }
 
+    /// The module's own checks, plus `dispatch_queue` with the number of pending
+    /// dispatches; it's unhealthy while new dispatches are being refused.
     fn health_check(&self) -> HashMap<String, HealthStatus> {
-        Python::with_gil(|py| {
+        // Read before waiting for the GIL, which queued dispatches may hold for a while
+        let pending = self.pending();
+        let queue = match self.max_pending {
+            Some(max) if pending >= max => {
+                HealthStatus::unhealthy(format!("{} pending (limit {})", pending, max))
+            }
+            Some(max) => HealthStatus {
+                message: Some(format!("{} pending (limit {})", pending, max)),
+                ..HealthStatus::healthy()
+            },
+            None => HealthStatus {
+                message: Some(format!("{} pending", pending)),
+                ..HealthStatus::healthy()
+            },
+        };
+
+        let mut checks = Python::with_gil(|py| {
             let instance = self.instance.bind(py);
 
             if !instance.hasattr("health_check").unwrap_or(false) {
@@ -270,7 +398,9 @@ impl FgpService for PythonModule {
                     map
                 }
             }
-        })
+        });
+        checks.insert("dispatch_queue".to_string(), queue);
+        checks
     }
 }
 
@@ -392,10 +522,42 @@ fn parse_method_info(obj: &Bound<'_, PyAny>) -> Result<MethodInfo> {
         vec![]
     };
 
+    let schema = dict.get_item("schema")?.and_then(|s| py_to_json(s).ok());
+
+    let returns = dict.get_item("returns")?.and_then(|r| py_to_json(r).ok());
+
+    let examples = match dict.get_item("examples")?.and_then(|e| py_to_json(e).ok()) {
+        Some(Value::Array(values)) => values
+            .into_iter()
+            .filter_map(|e| serde_json::from_value::<MethodExample>(e).ok())
+            .collect(),
+        _ => vec![],
+    };
+
+    let errors: Vec<String> = dict
+        .get_item("errors")?
+        .and_then(|e| e.extract().ok())
+        .unwrap_or_default();
+
+    let deprecated: bool = dict
+        .get_item("deprecated")?
+        .map(|d| d.extract().unwrap_or(false))
+        .unwrap_or(false);
+
     Ok(MethodInfo {
         name,
         description,
         params,
+        schema,
+        returns,
+        examples,
+        errors,
+        deprecated,
+        replaced_by: dict.get_item("replaced_by")?.and_then(|r| r.extract().ok()),
+        strict_params: dict
+            .get_item("strict_params")?
+            .map(|s| s.extract().unwrap_or(false))
+            .unwrap_or(false),
     })
 }
 
@@ -415,6 +577,8 @@ fn parse_param_info(obj: &Bound<'_, PyAny>) -> Result<ParamInfo> {
         .map(|t| t.extract().unwrap_or_else(|_| "string".to_string()))
         .unwrap_or_else(|| "string".to_string());
 
+    let description: Option<String> = dict.get_item("description")?.and_then(|d| d.extract().ok());
+
     let required: bool = dict
         .get_item("required")?
         .map(|r| r.extract().unwrap_or(false))
@@ -422,11 +586,27 @@ fn parse_param_info(obj: &Bound<'_, PyAny>) -> Result<ParamInfo> {
 
     let default = dict.get_item("default")?.and_then(|d| py_to_json(d).ok());
 
+    let items_type: Option<String> = dict.get_item("items_type")?.and_then(|t| t.extract().ok());
+
+    let items_schema = dict.get_item("items")?.and_then(|i| py_to_json(i).ok());
+
+    let enum_values = dict
+        .get_item("enum")?
+        .and_then(|e| py_to_json(e).ok())
+        .and_then(|v| match v {
+            Value::Array(values) => Some(values),
+            _ => None,
+        });
+
     Ok(ParamInfo {
         name,
         param_type,
+        description,
         required,
         default,
+        items_type,
+        items_schema,
+        enum_values,
     })
 }
 
@@ -462,6 +642,9 @@ fn parse_health_status_map(dict: &Bound<'_, PyDict>) -> HashMap<String, HealthSt
                         ok,
                         latency_ms,
                         message,
+                        last_error: optional_string(status_dict, "last_error"),
+                        last_error_at: optional_string(status_dict, "last_error_at"),
+                        last_checked: optional_string(status_dict, "last_checked"),
                     },
                 );
             }
@@ -471,6 +654,38 @@ fn parse_health_status_map(dict: &Bound<'_, PyDict>) -> HashMap<String, HealthSt
     map
 }
 
+/// Read an optional string entry from a Python dict.
+fn optional_string(dict: &Bound<'_, PyDict>, key: &str) -> Option<String> {
+    dict.get_item(key)
+        .ok()
+        .flatten()
+        .and_then(|v| v.extract().ok())
+}
+
+/// Import a Python file, adding its directory to `sys.path`.
+fn import_module<'py>(py: Python<'py>, module_path: &Path) -> Result<Bound<'py, PyModule>> {
+    // Add module directory to Python path
+    let sys = py.import("sys")?;
+    let path_attr = sys.getattr("path")?;
+    let path: &Bound<'_, PyList> = path_attr
+        .downcast()
+        .map_err(|e| anyhow::anyhow!("sys.path is not a list: {}", e))?;
+
+    if let Some(parent) = module_path.parent() {
+        path.insert(0, parent.to_string_lossy().as_ref())?;
+    }
+
+    // Get module name from file
+    let module_name = module_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid module path"))?;
+
+    // Import the module
+    py.import(module_name)
+        .with_context(|| format!("Failed to import Python module: {}", module_name))
+}
+
 /// Expand `~` in path to home directory.
 fn expand_path(path: &Path) -> Result<std::path::PathBuf> {
     let path_str = path.to_string_lossy();
@@ -481,6 +696,277 @@ fn expand_path(path: &Path) -> Result<std::path::PathBuf> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+    use std::thread;
+    use tempfile::TempDir;
+
+    /// A service whose `slow.block` method sleeps (releasing the GIL) and `slow.fail` raises.
+    const SLOW_MODULE: &str = r#"
+import time
+
+class Module:
+    name = "slow"
+    version = "1.0.0"
+
+    def dispatch(self, method, params):
+        if method == "slow.block":
+            time.sleep(0.3)
+            return {"done": True}
+        if method == "slow.fail":
+            raise ValueError("failed")
+        return {"method": method}
+"#;
+
+    /// Write a Python file; `stem` must be unique per test, as imports are cached by name.
+    fn write_module(dir: &TempDir, stem: &str, source: &str) -> PathBuf {
+        let path = dir.path().join(format!("{}.py", stem));
+        std::fs::write(&path, source).unwrap();
+        path
+    }
+
+    /// Wait until `module` has `count` dispatches pending.
+    fn wait_for_pending(module: &PythonModule, count: usize) {
+        for _ in 0..200 {
+            if module.pending() == count {
+                return;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        panic!("expected {} pending dispatches", count);
+    }
+
+    #[test]
+    fn test_pending_released_after_dispatch() {
+        let dir = TempDir::new().unwrap();
+        let path = write_module(&dir, "fgp_test_pending_released", SLOW_MODULE);
+        let module = PythonModule::load(&path, "Module")
+            .unwrap()
+            .with_max_pending(1);
+
+        let result = module.dispatch("slow.echo", HashMap::new()).unwrap();
+        assert_eq!(result, serde_json::json!({"method": "slow.echo"}));
+        assert_eq!(module.pending(), 0);
+
+        assert!(module.dispatch("slow.fail", HashMap::new()).is_err());
+        assert_eq!(module.pending(), 0);
+    }
+
+    #[test]
+    fn test_max_pending_refuses_dispatch() {
+        let dir = TempDir::new().unwrap();
+        let path = write_module(&dir, "fgp_test_max_pending", SLOW_MODULE);
+        let module = Arc::new(
+            PythonModule::load(&path, "Module")
+                .unwrap()
+                .with_max_pending(1),
+        );
+
+        let blocking = {
+            let module = Arc::clone(&module);
+            thread::spawn(move || module.dispatch("slow.block", HashMap::new()))
+        };
+        wait_for_pending(&module, 1);
+
+        let err = module.dispatch("slow.echo", HashMap::new()).unwrap_err();
+        let err = err.downcast_ref::<ServiceError>().unwrap();
+        assert_eq!(err.code, error_codes::SERVICE_UNAVAILABLE);
+        // The refused dispatch doesn't stay counted
+        assert_eq!(module.pending(), 1);
+
+        assert_eq!(
+            blocking.join().unwrap().unwrap(),
+            serde_json::json!({"done": true})
+        );
+        assert_eq!(module.pending(), 0);
+        assert!(module.dispatch("slow.echo", HashMap::new()).is_ok());
+    }
+
+    #[test]
+    fn test_busy_dispatch_retry_hint_and_health() {
+        let dir = TempDir::new().unwrap();
+        let path = write_module(&dir, "fgp_test_busy_health", SLOW_MODULE);
+        let module = PythonModule::load(&path, "Module")
+            .unwrap()
+            .with_max_pending(1);
+        let harness = crate::testing::FgpTestHarness::new(module);
+        let deep = serde_json::json!({"deep": true});
+
+        let queue =
+            &harness.call("health", deep.clone()).result.unwrap()["services"]["dispatch_queue"];
+        assert_eq!(queue["ok"], true);
+        assert_eq!(queue["message"], "0 pending (limit 1)");
+        // Only a deep check reports it
+        let shallow = harness.call("health", serde_json::json!({}));
+        assert!(shallow.result.unwrap()["services"]
+            .get("dispatch_queue")
+            .is_none());
+
+        thread::scope(|s| {
+            let blocking = s.spawn(|| harness.call("slow.block", serde_json::json!({})));
+            wait_for_pending(harness.service(), 1);
+
+            let refused = harness.call("slow.echo", serde_json::json!({}));
+            let error = refused.error.unwrap();
+            assert_eq!(error.code, error_codes::SERVICE_UNAVAILABLE);
+            assert_eq!(
+                error.details,
+                Some(serde_json::json!({"retriable": true, "retry_after_ms": 100}))
+            );
+
+            let health = harness.call("health", deep.clone()).result.unwrap();
+            assert_eq!(health["status"], "unhealthy");
+            let queue = &health["services"]["dispatch_queue"];
+            assert_eq!(queue["ok"], false);
+            assert_eq!(queue["message"], "1 pending (limit 1)");
+
+            assert!(blocking.join().unwrap().ok);
+        });
+
+        let health = harness.call("health", deep).result.unwrap();
+        assert_eq!(health["services"]["dispatch_queue"]["ok"], true);
+    }
+
+    #[test]
+    fn test_load_all() {
+        let dir = TempDir::new().unwrap();
+        let path = write_module(
+            &dir,
+            "fgp_test_load_all",
+            r#"
+from collections import OrderedDict
+
+class Mail:
+    name = "mail"
+    version = "1.0.0"
+
+    def dispatch(self, method, params):
+        return {"service": self.name}
+
+class Helper:
+    name = "helper"
+
+class Calendar:
+    name = "calendar"
+    version = "2.0.0"
+
+    def dispatch(self, method, params):
+        return {"service": self.name}
+"#,
+        );
+
+        let modules = PythonModule::load_all(&path).unwrap();
+        let names: Vec<_> = modules.iter().map(|m| (m.name(), m.version())).collect();
+        assert_eq!(names, [("mail", "1.0.0"), ("calendar", "2.0.0")]);
+        let result = modules[1]
+            .dispatch("calendar.list", HashMap::new())
+            .unwrap();
+        assert_eq!(result, serde_json::json!({"service": "calendar"}));
+
+        let path = write_module(&dir, "fgp_test_load_all_empty", "class Helper:\n    pass\n");
+        assert!(PythonModule::load_all(&path).is_err());
+    }
+
+    #[test]
+    fn test_method_list_fields() {
+        let dir = TempDir::new().unwrap();
+        let path = write_module(
+            &dir,
+            "fgp_test_method_list",
+            r#"
+class Module:
+    name = "mail"
+    version = "1.0.0"
+
+    def dispatch(self, method, params):
+        return None
+
+    def method_list(self):
+        return [
+            {
+                "name": "mail.send",
+                "description": "Send a message",
+                "params": [
+                    {"name": "to", "type": "array", "required": True, "items_type": "string",
+                     "items": {"type": "string", "format": "email"}},
+                    {"name": "priority", "type": "string", "default": "normal",
+                     "enum": ["low", "normal", "high"]},
+                ],
+                "schema": {"type": "object", "required": ["to"]},
+                "returns": {"type": "object", "properties": {"id": {"type": "string"}}},
+                "examples": [
+                    {"description": "Send to one recipient", "params": {"to": ["a@b.c"]},
+                     "result": {"id": "1"}},
+                    {"params": {}},
+                ],
+                "errors": ["INVALID_PARAMS", "RATE_LIMITED"],
+                "deprecated": True,
+                "replaced_by": "mail.deliver",
+            },
+            {"name": "mail.list"},
+            {"description": "No name"},
+        ]
+"#,
+        );
+        let module = PythonModule::load(&path, "Module").unwrap();
+
+        let methods = module.method_list();
+        assert_eq!(methods.len(), 2);
+
+        let send = &methods[0];
+        assert_eq!(send.description, "Send a message");
+        assert_eq!(
+            send.schema,
+            Some(serde_json::json!({"type": "object", "required": ["to"]}))
+        );
+        assert_eq!(
+            send.returns.as_ref().unwrap()["properties"]["id"]["type"],
+            "string"
+        );
+        // Examples that don't match `MethodExample` are skipped
+        assert_eq!(send.examples.len(), 1);
+        assert_eq!(send.examples[0].description, "Send to one recipient");
+        assert_eq!(
+            send.examples[0].params,
+            serde_json::json!({"to": ["a@b.c"]})
+        );
+        assert_eq!(
+            send.examples[0].result,
+            Some(serde_json::json!({"id": "1"}))
+        );
+        assert_eq!(send.errors, ["INVALID_PARAMS", "RATE_LIMITED"]);
+        assert!(send.deprecated);
+        assert_eq!(send.replaced_by.as_deref(), Some("mail.deliver"));
+
+        let to = &send.params[0];
+        assert_eq!(to.param_type, "array");
+        assert!(to.required);
+        assert_eq!(to.items_type.as_deref(), Some("string"));
+        assert_eq!(
+            to.items_schema,
+            Some(serde_json::json!({"type": "string", "format": "email"}))
+        );
+        let priority = &send.params[1];
+        assert!(!priority.required);
+        assert_eq!(priority.default, Some(serde_json::json!("normal")));
+        assert_eq!(
+            priority.enum_values,
+            Some(vec![
+                serde_json::json!("low"),
+                serde_json::json!("normal"),
+                serde_json::json!("high")
+            ])
+        );
+
+        // Optional fields fall back to their defaults
+        let list = &methods[1];
+        assert_eq!(list.name, "mail.list");
+        assert!(list.description.is_empty());
+        assert!(list.params.is_empty());
+        assert!(list.schema.is_none() && list.returns.is_none());
+        assert!(list.examples.is_empty() && list.errors.is_empty());
+        assert!(!list.deprecated);
+    }
 
     #[test]
     fn test_json_conversion() {
@@ -2,7 +2,12 @@
 //!
 //! This module provides:
 //! - [`SchemaBuilder`] for ergonomic JSON Schema construction
-//! - Format converters: [`to_openai`], [`to_anthropic`], [`to_mcp`]
+//! - Format converters: [`to_openai`], [`to_anthropic`], [`to_mcp`] (and
+//!   [`to_mcp_response`] for a paginated MCP `tools/list` result); the `_with_examples`
+//!   variants add an example call to each description
+//! - [`to_json_schema_document`] for a single document describing every method
+//! - [`to_typescript`] for TypeScript declarations of every method's params and result
+//! - [`validate`] to check a value against a schema
 //! - Types for rich method documentation
 //!
 //! # Example
@@ -21,8 +26,10 @@
 //!     .build();
 //! ```
 
+use anyhow::{bail, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
+use std::collections::HashMap;
 
 use crate::service::{MethodInfo, ParamInfo};
 
@@ -120,12 +127,82 @@ impl SchemaBuilder {
         self
     }
 
+    /// Add schemas for properties whose names match a regex (`patternProperties`).
+    ///
+    /// Useful for config-style objects where keys are not known up front.
+    pub fn pattern_properties(mut self, patterns: &[(&str, SchemaBuilder)]) -> Self {
+        let mut map = self
+            .schema
+            .get("patternProperties")
+            .and_then(|v| v.as_object())
+            .cloned()
+            .unwrap_or_default();
+
+        for (regex, prop_schema) in patterns {
+            map.insert(regex.to_string(), prop_schema.clone().build());
+        }
+
+        self.schema
+            .insert("patternProperties".to_string(), Value::Object(map));
+        self
+    }
+
+    /// Constrain the names of all properties with a schema (`propertyNames`).
+    pub fn property_names(mut self, names_schema: SchemaBuilder) -> Self {
+        self.schema
+            .insert("propertyNames".to_string(), names_schema.build());
+        self
+    }
+
     /// Mark fields as required.
     pub fn required(mut self, fields: &[&str]) -> Self {
         self.required.extend(fields.iter().map(|s| s.to_string()));
         self
     }
 
+    /// Require other properties whenever a property is present (`dependentRequired`).
+    ///
+    /// `&[("end", &["start"])]` means a schema with `end` must also have `start`.
+    pub fn dependent_required(mut self, dependencies: &[(&str, &[&str])]) -> Self {
+        let mut map = self
+            .schema
+            .get("dependentRequired")
+            .and_then(|v| v.as_object())
+            .cloned()
+            .unwrap_or_default();
+
+        for (name, needs) in dependencies {
+            map.insert(name.to_string(), json!(needs));
+        }
+
+        self.schema
+            .insert("dependentRequired".to_string(), Value::Object(map));
+        self
+    }
+
+    /// Require exactly one of `fields` to be present.
+    ///
+    /// Emits `oneOf` with one `required` alternative per field, e.g. for a lookup by
+    /// `user_id` *or* `email`. Calling it again adds another independent group (via
+    /// `allOf`).
+    pub fn exactly_one_of(mut self, fields: &[&str]) -> Self {
+        let alternatives: Vec<Value> = fields.iter().map(|f| json!({"required": [f]})).collect();
+
+        if self.schema.contains_key("oneOf") {
+            let mut all_of = self
+                .schema
+                .get("allOf")
+                .and_then(|v| v.as_array())
+                .cloned()
+                .unwrap_or_default();
+            all_of.push(json!({"oneOf": alternatives}));
+            self.schema.insert("allOf".to_string(), json!(all_of));
+        } else {
+            self.schema.insert("oneOf".to_string(), json!(alternatives));
+        }
+        self
+    }
+
     /// Set the description.
     pub fn description(mut self, desc: &str) -> Self {
         self.schema
@@ -133,6 +210,18 @@ impl SchemaBuilder {
         self
     }
 
+    /// Set a short human-readable title.
+    pub fn title(mut self, title: &str) -> Self {
+        self.schema.insert("title".to_string(), json!(title));
+        self
+    }
+
+    /// Set example values, shown to LLMs alongside the description.
+    pub fn examples(mut self, examples: &[Value]) -> Self {
+        self.schema.insert("examples".to_string(), json!(examples));
+        self
+    }
+
     /// Set the format (e.g., "email", "uri", "uuid", "date-time").
     pub fn format(mut self, fmt: &str) -> Self {
         self.schema.insert("format".to_string(), json!(fmt));
@@ -170,7 +259,13 @@ impl SchemaBuilder {
     }
 
     /// Set enum values.
-    pub fn enum_values(mut self, values: &[&str]) -> Self {
+    pub fn enum_values(self, values: &[&str]) -> Self {
+        let values: Vec<Value> = values.iter().map(|v| json!(v)).collect();
+        self.enum_raw(&values)
+    }
+
+    /// Set enum values of any JSON type (e.g. `[1, 2, 3]` or `["a", 1, true]`).
+    pub fn enum_raw(mut self, values: &[Value]) -> Self {
         self.schema.insert("enum".to_string(), json!(values));
         self
     }
@@ -229,6 +324,287 @@ impl SchemaBuilder {
 
         Value::Object(self.schema)
     }
+
+    /// Build the schema and check it for internal consistency.
+    ///
+    /// Catches mistakes such as `required` naming a property that doesn't exist or
+    /// `minimum` greater than `maximum`; see [`validate_schema`]. [`build`](Self::build)
+    /// stays infallible for ergonomics.
+    pub fn try_build(self) -> Result<Value> {
+        let schema = self.build();
+        validate_schema(&schema)?;
+        Ok(schema)
+    }
+}
+
+// =============================================================================
+// Schema Validation
+// =============================================================================
+
+/// Check a JSON Schema for self-consistency, reporting every problem found.
+///
+/// This is a sanity check for hand-written schemas, not a full meta-schema
+/// validation. Checks, recursively through nested schemas:
+/// - every `required` or `dependentRequired` name is declared in `properties`
+///   (unless `patternProperties` or an `additionalProperties` schema could supply
+///   it); `anyOf`/`oneOf`/`allOf`/`not` branches may name the enclosing properties
+/// - `minimum`/`maximum`, `minLength`/`maxLength`, `minItems`/`maxItems` and
+///   `minProperties`/`maxProperties` bounds are not inverted
+/// - `enum` is not empty, and `default` is one of its values
+pub fn validate_schema(schema: &Value) -> Result<()> {
+    let mut problems = Vec::new();
+    collect_schema_problems(schema, "#", None, &mut problems);
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        bail!("Invalid schema:\n  {}", problems.join("\n  "))
+    }
+}
+
+/// `enclosing` is the property map of the schema a combinator branch belongs to.
+fn collect_schema_problems(
+    schema: &Value,
+    path: &str,
+    enclosing: Option<&Map<String, Value>>,
+    problems: &mut Vec<String>,
+) {
+    let Some(obj) = schema.as_object() else {
+        return;
+    };
+
+    let properties = obj.get("properties").and_then(|v| v.as_object());
+    let open_keys = obj.contains_key("patternProperties")
+        || obj
+            .get("additionalProperties")
+            .is_some_and(|v| v.is_object());
+    let is_defined = |name: &str| {
+        open_keys
+            || properties.is_some_and(|p| p.contains_key(name))
+            || enclosing.is_some_and(|p| p.contains_key(name))
+    };
+    if let Some(required) = obj.get("required").and_then(|v| v.as_array()) {
+        for name in required.iter().filter_map(|v| v.as_str()) {
+            if !is_defined(name) {
+                problems.push(format!(
+                    "{}: required property '{}' is not defined in properties",
+                    path, name
+                ));
+            }
+        }
+    }
+    if let Some(dependencies) = obj.get("dependentRequired").and_then(|v| v.as_object()) {
+        for (name, needs) in dependencies {
+            let needs = needs.as_array().into_iter().flatten();
+            for name in std::iter::once(name.as_str()).chain(needs.filter_map(|v| v.as_str())) {
+                if !is_defined(name) {
+                    problems.push(format!(
+                        "{}: dependentRequired property '{}' is not defined in properties",
+                        path, name
+                    ));
+                }
+            }
+        }
+    }
+
+    for (min, max) in [
+        ("minimum", "maximum"),
+        ("minLength", "maxLength"),
+        ("minItems", "maxItems"),
+        ("minProperties", "maxProperties"),
+    ] {
+        let lo = obj.get(min).and_then(|v| v.as_f64());
+        let hi = obj.get(max).and_then(|v| v.as_f64());
+        if let (Some(lo), Some(hi)) = (lo, hi) {
+            if lo > hi {
+                problems.push(format!(
+                    "{}: {} ({}) is greater than {} ({})",
+                    path, min, lo, max, hi
+                ));
+            }
+        }
+    }
+
+    if let Some(values) = obj.get("enum").and_then(|v| v.as_array()) {
+        if values.is_empty() {
+            problems.push(format!("{}: enum has no values", path));
+        } else if let Some(default) = obj.get("default") {
+            if !values.contains(default) {
+                problems.push(format!(
+                    "{}: default {} is not one of the enum values",
+                    path, default
+                ));
+            }
+        }
+    }
+
+    // Recurse into nested schemas; combinator branches see this schema's properties
+    let branch_properties = properties.or(enclosing);
+    for key in ["properties", "patternProperties", "$defs"] {
+        if let Some(children) = obj.get(key).and_then(|v| v.as_object()) {
+            for (name, child) in children {
+                let child_path = format!("{}/{}/{}", path, key, name);
+                collect_schema_problems(child, &child_path, None, problems);
+            }
+        }
+    }
+    for key in ["items", "additionalProperties", "propertyNames"] {
+        if let Some(child) = obj.get(key) {
+            collect_schema_problems(child, &format!("{}/{}", path, key), None, problems);
+        }
+    }
+    if let Some(child) = obj.get("not") {
+        let child_path = format!("{}/not", path);
+        collect_schema_problems(child, &child_path, branch_properties, problems);
+    }
+    for key in ["anyOf", "oneOf", "allOf"] {
+        if let Some(children) = obj.get(key).and_then(|v| v.as_array()) {
+            for (i, child) in children.iter().enumerate() {
+                let child_path = format!("{}/{}/{}", path, key, i);
+                collect_schema_problems(child, &child_path, branch_properties, problems);
+            }
+        }
+    }
+}
+
+// =============================================================================
+// Instance Validation
+// =============================================================================
+
+/// A value that failed to validate against a schema.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValidationError {
+    /// JSON Pointer to the offending part of the value (`""` for the value itself)
+    pub instance_path: String,
+    /// What was wrong with it
+    pub message: String,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.instance_path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.instance_path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Validate a value against a JSON Schema, reporting every error found.
+///
+/// A schema that can't be compiled (e.g. an unknown `type`) is reported as a
+/// single error at the root.
+///
+/// # Example
+///
+/// ```rust
+/// use fgp_daemon::schema::{validate, SchemaBuilder};
+/// use serde_json::json;
+///
+/// let schema = SchemaBuilder::object()
+///     .property("limit", SchemaBuilder::integer().minimum(1))
+///     .required(&["limit"])
+///     .build();
+///
+/// assert!(validate(&json!({"limit": 10}), &schema).is_ok());
+///
+/// let errors = validate(&json!({"limit": 0}), &schema).unwrap_err();
+/// assert_eq!(errors[0].instance_path, "/limit");
+/// ```
+pub fn validate(instance: &Value, schema: &Value) -> std::result::Result<(), Vec<ValidationError>> {
+    let validator = jsonschema::validator_for(schema).map_err(|e| {
+        vec![ValidationError {
+            instance_path: String::new(),
+            message: format!("Invalid schema: {}", e),
+        }]
+    })?;
+    let errors: Vec<ValidationError> = validator
+        .iter_errors(instance)
+        .map(|e| ValidationError {
+            instance_path: e.instance_path.to_string(),
+            message: e.to_string(),
+        })
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Parse string params into the type their property schema declares.
+///
+/// LLMs often send `"10"` where an integer is expected. Each top-level string param
+/// whose property in `schema` declares `integer`, `number` or `boolean` (and not
+/// `string`) is replaced with the parsed value. Params without a declared type and
+/// strings that don't parse are left as they are, for the method to reject.
+///
+/// # Example
+///
+/// ```rust
+/// use fgp_daemon::schema::{coerce_params, SchemaBuilder};
+/// use serde_json::json;
+/// use std::collections::HashMap;
+///
+/// let schema = SchemaBuilder::object()
+///     .property("limit", SchemaBuilder::integer())
+///     .property("query", SchemaBuilder::string())
+///     .build();
+/// let mut params = HashMap::from([
+///     ("limit".to_string(), json!("10")),
+///     ("query".to_string(), json!("10")),
+/// ]);
+/// coerce_params(&mut params, &schema);
+/// assert_eq!(params["limit"], json!(10));
+/// assert_eq!(params["query"], json!("10"));
+/// ```
+pub fn coerce_params(params: &mut HashMap<String, Value>, schema: &Value) {
+    let Some(properties) = schema.get("properties").and_then(|v| v.as_object()) else {
+        return;
+    };
+    for (name, value) in params.iter_mut() {
+        let (Some(text), Some(property)) = (value.as_str(), properties.get(name)) else {
+            continue;
+        };
+        let types: Vec<&str> = match property.get("type") {
+            Some(Value::String(t)) => vec![t.as_str()],
+            Some(Value::Array(types)) => types.iter().filter_map(|t| t.as_str()).collect(),
+            _ => continue,
+        };
+        if let Some(coerced) = coerce_string(text.trim(), &types) {
+            *value = coerced;
+        }
+    }
+}
+
+/// Parse `text` as the first of `types` it fits; `None` if a string is allowed.
+fn coerce_string(text: &str, types: &[&str]) -> Option<Value> {
+    if types.contains(&"string") {
+        return None;
+    }
+    if types.contains(&"integer") || types.contains(&"number") {
+        if let Ok(n) = text.parse::<i64>() {
+            return Some(json!(n));
+        }
+        if let Ok(n) = text.parse::<u64>() {
+            return Some(json!(n));
+        }
+    }
+    if types.contains(&"number") {
+        let number = text.parse().ok().and_then(serde_json::Number::from_f64);
+        if let Some(n) = number {
+            return Some(Value::Number(n));
+        }
+    }
+    if types.contains(&"boolean") {
+        match text {
+            "true" => return Some(json!(true)),
+            "false" => return Some(json!(false)),
+            _ => {}
+        }
+    }
+    None
 }
 
 // =============================================================================
@@ -244,6 +620,15 @@ pub struct McpTool {
     pub input_schema: McpInputSchema,
 }
 
+/// MCP `tools/list` result: one page of tools and the cursor for the next.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpToolsList {
+    pub tools: Vec<McpTool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
 /// MCP input schema.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpInputSchema {
@@ -253,6 +638,12 @@ pub struct McpInputSchema {
     pub properties: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub required: Option<Vec<String>>,
+    #[serde(
+        rename = "additionalProperties",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub additional_properties: Option<Value>,
 }
 
 /// Convert FGP methods to OpenAI function calling format.
@@ -263,6 +654,8 @@ pub struct McpInputSchema {
 /// - Schema: inlines all `$ref` references
 /// - Adds `additionalProperties: false` for stricter validation
 ///
+/// See [`to_openai_with_examples`] to include an example call in each description.
+///
 /// # Example output
 /// ```json
 /// {
@@ -276,11 +669,25 @@ pub struct McpInputSchema {
 /// }
 /// ```
 pub fn to_openai(methods: &[MethodInfo]) -> Value {
+    openai_functions(methods, false)
+}
+
+/// Like [`to_openai`], with an example call appended to each description.
+///
+/// The params of a method's first [example](MethodInfo::examples) are appended as
+/// compact JSON (`Example: {"to":"alice@example.com"}`), cut off after 256 bytes. One
+/// few-shot example noticeably improves how models fill in params, at the cost of a
+/// longer tool definition.
+pub fn to_openai_with_examples(methods: &[MethodInfo]) -> Value {
+    openai_functions(methods, true)
+}
+
+fn openai_functions(methods: &[MethodInfo], examples: bool) -> Value {
     let functions: Vec<Value> = methods
         .iter()
         .map(|method| {
             let name = method.name.replace('.', "_");
-            let description = truncate(&method.description, 1024);
+            let description = truncate(&describe(method, examples), 1024);
             let parameters = get_schema_or_synthesize(method);
             let mut parameters = inline_refs(parameters);
 
@@ -307,6 +714,8 @@ pub fn to_openai(methods: &[MethodInfo]) -> Value {
 /// - Schema: preserved with full JSON Schema support
 /// - Includes `cache_control` hint for prompt caching
 ///
+/// See [`to_anthropic_with_examples`] to include an example call in each description.
+///
 /// # Example output
 /// ```json
 /// {
@@ -321,6 +730,16 @@ pub fn to_openai(methods: &[MethodInfo]) -> Value {
 /// }
 /// ```
 pub fn to_anthropic(methods: &[MethodInfo]) -> Value {
+    anthropic_tools(methods, false)
+}
+
+/// Like [`to_anthropic`], with an example call appended to each description (as in
+/// [`to_openai_with_examples`]).
+pub fn to_anthropic_with_examples(methods: &[MethodInfo]) -> Value {
+    anthropic_tools(methods, true)
+}
+
+fn anthropic_tools(methods: &[MethodInfo], examples: bool) -> Value {
     let tools: Vec<Value> = methods
         .iter()
         .map(|method| {
@@ -328,7 +747,7 @@ pub fn to_anthropic(methods: &[MethodInfo]) -> Value {
 
             json!({
                 "name": method.name,
-                "description": method.description,
+                "description": describe(method, examples),
                 "input_schema": schema,
                 "cache_control": { "type": "ephemeral" }
             })
@@ -357,23 +776,383 @@ pub fn to_mcp(methods: &[MethodInfo]) -> Vec<McpTool> {
                     schema_type: "object".to_string(),
                     properties,
                     required,
+                    additional_properties: schema.get("additionalProperties").cloned(),
                 },
             }
         })
         .collect()
 }
 
+/// Number of tools per page of [`to_mcp_response`].
+pub const MCP_PAGE_SIZE: usize = 100;
+
+/// Convert FGP methods to an MCP `tools/list` result.
+///
+/// Returns the page of [`MCP_PAGE_SIZE`] tools starting at `cursor` (`None` for the
+/// first page), with `nextCursor` set while more remain. Cursors are opaque to MCP
+/// clients; here they hold the offset of the page, as with the `offset` param of the
+/// `methods` built-in. An unparseable or out-of-range cursor is an error.
+pub fn to_mcp_response(methods: &[MethodInfo], cursor: Option<&str>) -> Result<McpToolsList> {
+    let offset = match cursor {
+        None => 0,
+        Some(cursor) => match cursor.parse::<usize>() {
+            Ok(offset) if offset <= methods.len() => offset,
+            _ => bail!("Invalid cursor: {}", cursor),
+        },
+    };
+    let end = methods.len().min(offset + MCP_PAGE_SIZE);
+    Ok(McpToolsList {
+        tools: to_mcp(&methods[offset..end]),
+        next_cursor: (end < methods.len()).then(|| end.to_string()),
+    })
+}
+
+/// Build a single JSON Schema document describing every method.
+///
+/// Intended for non-LLM consumers such as documentation generators and TypeScript
+/// codegen. Each method becomes an entry under `methods` with its `params` and
+/// `returns` schemas. `$defs` from the individual schemas are hoisted to the
+/// document's top-level `$defs` so shared types appear once; local `#/$defs/...`
+/// refs keep resolving against the document root. If two methods define the same
+/// name differently, the later method's refs are inlined instead.
+///
+/// # Example output
+/// ```json
+/// {
+///   "$schema": "https://json-schema.org/draft/2020-12/schema",
+///   "methods": {
+///     "gmail.send": {
+///       "description": "Send an email",
+///       "params": { "type": "object", "properties": {...} },
+///       "returns": { "$ref": "#/$defs/Message" }
+///     }
+///   },
+///   "$defs": { "Message": {...} }
+/// }
+/// ```
+pub fn to_json_schema_document(methods: &[MethodInfo]) -> Value {
+    let mut defs = Map::new();
+    let mut entries = Map::new();
+
+    for method in methods {
+        let mut entry = Map::new();
+        entry.insert("description".to_string(), json!(method.description));
+        entry.insert(
+            "params".to_string(),
+            hoist_defs(get_schema_or_synthesize(method), &mut defs),
+        );
+        entry.insert(
+            "returns".to_string(),
+            method
+                .returns
+                .clone()
+                .map(|returns| hoist_defs(returns, &mut defs))
+                .unwrap_or(Value::Null),
+        );
+        if method.deprecated {
+            entry.insert("deprecated".to_string(), json!(true));
+        }
+        entries.insert(method.name.clone(), Value::Object(entry));
+    }
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "methods": entries,
+        "$defs": defs,
+    })
+}
+
+/// Generate TypeScript declarations for FGP methods.
+///
+/// Each method gets a `<Name>Params` interface and a `<Name>Result` type, named by
+/// PascalCasing the method name (`gmail.send` → `GmailSendParams`). A `MethodCall`
+/// discriminated union pairs each method name with its params, and `MethodName`
+/// is the union of the names. Refs are inlined; JSON Schema features with no TS
+/// equivalent (formats, bounds, patterns) are dropped.
+///
+/// # Example output
+/// ```typescript
+/// /** Send an email */
+/// export interface GmailSendParams {
+///   /** Recipient email address */
+///   to: string;
+///   cc?: string[];
+/// }
+///
+/// export type GmailSendResult = unknown;
+///
+/// export type MethodCall =
+///   | { method: "gmail.send"; params: GmailSendParams };
+///
+/// export type MethodName = MethodCall["method"];
+/// ```
+pub fn to_typescript(methods: &[MethodInfo]) -> String {
+    let mut out = String::new();
+
+    for method in methods {
+        let name = ts_type_name(&method.name);
+        let params = inline_refs(get_schema_or_synthesize(method));
+        let returns = method.returns.clone().map(inline_refs);
+        let description = method.description.as_str();
+        let params_name = format!("{}Params", name);
+        ts_declaration(&mut out, &params_name, Some(&params), description);
+        ts_declaration(&mut out, &format!("{}Result", name), returns.as_ref(), "");
+    }
+
+    if methods.is_empty() {
+        out.push_str("export type MethodCall = never;\n\n");
+    } else {
+        out.push_str("export type MethodCall =\n");
+        for (i, method) in methods.iter().enumerate() {
+            let end = if i + 1 == methods.len() { ";" } else { "" };
+            out.push_str(&format!(
+                "  | {{ method: {}; params: {}Params }}{}\n",
+                json!(method.name),
+                ts_type_name(&method.name),
+                end
+            ));
+        }
+        out.push('\n');
+    }
+    out.push_str("export type MethodName = MethodCall[\"method\"];\n");
+    out
+}
+
 // =============================================================================
 // Helper Functions
 // =============================================================================
 
+/// Move a schema's `$defs` into `defs`, inlining its refs on a name conflict.
+fn hoist_defs(schema: Value, defs: &mut Map<String, Value>) -> Value {
+    let Some(local) = schema.get("$defs").and_then(|d| d.as_object()) else {
+        return schema;
+    };
+
+    let conflicts = local
+        .iter()
+        .any(|(name, def)| defs.get(name).is_some_and(|existing| existing != def));
+    if conflicts {
+        return inline_refs(schema);
+    }
+
+    for (name, def) in local {
+        defs.insert(name.clone(), def.clone());
+    }
+    let mut schema = schema;
+    if let Some(obj) = schema.as_object_mut() {
+        obj.remove("$defs");
+    }
+    schema
+}
+
+/// PascalCase a method name for use as a TypeScript type name.
+fn ts_type_name(method: &str) -> String {
+    method
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .flat_map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase())
+                .into_iter()
+                .chain(chars)
+        })
+        .collect()
+}
+
+/// Append a `/** ... */` comment at `indent`, if there's any text.
+fn ts_doc(out: &mut String, text: &str, indent: usize) {
+    let text = text.trim();
+    if !text.is_empty() {
+        let text = text.replace("*/", "*\\/").replace('\n', " ");
+        out.push_str(&format!("{:indent$}/** {} */\n", "", text, indent = indent));
+    }
+}
+
+/// Append an exported declaration of `name`: an interface for object schemas with
+/// properties, a type alias otherwise (`unknown` without a schema).
+fn ts_declaration(out: &mut String, name: &str, schema: Option<&Value>, description: &str) {
+    ts_doc(out, description, 0);
+    let Some(schema) = schema else {
+        out.push_str(&format!("export type {} = unknown;\n\n", name));
+        return;
+    };
+    match schema.get("properties").and_then(|p| p.as_object()) {
+        Some(properties) if !properties.is_empty() => {
+            out.push_str(&format!("export interface {} {{\n", name));
+            ts_members(out, schema, 2);
+            out.push_str("}\n\n");
+        }
+        _ => {
+            let ts = ts_type(schema, 0);
+            out.push_str(&format!("export type {} = {};\n\n", name, ts));
+        }
+    }
+}
+
+/// Append one `key: type;` line (with its description) per property of `schema`.
+fn ts_members(out: &mut String, schema: &Value, indent: usize) {
+    let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) else {
+        return;
+    };
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(|r| r.as_array())
+        .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+        .unwrap_or_default();
+
+    for (key, property) in properties {
+        let description = property.get("description").and_then(|d| d.as_str());
+        ts_doc(out, description.unwrap_or_default(), indent);
+        let is_identifier = key.chars().enumerate().all(|(i, c)| {
+            c == '_' || c == '$' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit())
+        });
+        let optional = if required.contains(&key.as_str()) {
+            ""
+        } else {
+            "?"
+        };
+        let key = if is_identifier && !key.is_empty() {
+            key.clone()
+        } else {
+            json!(key).to_string()
+        };
+        out.push_str(&format!(
+            "{:indent$}{}{}: {};\n",
+            "",
+            key,
+            optional,
+            ts_type(property, indent),
+            indent = indent
+        ));
+    }
+}
+
+/// The TypeScript type for a JSON Schema, with nested object literals laid out
+/// for a declaration at `indent`.
+fn ts_type(schema: &Value, indent: usize) -> String {
+    let Some(obj) = schema.as_object() else {
+        // Boolean schemas: `true` accepts anything, `false` nothing
+        let ts = match schema {
+            Value::Bool(false) => "never",
+            _ => "unknown",
+        };
+        return ts.to_string();
+    };
+
+    if let Some(value) = obj.get("const") {
+        return ts_literal(value);
+    }
+    if let Some(values) = obj.get("enum").and_then(|v| v.as_array()) {
+        let literals: Vec<String> = values.iter().map(ts_literal).collect();
+        return literals.join(" | ");
+    }
+    for (keyword, separator) in [("anyOf", " | "), ("oneOf", " | "), ("allOf", " & ")] {
+        if let Some(variants) = obj.get(keyword).and_then(|v| v.as_array()) {
+            let types: Vec<String> = variants
+                .iter()
+                .map(|variant| ts_parenthesize(ts_type(variant, indent)))
+                .collect();
+            return types.join(separator);
+        }
+    }
+
+    match obj.get("type") {
+        Some(Value::String(json_type)) => ts_type_of(json_type, schema, indent),
+        Some(Value::Array(json_types)) => {
+            let types: Vec<String> = json_types
+                .iter()
+                .filter_map(|t| t.as_str())
+                .map(|t| ts_type_of(t, schema, indent))
+                .collect();
+            types.join(" | ")
+        }
+        _ if obj.contains_key("properties") => ts_type_of("object", schema, indent),
+        _ => "unknown".to_string(),
+    }
+}
+
+/// The TypeScript type for one JSON Schema `type` of `schema`.
+fn ts_type_of(json_type: &str, schema: &Value, indent: usize) -> String {
+    match json_type {
+        "string" => "string".to_string(),
+        "integer" | "number" => "number".to_string(),
+        "boolean" => "boolean".to_string(),
+        "null" => "null".to_string(),
+        "array" => match schema.get("items") {
+            Some(items) => format!("{}[]", ts_parenthesize(ts_type(items, indent))),
+            None => "unknown[]".to_string(),
+        },
+        "object" => {
+            let has_properties = schema
+                .get("properties")
+                .and_then(|p| p.as_object())
+                .is_some_and(|p| !p.is_empty());
+            if has_properties {
+                let mut members = String::new();
+                ts_members(&mut members, schema, indent + 2);
+                format!("{{\n{}{:indent$}}}", members, "", indent = indent)
+            } else {
+                match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => "Record<string, never>".to_string(),
+                    Some(values @ Value::Object(_)) => {
+                        format!("Record<string, {}>", ts_type(values, indent))
+                    }
+                    _ => "Record<string, unknown>".to_string(),
+                }
+            }
+        }
+        _ => "unknown".to_string(),
+    }
+}
+
+/// A JSON value as a TypeScript literal type; only scalars have one.
+fn ts_literal(value: &Value) -> String {
+    match value {
+        Value::Array(_) | Value::Object(_) => "unknown".to_string(),
+        scalar => scalar.to_string(),
+    }
+}
+
+/// Wrap union and intersection types in parentheses, for use in `T[]` or `A & B`.
+fn ts_parenthesize(ts: String) -> String {
+    if ts.contains(" | ") || ts.contains(" & ") {
+        format!("({})", ts)
+    } else {
+        ts
+    }
+}
+
+/// Longest rendering of example params that [`describe`] appends to a description.
+const EXAMPLE_MAX_LEN: usize = 256;
+
+/// A method's description, followed by its first example's params if `examples`.
+fn describe(method: &MethodInfo, examples: bool) -> String {
+    match method.examples.first().filter(|_| examples) {
+        Some(example) => {
+            let params = truncate(&example.params.to_string(), EXAMPLE_MAX_LEN);
+            format!("{}\n\nExample: {}", method.description, params)
+        }
+        None => method.description.clone(),
+    }
+}
+
 /// Get the schema from MethodInfo, or synthesize from params.
-fn get_schema_or_synthesize(method: &MethodInfo) -> Value {
-    if let Some(schema) = &method.schema {
+///
+/// Strict methods get `additionalProperties: false` unless the schema sets it.
+pub(crate) fn get_schema_or_synthesize(method: &MethodInfo) -> Value {
+    let mut schema = if let Some(schema) = &method.schema {
         schema.clone()
     } else {
         synthesize_schema_from_params(&method.params)
+    };
+
+    if method.strict_params {
+        if let Some(obj) = schema.as_object_mut() {
+            obj.entry("additionalProperties").or_insert(json!(false));
+        }
     }
+    schema
 }
 
 /// Synthesize a JSON Schema from legacy ParamInfo list.
@@ -389,26 +1168,50 @@ fn synthesize_schema_from_params(params: &[ParamInfo]) -> Value {
     let mut required = Vec::new();
 
     for param in params {
-        let json_type = match param.param_type.as_str() {
-            "string" => "string",
-            "integer" | "int" => "integer",
-            "number" | "float" => "number",
-            "boolean" | "bool" => "boolean",
-            "array" | "list" => "array",
-            "object" | "dict" => "object",
-            _ => "string",
-        };
+        let json_type = json_type_for(&param.param_type);
 
         let mut prop = json!({ "type": json_type });
 
-        // Add description (use param name if no description field)
         if let Some(obj) = prop.as_object_mut() {
-            // ParamInfo doesn't have description yet, use name as fallback
-            obj.insert("description".to_string(), json!(param.name));
+            // Only emit a description when one was provided; the param name alone
+            // reads as a misleading description in LLM tool exports.
+            if let Some(description) = param.description.as_deref().filter(|d| !d.is_empty()) {
+                obj.insert("description".to_string(), json!(description));
+            }
 
             if let Some(default) = &param.default {
                 obj.insert("default".to_string(), default.clone());
             }
+
+            if json_type == "array" {
+                // Element schema: explicit schema wins, then items_type, else untyped
+                let items = if let Some(items_schema) = &param.items_schema {
+                    Some(items_schema.clone())
+                } else {
+                    param
+                        .items_type
+                        .as_deref()
+                        .map(|t| json!({ "type": json_type_for(t) }))
+                };
+
+                match (items, &param.enum_values) {
+                    (Some(mut items), Some(values)) => {
+                        if let Some(items_obj) = items.as_object_mut() {
+                            items_obj.insert("enum".to_string(), json!(values));
+                        }
+                        obj.insert("items".to_string(), items);
+                    }
+                    (Some(items), None) => {
+                        obj.insert("items".to_string(), items);
+                    }
+                    (None, Some(values)) => {
+                        obj.insert("items".to_string(), json!({ "enum": values }));
+                    }
+                    (None, None) => {}
+                }
+            } else if let Some(values) = &param.enum_values {
+                obj.insert("enum".to_string(), json!(values));
+            }
         }
 
         properties.insert(param.name.clone(), prop);
@@ -433,6 +1236,19 @@ fn synthesize_schema_from_params(params: &[ParamInfo]) -> Value {
     schema
 }
 
+/// Map a legacy ParamInfo type name to a JSON Schema type.
+fn json_type_for(param_type: &str) -> &'static str {
+    match param_type {
+        "string" => "string",
+        "integer" | "int" => "integer",
+        "number" | "float" => "number",
+        "boolean" | "bool" => "boolean",
+        "array" | "list" => "array",
+        "object" | "dict" => "object",
+        _ => "string",
+    }
+}
+
 /// Extract properties and required arrays from a schema.
 fn extract_properties_and_required(schema: &Value) -> (Option<Value>, Option<Vec<String>>) {
     let properties = schema.get("properties").cloned();
@@ -505,12 +1321,16 @@ fn resolve_ref(ref_path: &str, defs: &Option<Value>) -> Option<Value> {
     None
 }
 
-/// Truncate a string to a maximum length.
+/// Truncate a string to a maximum length in bytes, without splitting a character.
 fn truncate(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
         s.to_string()
     } else {
-        format!("{}...", &s[..max_len - 3])
+        let mut end = max_len - 3;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("{}...", &s[..end])
     }
 }
 
@@ -575,6 +1395,313 @@ mod tests {
         assert_eq!(schema["enum"], json!(["draft", "sent", "trash"]));
     }
 
+    #[test]
+    fn test_schema_builder_enum_raw() {
+        let method = MethodInfo::new("job.retry", "Retry a job").schema(
+            SchemaBuilder::object()
+                .property(
+                    "attempts",
+                    SchemaBuilder::integer().enum_raw(&[json!(1), json!(2), json!(3)]),
+                )
+                .property(
+                    "mode",
+                    SchemaBuilder::default().enum_raw(&[json!("a"), json!(1), json!(true)]),
+                )
+                .build(),
+        );
+
+        let openai = to_openai(std::slice::from_ref(&method));
+        let properties = &openai["functions"][0]["parameters"]["properties"];
+        assert_eq!(properties["attempts"]["enum"], json!([1, 2, 3]));
+        assert_eq!(properties["mode"]["enum"], json!(["a", 1, true]));
+
+        let anthropic = to_anthropic(&[method]);
+        let properties = &anthropic["tools"][0]["input_schema"]["properties"];
+        assert_eq!(properties["attempts"]["enum"], json!([1, 2, 3]));
+    }
+
+    #[test]
+    fn test_json_schema_document_hoists_defs() {
+        let address = json!({"type": "object", "properties": {"email": {"type": "string"}}});
+        let send = MethodInfo::new("mail.send", "Send mail")
+            .schema(json!({
+                "type": "object",
+                "properties": {"to": {"$ref": "#/$defs/Address"}},
+                "$defs": {"Address": address},
+            }))
+            .returns(json!({"type": "object", "properties": {"id": {"type": "string"}}}));
+        let conflicting = MethodInfo::new("mail.forward", "Forward mail").schema(json!({
+            "type": "object",
+            "properties": {"to": {"$ref": "#/$defs/Address"}},
+            "$defs": {"Address": {"type": "string"}},
+        }));
+        let legacy = MethodInfo::new("mail.list", "List mail");
+
+        let doc = to_json_schema_document(&[send, conflicting, legacy]);
+
+        assert_eq!(doc["$defs"]["Address"], address);
+        let send = &doc["methods"]["mail.send"];
+        assert_eq!(
+            send["params"]["properties"]["to"]["$ref"],
+            "#/$defs/Address"
+        );
+        assert!(send["params"].get("$defs").is_none());
+        assert_eq!(send["returns"]["properties"]["id"]["type"], "string");
+
+        // A conflicting definition is inlined rather than overwriting the shared one
+        let forward = &doc["methods"]["mail.forward"];
+        assert_eq!(forward["params"]["properties"]["to"]["type"], "string");
+
+        assert_eq!(doc["methods"]["mail.list"]["params"]["type"], "object");
+        assert!(doc["methods"]["mail.list"]["returns"].is_null());
+    }
+
+    #[test]
+    fn test_to_typescript() {
+        let send = MethodInfo::new("mail.send_message", "Send mail")
+            .schema(json!({
+                "type": "object",
+                "properties": {
+                    "to": {"$ref": "#/$defs/Address"},
+                    "cc": {"type": "array", "items": {"type": ["string", "null"]}},
+                    "priority": {"enum": ["low", "high"]},
+                    "retries": {"type": "integer", "description": "Send attempts"},
+                    "x-trace": {"type": "object", "additionalProperties": {"type": "string"}},
+                },
+                "required": ["to"],
+                "$defs": {
+                    "Address": {"type": "object", "properties": {"email": {"type": "string"}}},
+                },
+            }))
+            .returns(json!({"type": "array", "items": {"type": "boolean"}}));
+        let list = MethodInfo::new("mail.list", "List mail");
+
+        let ts = to_typescript(&[send, list]);
+
+        assert!(ts.contains("/** Send mail */\nexport interface MailSendMessageParams {\n"));
+        assert!(ts.contains("  to: {\n    email?: string;\n  };\n"));
+        assert!(ts.contains("  cc?: (string | null)[];\n"));
+        assert!(ts.contains("  priority?: \"low\" | \"high\";\n"));
+        assert!(ts.contains("  /** Send attempts */\n  retries?: number;\n"));
+        assert!(ts.contains("  \"x-trace\"?: Record<string, string>;\n"));
+        assert!(ts.contains("export type MailSendMessageResult = boolean[];\n"));
+        assert!(ts.contains("export type MailListParams = Record<string, unknown>;\n"));
+        assert!(ts.contains("export type MailListResult = unknown;\n"));
+        assert!(ts.contains(concat!(
+            "export type MethodCall =\n",
+            "  | { method: \"mail.send_message\"; params: MailSendMessageParams }\n",
+            "  | { method: \"mail.list\"; params: MailListParams };\n",
+        )));
+        assert!(ts.ends_with("export type MethodName = MethodCall[\"method\"];\n"));
+
+        assert!(to_typescript(&[]).contains("export type MethodCall = never;"));
+    }
+
+    #[test]
+    fn test_try_build_reports_problems() {
+        let valid = SchemaBuilder::object()
+            .property("limit", SchemaBuilder::integer().minimum(1).maximum(100))
+            .required(&["limit"])
+            .try_build();
+        assert!(valid.is_ok());
+
+        let err = SchemaBuilder::object()
+            .property("limit", SchemaBuilder::integer().minimum(10).maximum(1))
+            .property(
+                "mode",
+                SchemaBuilder::string()
+                    .enum_values(&["a", "b"])
+                    .default_value(json!("c")),
+            )
+            .required(&["limti"])
+            .try_build()
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("#: required property 'limti'"));
+        assert!(err.contains("#/properties/limit: minimum (10) is greater than maximum (1)"));
+        assert!(err.contains("#/properties/mode: default \"c\""));
+    }
+
+    #[test]
+    fn test_validate_instance() {
+        let schema = SchemaBuilder::object()
+            .property("name", SchemaBuilder::string().min_length(1))
+            .property(
+                "tags",
+                SchemaBuilder::array().items(SchemaBuilder::string()),
+            )
+            .required(&["name"])
+            .build();
+
+        assert!(validate(&json!({"name": "x", "tags": ["a"]}), &schema).is_ok());
+
+        let errors = validate(&json!({"name": "", "tags": ["a", 2]}), &schema).unwrap_err();
+        let mut paths: Vec<&str> = errors.iter().map(|e| e.instance_path.as_str()).collect();
+        paths.sort();
+        assert_eq!(paths, ["/name", "/tags/1"]);
+
+        let errors = validate(&json!({}), &schema).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].instance_path, "");
+        assert!(errors[0].message.contains("name"));
+
+        let errors = validate(&json!({}), &json!({"type": "nope"})).unwrap_err();
+        assert!(errors[0].message.starts_with("Invalid schema"));
+    }
+
+    #[test]
+    fn test_coerce_params() {
+        let schema = SchemaBuilder::object()
+            .property("limit", SchemaBuilder::integer())
+            .property("ratio", SchemaBuilder::number())
+            .property("unread", SchemaBuilder::boolean())
+            .property("label", SchemaBuilder::string())
+            .property_raw("size", json!({"type": ["integer", "null"]}))
+            .build();
+        let mut params: HashMap<String, Value> = [
+            ("limit", json!(" 10 ")),
+            ("ratio", json!("0.5")),
+            ("unread", json!("true")),
+            ("label", json!("42")),
+            ("size", json!("big")),
+            ("extra", json!("7")),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+
+        coerce_params(&mut params, &schema);
+
+        assert_eq!(params["limit"], json!(10));
+        assert_eq!(params["ratio"], json!(0.5));
+        assert_eq!(params["unread"], json!(true));
+        assert_eq!(params["label"], json!("42"));
+        assert_eq!(params["size"], json!("big"));
+        assert_eq!(params["extra"], json!("7"));
+
+        let mut params = HashMap::from([("ratio".to_string(), json!("NaN"))]);
+        coerce_params(&mut params, &schema);
+        assert_eq!(params["ratio"], json!("NaN"));
+    }
+
+    #[test]
+    fn test_strict_params() {
+        let strict = MethodInfo::new("gmail.list", "List emails")
+            .param(ParamInfo {
+                name: "limit".into(),
+                param_type: "integer".into(),
+                ..Default::default()
+            })
+            .strict_params();
+        let open = MethodInfo::new("gmail.read", "Read an email");
+        let explicit = MethodInfo::new("gmail.send", "Send an email")
+            .schema(json!({"type": "object", "additionalProperties": true}))
+            .strict_params();
+
+        let tools = to_anthropic(&[strict.clone(), open, explicit]);
+        let additional = |i: usize| tools["tools"][i]["input_schema"].get("additionalProperties");
+        assert_eq!(additional(0), Some(&json!(false)));
+        assert_eq!(additional(1), None);
+        assert_eq!(additional(2), Some(&json!(true)));
+
+        let mcp = serde_json::to_value(to_mcp(&[strict])).unwrap();
+        assert_eq!(mcp[0]["inputSchema"]["additionalProperties"], json!(false));
+    }
+
+    #[test]
+    fn test_schema_builder_exactly_one_of() {
+        let schema = SchemaBuilder::object()
+            .property("user_id", SchemaBuilder::string())
+            .property("email", SchemaBuilder::string())
+            .property("start", SchemaBuilder::string())
+            .property("end", SchemaBuilder::string())
+            .exactly_one_of(&["user_id", "email"])
+            .dependent_required(&[("end", &["start"])])
+            .try_build()
+            .unwrap();
+
+        assert_eq!(
+            schema["oneOf"],
+            json!([{"required": ["user_id"]}, {"required": ["email"]}])
+        );
+        assert_eq!(schema["dependentRequired"], json!({"end": ["start"]}));
+
+        let err = SchemaBuilder::object()
+            .property("email", SchemaBuilder::string())
+            .exactly_one_of(&["email", "phone"])
+            .dependent_required(&[("email", &["name"])])
+            .try_build()
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("#/oneOf/1: required property 'phone'"));
+        assert!(err.contains("#: dependentRequired property 'name'"));
+    }
+
+    #[test]
+    fn test_schema_builder_pattern_properties() {
+        let schema = SchemaBuilder::object()
+            .pattern_properties(&[
+                ("^x-", SchemaBuilder::string()),
+                ("^[0-9]+$", SchemaBuilder::integer()),
+            ])
+            .property_names(SchemaBuilder::string().max_length(32))
+            .build();
+
+        assert_eq!(schema["patternProperties"]["^x-"]["type"], "string");
+        assert_eq!(schema["patternProperties"]["^[0-9]+$"]["type"], "integer");
+        assert_eq!(schema["propertyNames"]["type"], "string");
+        assert_eq!(schema["propertyNames"]["maxLength"], 32);
+    }
+
+    #[test]
+    fn test_pattern_properties_pass_through_converters() {
+        let method = MethodInfo::new("config.set", "Set config values").schema(
+            SchemaBuilder::object()
+                .pattern_properties(&[("^opt_", SchemaBuilder::boolean())])
+                .property_names(SchemaBuilder::string().pattern("^[a-z_]+$"))
+                .build(),
+        );
+
+        let anthropic = to_anthropic(std::slice::from_ref(&method));
+        let input_schema = &anthropic["tools"][0]["input_schema"];
+        assert_eq!(
+            input_schema["patternProperties"]["^opt_"]["type"],
+            "boolean"
+        );
+        assert_eq!(input_schema["propertyNames"]["pattern"], "^[a-z_]+$");
+
+        let openai = to_openai(&[method]);
+        let parameters = &openai["functions"][0]["parameters"];
+        assert_eq!(parameters["patternProperties"]["^opt_"]["type"], "boolean");
+    }
+
+    #[test]
+    fn test_title_and_examples_pass_through_converters() {
+        let method = MethodInfo::new("gmail.search", "Search emails").schema(
+            SchemaBuilder::object()
+                .property(
+                    "query",
+                    SchemaBuilder::string()
+                        .title("Search query")
+                        .description("Gmail search syntax")
+                        .examples(&[json!("from:alice"), json!("is:unread")]),
+                )
+                .required(&["query"])
+                .build(),
+        );
+
+        let anthropic = to_anthropic(std::slice::from_ref(&method));
+        let query = &anthropic["tools"][0]["input_schema"]["properties"]["query"];
+        assert_eq!(query["title"], "Search query");
+        assert_eq!(query["examples"], json!(["from:alice", "is:unread"]));
+
+        let tools = to_mcp(&[method]);
+        let query = &tools[0].input_schema.properties.as_ref().unwrap()["query"];
+        assert_eq!(query["title"], "Search query");
+        assert_eq!(query["examples"][1], "is:unread");
+    }
+
     #[test]
     fn test_to_openai_name_conversion() {
         let method = MethodInfo {
@@ -586,6 +1713,8 @@ mod tests {
             examples: vec![],
             errors: vec![],
             deprecated: false,
+            replaced_by: None,
+            strict_params: false,
         };
 
         let result = to_openai(&[method]);
@@ -608,6 +1737,8 @@ mod tests {
             examples: vec![],
             errors: vec![],
             deprecated: false,
+            replaced_by: None,
+            strict_params: false,
         };
 
         let result = to_anthropic(&[method]);
@@ -616,6 +1747,36 @@ mod tests {
         assert_eq!(result["tools"][0]["cache_control"]["type"], "ephemeral");
     }
 
+    #[test]
+    fn test_converters_with_examples() {
+        let send = MethodInfo::new("gmail.send", "Send an email")
+            .example("Simple email", json!({"to": "alice@example.com"}))
+            .example("Another", json!({"to": "bob@example.com"}));
+        let long = MethodInfo::new("gmail.import", "Import mail")
+            .example("Big", json!({"raw": "é".repeat(EXAMPLE_MAX_LEN)}));
+        let bare = MethodInfo::new("gmail.list", "List emails");
+        let methods = [send, long, bare];
+
+        let expected = "Send an email\n\nExample: {\"to\":\"alice@example.com\"}";
+        let openai = to_openai_with_examples(&methods);
+        assert_eq!(openai["functions"][0]["description"], expected);
+        let anthropic = to_anthropic_with_examples(&methods);
+        assert_eq!(anthropic["tools"][0]["description"], expected);
+
+        let import = anthropic["tools"][1]["description"].as_str().unwrap();
+        let rendered = import.strip_prefix("Import mail\n\nExample: ").unwrap();
+        assert!(rendered.len() <= EXAMPLE_MAX_LEN);
+        assert!(rendered.ends_with("..."));
+
+        assert_eq!(anthropic["tools"][2]["description"], "List emails");
+
+        // Opt-in only
+        let openai = to_openai(&methods);
+        assert_eq!(openai["functions"][0]["description"], "Send an email");
+        let anthropic = to_anthropic(&methods);
+        assert_eq!(anthropic["tools"][0]["description"], "Send an email");
+    }
+
     #[test]
     fn test_synthesize_from_params() {
         let method = MethodInfo {
@@ -627,12 +1788,14 @@ mod tests {
                     param_type: "string".to_string(),
                     required: true,
                     default: None,
+                    ..Default::default()
                 },
                 ParamInfo {
                     name: "limit".to_string(),
                     param_type: "integer".to_string(),
                     required: false,
                     default: Some(json!(10)),
+                    ..Default::default()
                 },
             ],
             schema: None, // No explicit schema, should synthesize
@@ -640,6 +1803,8 @@ mod tests {
             examples: vec![],
             errors: vec![],
             deprecated: false,
+            replaced_by: None,
+            strict_params: false,
         };
 
         let result = to_openai(&[method]);
@@ -651,6 +1816,78 @@ mod tests {
         assert_eq!(params["required"], json!(["query"]));
     }
 
+    #[test]
+    fn test_synthesize_param_description() {
+        let method = MethodInfo::new("test.search", "Search")
+            .param(ParamInfo {
+                name: "query".to_string(),
+                param_type: "string".to_string(),
+                description: Some("Full-text search query".to_string()),
+                ..Default::default()
+            })
+            .param(ParamInfo {
+                name: "limit".to_string(),
+                param_type: "integer".to_string(),
+                ..Default::default()
+            });
+
+        let schema = get_schema_or_synthesize(&method);
+
+        assert_eq!(
+            schema["properties"]["query"]["description"],
+            "Full-text search query"
+        );
+        // No description: omitted rather than falling back to the param name
+        assert!(schema["properties"]["limit"].get("description").is_none());
+    }
+
+    #[test]
+    fn test_synthesize_array_items() {
+        let method = MethodInfo::new("test.tags", "Tag items")
+            .param(ParamInfo {
+                name: "tags".to_string(),
+                param_type: "list".to_string(),
+                items_type: Some("str".to_string()),
+                enum_values: Some(vec![json!("red"), json!("blue")]),
+                ..Default::default()
+            })
+            .param(ParamInfo {
+                name: "people".to_string(),
+                param_type: "array".to_string(),
+                items_type: Some("string".to_string()),
+                items_schema: Some(json!({
+                    "type": "object",
+                    "properties": {"name": {"type": "string"}},
+                    "required": ["name"]
+                })),
+                ..Default::default()
+            })
+            .param(ParamInfo {
+                name: "ids".to_string(),
+                param_type: "array".to_string(),
+                ..Default::default()
+            })
+            .param(ParamInfo {
+                name: "mode".to_string(),
+                param_type: "string".to_string(),
+                enum_values: Some(vec![json!("fast"), json!("safe")]),
+                ..Default::default()
+            });
+
+        let schema = get_schema_or_synthesize(&method);
+        let props = &schema["properties"];
+
+        // Unknown type names fall back to string, enum applies to elements
+        assert_eq!(props["tags"]["items"]["type"], "string");
+        assert_eq!(props["tags"]["items"]["enum"], json!(["red", "blue"]));
+        // Full item schema takes precedence over items_type
+        assert_eq!(props["people"]["items"]["type"], "object");
+        assert_eq!(props["people"]["items"]["required"], json!(["name"]));
+        // No item info keeps the previous untyped behavior
+        assert_eq!(props["ids"], json!({"type": "array"}));
+        assert_eq!(props["mode"]["enum"], json!(["fast", "safe"]));
+    }
+
     #[test]
     fn test_inline_refs() {
         let schema = json!({
@@ -685,6 +1922,7 @@ mod tests {
     fn test_truncate() {
         assert_eq!(truncate("hello", 10), "hello");
         assert_eq!(truncate("hello world", 8), "hello...");
+        assert_eq!(truncate("héllo world", 5), "h...");
     }
 
     #[test]
@@ -704,6 +1942,8 @@ mod tests {
             examples: vec![],
             errors: vec![],
             deprecated: false,
+            replaced_by: None,
+            strict_params: false,
         };
 
         let tools = to_mcp(&[method]);
@@ -714,4 +1954,28 @@ mod tests {
         assert!(tools[0].input_schema.properties.is_some());
         assert_eq!(tools[0].input_schema.required, Some(vec!["limit".to_string()]));
     }
+
+    #[test]
+    fn test_to_mcp_response() {
+        let methods: Vec<MethodInfo> = (0..MCP_PAGE_SIZE + 5)
+            .map(|i| MethodInfo::new(format!("svc.m{}", i), "A method"))
+            .collect();
+
+        let first = to_mcp_response(&methods, None).unwrap();
+        assert_eq!(first.tools.len(), MCP_PAGE_SIZE);
+        assert_eq!(first.tools[0].name, "svc.m0");
+        let cursor = first.next_cursor.unwrap();
+
+        let last = to_mcp_response(&methods, Some(&cursor)).unwrap();
+        assert_eq!(last.tools.len(), 5);
+        assert_eq!(last.tools[0].name, format!("svc.m{}", MCP_PAGE_SIZE));
+        assert!(last.next_cursor.is_none());
+
+        let envelope = serde_json::to_value(to_mcp_response(&methods[..1], None).unwrap()).unwrap();
+        assert_eq!(envelope["tools"][0]["name"], "svc.m0");
+        assert!(envelope.get("nextCursor").is_none());
+
+        assert!(to_mcp_response(&methods, Some("not-a-cursor")).is_err());
+        assert!(to_mcp_response(&methods, Some("1000")).is_err());
+    }
 }
@@ -2,20 +2,31 @@
 //!
 //! The [`FgpServer`] handles socket creation, connection management, and request dispatch.
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use chrono::{SecondsFormat, Utc};
-use std::io::{BufRead, BufReader, Write};
+use serde::Serialize;
+use socket2::{Domain, SockAddr, Socket, Type};
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::io::{self, Write};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::ffi::OsStrExt;
 use std::os::unix::net::{UnixListener, UnixStream};
+use std::panic::{self, AssertUnwindSafe};
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex, OnceLock};
 use std::thread;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
 
-use crate::protocol::{self, error_codes, Response};
+use crate::logging::LogGuard;
+use crate::protocol::{self, error_codes, FramingMode, NdjsonReader, Response};
 use crate::schema;
-use crate::service::{FgpService, MethodInfo, ParamInfo};
+use crate::service::{
+    ConnectionState, FgpService, HealthReport, HealthState, MethodInfo, ParamInfo, RawJson,
+    RawJsonInner, RequestContext, ResultChunks, ServerMetrics, StopReason,
+};
 
 /// FGP daemon server.
 ///
@@ -43,9 +54,782 @@ use crate::service::{FgpService, MethodInfo, ParamInfo};
 pub struct FgpServer<S: FgpService + 'static> {
     service: Arc<S>,
     socket_path: PathBuf,
-    started_at: Arc<Instant>,
-    started_at_iso: Arc<String>,
-    running: Arc<AtomicBool>,
+    /// Replaced when `serve` binds the socket
+    started_at: Mutex<Arc<StartedAt>>,
+    shutdown: Arc<ShutdownState>,
+    connections: Arc<ConnectionCounters>,
+    drain_timeout: Duration,
+    socket_mode: u32,
+    socket_group: Option<u32>,
+    ready_file: Option<PathBuf>,
+    ready_stdout: bool,
+    backlog: Option<i32>,
+    /// Size of the connection thread pool; a thread per connection when `None`
+    connection_threads: Option<usize>,
+    pid_file: Option<PathBuf>,
+    state_file: Option<PathBuf>,
+    /// Listener passed in by systemd socket activation, taken by `serve`
+    inherited_listener: Mutex<Option<UnixListener>>,
+    /// Dropped (flushing logs) once `serve` has shut down
+    log_guard: Mutex<Option<LogGuard>>,
+    lifecycle_hook: Option<LifecycleHook>,
+    audit_sink: Option<AuditSink>,
+    cache: Option<Arc<ResponseCache>>,
+    options: Arc<ServerOptions>,
+}
+
+/// Request-handling options shared with connection threads.
+#[derive(Debug, Clone, Default)]
+struct ServerOptions {
+    /// Whether the `logs` built-in is enabled
+    logs_enabled: bool,
+    /// Whether the `reload` built-in is enabled
+    reload_enabled: bool,
+    /// Close a connection after serving this many requests
+    max_requests_per_connection: Option<usize>,
+    /// How requests and responses are delimited
+    framing: FramingMode,
+    /// Include params in audit records, with these top-level keys redacted
+    audit_params: Option<Vec<String>>,
+    /// Parse string params into the types declared by the method's schema
+    param_coercion: bool,
+    /// Warn about request fields the protocol doesn't define
+    unknown_field_warnings: bool,
+    /// Write results with their object keys sorted
+    sorted_output: bool,
+}
+
+/// State every connection needs to handle requests.
+pub(crate) struct ServerContext<S> {
+    service: Arc<S>,
+    started_at: Arc<StartedAt>,
+    shutdown: Arc<ShutdownState>,
+    connections: Arc<ConnectionCounters>,
+    options: Arc<ServerOptions>,
+    audit_sink: Option<AuditSink>,
+    cache: Option<Arc<ResponseCache>>,
+    /// Qualified method names and deprecations, read once from `method_list`
+    method_index: OnceLock<MethodIndex>,
+}
+
+/// What the server needs from `method_list`, keyed by qualified method name.
+#[derive(Default)]
+struct MethodIndex {
+    names: Vec<String>,
+    /// Deprecated methods (qualified name -> replacement)
+    deprecated: HashMap<String, Option<String>>,
+    /// Method schemas, only kept when param coercion is enabled
+    schemas: HashMap<String, serde_json::Value>,
+}
+
+impl<S> ServerContext<S> {
+    /// Context for handling requests without a socket (used by the test harness).
+    pub(crate) fn detached(service: S) -> Self {
+        let shutdown = ShutdownState::new(PathBuf::new());
+        shutdown.running.store(true, Ordering::SeqCst);
+        Self {
+            service: Arc::new(service),
+            started_at: Arc::new(StartedAt::now()),
+            shutdown: Arc::new(shutdown),
+            connections: Arc::new(ConnectionCounters::default()),
+            options: Arc::new(ServerOptions::default()),
+            audit_sink: None,
+            cache: None,
+            method_index: OnceLock::new(),
+        }
+    }
+
+    /// The service requests are dispatched to.
+    pub(crate) fn service(&self) -> &S {
+        &self.service
+    }
+
+    /// Whether the server is still running (i.e. `stop` hasn't been called).
+    pub(crate) fn is_running(&self) -> bool {
+        self.shutdown.is_running()
+    }
+
+    /// Current connection and request counters.
+    fn metrics(&self) -> ServerMetrics {
+        let mut metrics = self.connections.snapshot(&self.shutdown);
+        if let Some(cache) = &self.cache {
+            metrics.cache_hits = cache.hits.load(Ordering::SeqCst);
+            metrics.cache_misses = cache.misses.load(Ordering::SeqCst);
+        }
+        metrics
+    }
+
+    /// Pass the outcome of a request to the audit sink, if one is set.
+    fn audit(&self, request: Option<&AuditedRequest>, response: &Response) {
+        let (Some(sink), Some(request)) = (&self.audit_sink, request) else {
+            return;
+        };
+        sink(&AuditRecord {
+            at: Utc::now(),
+            id: response.id.clone(),
+            method: request.method.clone(),
+            trace_id: response.meta.trace_id.clone(),
+            peer_uid: request.peer_uid,
+            ok: response.ok,
+            error_code: response.error.as_ref().map(|e| e.code.clone()),
+            server_ms: response.meta.server_ms,
+            params: request.params.clone(),
+        });
+    }
+}
+
+impl<S: FgpService> ServerContext<S> {
+    /// Methods from `method_list`, read on the first dispatch and cached.
+    fn method_index(&self) -> &MethodIndex {
+        self.method_index.get_or_init(|| {
+            let mut index = MethodIndex::default();
+            for m in qualified_methods(&*self.service) {
+                let name = m.name.clone();
+                if self.options.param_coercion {
+                    let schema = crate::schema::get_schema_or_synthesize(&m);
+                    index.schemas.insert(name.clone(), schema);
+                }
+                if m.deprecated {
+                    index.deprecated.insert(name.clone(), m.replaced_by);
+                }
+                index.names.push(name);
+            }
+            index
+        })
+    }
+
+    /// Warning for a call to a method marked deprecated in `method_list`.
+    fn deprecation_warning(&self, method: &str) -> Option<String> {
+        Some(match self.method_index().deprecated.get(method)? {
+            Some(replacement) => {
+                format!("Method '{}' is deprecated, use '{}'", method, replacement)
+            }
+            None => format!("Method '{}' is deprecated", method),
+        })
+    }
+
+    /// Coerce string params to the types declared in the method's schema, when enabled.
+    fn coerce_params(&self, method: &str, params: &mut HashMap<String, serde_json::Value>) {
+        if !self.options.param_coercion {
+            return;
+        }
+        if let Some(schema) = self.method_index().schemas.get(method) {
+            crate::schema::coerce_params(params, schema);
+        }
+    }
+
+    /// A warning naming the request's unknown fields, when enabled and there are any.
+    fn unknown_fields_warning(&self, request: &protocol::Request) -> Option<String> {
+        if !self.options.unknown_field_warnings || request.extra.is_empty() {
+            return None;
+        }
+        let mut fields: Vec<&str> = request.extra.keys().map(String::as_str).collect();
+        fields.sort_unstable();
+        let fields = fields.join(", ");
+        warn!(
+            method = %request.method,
+            id = %request.id,
+            fields = %fields,
+            "Request has unknown fields"
+        );
+        Some(format!("Unknown request fields ignored: {}", fields))
+    }
+
+    /// Closest listed method to a call for a method not in `method_list`.
+    ///
+    /// Only suggested when the edit distance is at most a third of the name's length.
+    fn did_you_mean(&self, method: &str) -> Option<&str> {
+        let method = qualify_method(self.service.name(), &self.service.namespaces(), method)?;
+        let names = &self.method_index().names;
+        if names.contains(&method) {
+            return None;
+        }
+        names
+            .iter()
+            .map(|name| (levenshtein(&method, name), name))
+            .filter(|(distance, name)| *distance <= name.len().max(method.len()) / 3)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, name)| name.as_str())
+    }
+}
+
+/// When the daemon started: a monotonic instant for uptime and the wall-clock time.
+#[derive(Debug)]
+struct StartedAt {
+    instant: Instant,
+    iso: String,
+    /// Start history from the state file, if the server has one
+    history: Option<crate::lifecycle::RestartState>,
+}
+
+impl StartedAt {
+    fn now() -> Self {
+        Self {
+            instant: Instant::now(),
+            iso: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            history: None,
+        }
+    }
+}
+
+/// Default permission bits for the daemon socket (owner read/write only).
+pub const DEFAULT_SOCKET_MODE: u32 = 0o600;
+
+/// Default time shutdown waits for in-flight requests to finish.
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Retry hint given to requests refused while the server drains, long enough for a
+/// supervisor to bring the daemon back.
+const DRAINING_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Most dispatch threads allowed to keep running after their request timed out.
+///
+/// A method that hangs would otherwise leave one more thread behind on every call.
+/// Once the limit is reached, requests with a time budget are refused until some of
+/// those threads finish.
+const MAX_DETACHED_WORKERS: usize = 32;
+
+/// Retry hint given to requests refused because of [`MAX_DETACHED_WORKERS`].
+const DETACHED_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// Shutdown state shared with connection threads.
+///
+/// Tracks in-flight requests so shutdown can drain them before `on_stop` runs.
+#[derive(Debug)]
+struct ShutdownState {
+    running: AtomicBool,
+    /// Set once shutdown begins; unlike `running`, never set before `serve`
+    draining: AtomicBool,
+    in_flight: AtomicUsize,
+    /// Dispatch threads started for requests with a time budget, still running
+    workers: AtomicUsize,
+    /// Of `workers`, those whose request timed out and no longer waits for them
+    detached: AtomicUsize,
+    socket_path: PathBuf,
+    /// Why shutdown began, set by the first `initiate`
+    reason: OnceLock<StopReason>,
+}
+
+impl ShutdownState {
+    fn new(socket_path: PathBuf) -> Self {
+        Self {
+            running: AtomicBool::new(false),
+            draining: AtomicBool::new(false),
+            in_flight: AtomicUsize::new(0),
+            workers: AtomicUsize::new(0),
+            detached: AtomicUsize::new(0),
+            socket_path,
+            reason: OnceLock::new(),
+        }
+    }
+
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    /// Whether shutdown has begun and new requests are being refused.
+    fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+
+    /// Start draining: refuse new requests, stop accepting connections and wake the
+    /// accept loop.
+    fn initiate(&self, reason: StopReason) {
+        if self.running.swap(false, Ordering::SeqCst) {
+            let _ = self.reason.set(reason);
+            self.draining.store(true, Ordering::SeqCst);
+            // The accept loop only checks `running` when a connection arrives
+            let _ = crate::lifecycle::connect_socket(&self.socket_path);
+        }
+    }
+
+    /// Track a request until the guard is dropped; `None` once shutdown has begun.
+    fn begin_request(&self) -> Option<InFlight<'_>> {
+        // Increment before checking so drain never misses a request it raced with
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = InFlight(self);
+        (!self.is_draining()).then_some(guard)
+    }
+}
+
+/// Connection counters shared with connection threads.
+#[derive(Debug, Default)]
+struct ConnectionCounters {
+    total: AtomicU64,
+    open: AtomicUsize,
+    /// Accepted but waiting for a connection thread
+    queued: AtomicUsize,
+}
+
+impl ConnectionCounters {
+    fn snapshot(&self, shutdown: &ShutdownState) -> ServerMetrics {
+        ServerMetrics {
+            connections_total: self.total.load(Ordering::SeqCst),
+            connections_open: self.open.load(Ordering::SeqCst),
+            queued_connections: self.queued.load(Ordering::SeqCst),
+            active_requests: shutdown.in_flight.load(Ordering::SeqCst),
+            ..Default::default()
+        }
+    }
+}
+
+/// Counts a connection as open for the lifetime of the guard, and as queued until
+/// [`start`](Self::start) is called.
+struct OpenConnection {
+    counters: Arc<ConnectionCounters>,
+    queued: bool,
+}
+
+impl OpenConnection {
+    fn new(counters: &Arc<ConnectionCounters>) -> Self {
+        counters.total.fetch_add(1, Ordering::SeqCst);
+        counters.open.fetch_add(1, Ordering::SeqCst);
+        counters.queued.fetch_add(1, Ordering::SeqCst);
+        Self {
+            counters: Arc::clone(counters),
+            queued: true,
+        }
+    }
+
+    /// A thread has started handling the connection.
+    fn start(&mut self) {
+        if std::mem::take(&mut self.queued) {
+            self.counters.queued.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+impl Drop for OpenConnection {
+    fn drop(&mut self) {
+        self.start();
+        self.counters.open.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Marks a request as in flight for the lifetime of the guard.
+struct InFlight<'a>(&'a ShutdownState);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// A dispatch thread started for a request with a time budget.
+///
+/// Counted in `ShutdownState::workers` until the thread finishes, so shutdown waits
+/// for it even after its request has been answered with `TIMEOUT`.
+struct DispatchWorker {
+    shutdown: Arc<ShutdownState>,
+    /// Set by whichever comes first: the thread finishing or the request giving up
+    settled: AtomicBool,
+}
+
+impl DispatchWorker {
+    /// Count a new worker; `None` if [`MAX_DETACHED_WORKERS`] are already detached.
+    fn start(shutdown: &Arc<ShutdownState>) -> Option<Arc<Self>> {
+        if shutdown.detached.load(Ordering::SeqCst) >= MAX_DETACHED_WORKERS {
+            return None;
+        }
+        shutdown.workers.fetch_add(1, Ordering::SeqCst);
+        Some(Arc::new(Self {
+            shutdown: Arc::clone(shutdown),
+            settled: AtomicBool::new(false),
+        }))
+    }
+
+    /// The request stopped waiting for the thread.
+    fn detach(&self) {
+        // Count first so `finish` never decrements before this increments
+        self.shutdown.detached.fetch_add(1, Ordering::SeqCst);
+        if self.settled.swap(true, Ordering::SeqCst) {
+            // The thread finished in the meantime
+            self.shutdown.detached.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// The thread finished dispatching.
+    fn finish(&self) {
+        if self.settled.swap(true, Ordering::SeqCst) {
+            self.shutdown.detached.fetch_sub(1, Ordering::SeqCst);
+        }
+        self.shutdown.workers.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Callback set with [`FgpServer::with_audit_sink`].
+type AuditSink = Arc<dyn Fn(&AuditRecord) + Send + Sync>;
+
+/// One handled request, passed to [`FgpServer::with_audit_sink`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuditRecord {
+    /// When the response was sent
+    pub at: chrono::DateTime<Utc>,
+    /// Request ID
+    pub id: String,
+    /// Method as requested
+    pub method: String,
+    /// Trace/correlation ID, if the client supplied one
+    pub trace_id: Option<String>,
+    /// UID of the connecting process, where the platform reports it
+    pub peer_uid: Option<u32>,
+    /// Whether the call succeeded
+    pub ok: bool,
+    /// Error code of a failed call
+    pub error_code: Option<String>,
+    /// Server execution time in milliseconds
+    pub server_ms: f64,
+    /// Request params; only recorded after [`FgpServer::with_audit_params`]
+    pub params: Option<serde_json::Value>,
+}
+
+/// Settings for [`FgpServer::with_cache`].
+#[derive(Debug, Clone)]
+pub struct CacheConfig {
+    /// Fully-qualified names of the methods whose results are cached (e.g. `"gmail.labels"`)
+    pub methods: HashSet<String>,
+    /// How long a cached result is served before the method is called again
+    pub ttl: Duration,
+    /// Most results kept at once; the oldest is evicted to make room
+    pub max_entries: usize,
+}
+
+/// Successful results of cached methods, keyed by method and canonical params.
+struct ResponseCache {
+    config: CacheConfig,
+    entries: Mutex<HashMap<(String, String), (Instant, RawJson)>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ResponseCache {
+    fn new(config: CacheConfig) -> Self {
+        Self {
+            config,
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Cache key for a call, or `None` if the method isn't cached.
+    fn key(
+        &self,
+        method: &str,
+        params: &HashMap<String, serde_json::Value>,
+    ) -> Option<(String, String)> {
+        self.config
+            .methods
+            .contains(method)
+            .then(|| (method.to_string(), canonical_params(params)))
+    }
+
+    /// The cached result for `key`, if it hasn't expired. Counts a hit or a miss.
+    fn get(&self, key: &(String, String)) -> Option<RawJson> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let result = match entries.get(key) {
+            Some((at, result)) if at.elapsed() < self.config.ttl => Some(result.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        };
+        let counter = if result.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::SeqCst);
+        result
+    }
+
+    /// Store a result, evicting expired entries (then the oldest) when full.
+    fn insert(&self, key: (String, String), result: RawJson) {
+        let max_entries = self.config.max_entries;
+        if max_entries == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= max_entries && !entries.contains_key(&key) {
+            let ttl = self.config.ttl;
+            entries.retain(|_, (at, _)| at.elapsed() < ttl);
+            if entries.len() >= max_entries {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, (at, _))| *at)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(key, (Instant::now(), result));
+    }
+}
+
+/// What the connection loop keeps of a request for its audit record.
+struct AuditedRequest {
+    method: String,
+    peer_uid: Option<u32>,
+    params: Option<serde_json::Value>,
+}
+
+/// Callback set with [`FgpServer::with_lifecycle_hook`].
+type LifecycleHook = Arc<dyn Fn(LifecycleEvent) + Send + Sync>;
+
+/// A server lifecycle transition, passed to [`FgpServer::with_lifecycle_hook`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifecycleEvent {
+    /// What happened
+    pub kind: LifecycleEventKind,
+    /// The server's socket path
+    pub socket_path: PathBuf,
+    /// When it happened
+    pub at: chrono::DateTime<Utc>,
+}
+
+/// Kinds of [`LifecycleEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleEventKind {
+    /// The socket is bound and accepting connections
+    Started,
+    /// A client connection was accepted
+    ConnectionAccepted,
+    /// The server has drained, run `on_stop` and cleaned up
+    Stopped,
+}
+
+/// Fluent configuration for [`FgpServer`].
+///
+/// Collects the server's options in one place and applies them in
+/// [`build`](Self::build). Option errors (e.g. an invalid socket mode) are reported
+/// there. [`FgpServer::new`] remains the shortcut for a server with default options.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use fgp_daemon::FgpServerBuilder;
+/// # use fgp_daemon::FgpService;
+/// # use std::collections::HashMap;
+/// # use std::time::Duration;
+/// # use serde_json::Value;
+/// # use anyhow::Result;
+/// #
+/// # struct MyService;
+/// # impl FgpService for MyService {
+/// #     fn name(&self) -> &str { "test" }
+/// #     fn version(&self) -> &str { "1.0.0" }
+/// #     fn dispatch(&self, _: &str, _: HashMap<String, Value>) -> Result<Value> {
+/// #         Ok(Value::Null)
+/// #     }
+/// # }
+///
+/// let server = FgpServerBuilder::new()
+///     .socket_mode(0o660)
+///     .drain_timeout(Duration::from_secs(5))
+///     .logs_method(true)
+///     .build(MyService, "~/.fgp/services/test/daemon.sock")?;
+/// server.serve()?;
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FgpServerBuilder {
+    /// Socket mode and whether world-writable modes are allowed
+    socket_mode: Option<(u32, bool)>,
+    socket_group: Option<u32>,
+    ready_file: Option<PathBuf>,
+    ready_stdout: bool,
+    pid_file: Option<PathBuf>,
+    state_file: Option<PathBuf>,
+    backlog: Option<u32>,
+    connection_threads: Option<usize>,
+    drain_timeout: Option<Duration>,
+    logs_method: bool,
+    reload_method: bool,
+    param_coercion: bool,
+    unknown_field_warnings: bool,
+    sorted_output: bool,
+    max_requests_per_connection: Option<usize>,
+    framing: FramingMode,
+    cache: Option<CacheConfig>,
+}
+
+impl FgpServerBuilder {
+    /// Create a builder with default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Socket permission bits; see [`FgpServer::with_socket_mode`].
+    pub fn socket_mode(mut self, mode: u32) -> Self {
+        self.socket_mode = Some((mode, false));
+        self
+    }
+
+    /// Socket permission bits, allowing world-writable modes; see
+    /// [`FgpServer::with_world_writable_socket_mode`]. Replaces [`socket_mode`](Self::socket_mode).
+    pub fn world_writable_socket_mode(mut self, mode: u32) -> Self {
+        self.socket_mode = Some((mode, true));
+        self
+    }
+
+    /// Socket group; see [`FgpServer::with_socket_group`].
+    pub fn socket_group(mut self, gid: u32) -> Self {
+        self.socket_group = Some(gid);
+        self
+    }
+
+    /// Readiness marker file; see [`FgpServer::with_ready_file`].
+    pub fn ready_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ready_file = Some(path.into());
+        self
+    }
+
+    /// Print a readiness line to stdout; see [`FgpServer::with_ready_stdout`].
+    pub fn ready_stdout(mut self) -> Self {
+        self.ready_stdout = true;
+        self
+    }
+
+    /// PID file; see [`FgpServer::with_pid_file`].
+    pub fn pid_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.pid_file = Some(path.into());
+        self
+    }
+
+    /// Start-history file; see [`FgpServer::with_state_file`].
+    pub fn state_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.state_file = Some(path.into());
+        self
+    }
+
+    /// Listen backlog; see [`FgpServer::with_backlog`].
+    pub fn backlog(mut self, backlog: u32) -> Self {
+        self.backlog = Some(backlog);
+        self
+    }
+
+    /// Connection thread pool size; see [`FgpServer::with_connection_threads`].
+    pub fn connection_threads(mut self, threads: usize) -> Self {
+        self.connection_threads = Some(threads);
+        self
+    }
+
+    /// Shutdown drain timeout; see [`FgpServer::with_drain_timeout`].
+    pub fn drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = Some(timeout);
+        self
+    }
+
+    /// Enable the `logs` built-in; see [`FgpServer::with_logs_method`].
+    pub fn logs_method(mut self, enabled: bool) -> Self {
+        self.logs_method = enabled;
+        self
+    }
+
+    /// Enable the `reload` built-in; see [`FgpServer::with_reload_method`].
+    pub fn reload_method(mut self, enabled: bool) -> Self {
+        self.reload_method = enabled;
+        self
+    }
+
+    /// Coerce string params to their declared types; see
+    /// [`FgpServer::with_param_coercion`].
+    pub fn param_coercion(mut self, enabled: bool) -> Self {
+        self.param_coercion = enabled;
+        self
+    }
+
+    /// Warn about unknown request fields; see [`FgpServer::with_unknown_field_warnings`].
+    pub fn unknown_field_warnings(mut self, enabled: bool) -> Self {
+        self.unknown_field_warnings = enabled;
+        self
+    }
+
+    /// Sort result keys; see [`FgpServer::with_sorted_output`].
+    pub fn sorted_output(mut self, enabled: bool) -> Self {
+        self.sorted_output = enabled;
+        self
+    }
+
+    /// Response cache for pure methods; see [`FgpServer::with_cache`].
+    pub fn cache(mut self, config: CacheConfig) -> Self {
+        self.cache = Some(config);
+        self
+    }
+
+    /// Per-connection request limit; see [`FgpServer::with_max_requests_per_connection`].
+    pub fn max_requests_per_connection(mut self, max: usize) -> Self {
+        self.max_requests_per_connection = Some(max);
+        self
+    }
+
+    /// Message framing; see [`FgpServer::with_framing`].
+    pub fn framing(mut self, framing: FramingMode) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Create the server on `socket_path` with these options.
+    pub fn build<S: FgpService + 'static>(
+        self,
+        service: S,
+        socket_path: impl AsRef<Path>,
+    ) -> Result<FgpServer<S>> {
+        self.apply(FgpServer::new(service, socket_path)?)
+    }
+
+    /// Create the server at the service's standard location; see
+    /// [`FgpServer::for_service`].
+    pub fn build_for_service<S: FgpService + 'static>(self, service: S) -> Result<FgpServer<S>> {
+        self.apply(FgpServer::for_service(service)?)
+    }
+
+    fn apply<S: FgpService + 'static>(self, mut server: FgpServer<S>) -> Result<FgpServer<S>> {
+        server = match self.socket_mode {
+            Some((mode, false)) => server.with_socket_mode(mode)?,
+            Some((mode, true)) => server.with_world_writable_socket_mode(mode)?,
+            None => server,
+        };
+        if let Some(gid) = self.socket_group {
+            server = server.with_socket_group(gid);
+        }
+        if let Some(path) = self.ready_file {
+            server = server.with_ready_file(path)?;
+        }
+        if self.ready_stdout {
+            server = server.with_ready_stdout();
+        }
+        if let Some(path) = self.pid_file {
+            server = server.with_pid_file(path)?;
+        }
+        if let Some(path) = self.state_file {
+            server = server.with_state_file(path)?;
+        }
+        if let Some(backlog) = self.backlog {
+            server = server.with_backlog(backlog);
+        }
+        if let Some(threads) = self.connection_threads {
+            server = server.with_connection_threads(threads);
+        }
+        if let Some(timeout) = self.drain_timeout {
+            server = server.with_drain_timeout(timeout);
+        }
+        if let Some(max) = self.max_requests_per_connection {
+            server = server.with_max_requests_per_connection(max);
+        }
+        if let Some(config) = self.cache {
+            server = server.with_cache(config);
+        }
+        Ok(server
+            .with_logs_method(self.logs_method)
+            .with_reload_method(self.reload_method)
+            .with_param_coercion(self.param_coercion)
+            .with_unknown_field_warnings(self.unknown_field_warnings)
+            .with_sorted_output(self.sorted_output)
+            .with_framing(self.framing))
+    }
 }
 
 impl<S: FgpService + 'static> FgpServer<S> {
@@ -53,51 +837,576 @@ impl<S: FgpService + 'static> FgpServer<S> {
     ///
     /// # Arguments
     /// * `service` - The service implementation
-    /// * `socket_path` - Path to the UNIX socket (supports `~` expansion)
+    /// * `socket_path` - Path to the UNIX socket (supports `~` expansion). On Linux,
+    ///   `@name` binds the abstract socket `name` instead: it has no file to clean up
+    ///   and no permissions, so the socket mode and group options don't apply.
     pub fn new(service: S, socket_path: impl AsRef<Path>) -> Result<Self> {
         let socket_path = expand_path(socket_path.as_ref())?;
-        let started_at_iso = Arc::new(Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true));
+        let shutdown = Arc::new(ShutdownState::new(socket_path.clone()));
+
+        if crate::lifecycle::abstract_socket_name(&socket_path).is_none() {
+            // Create parent directory if needed
+            if let Some(parent) = socket_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+        } else if !cfg!(target_os = "linux") {
+            bail!("Abstract UNIX sockets (@name) are only supported on Linux");
+        }
+
+        Ok(Self {
+            service: Arc::new(service),
+            socket_path,
+            started_at: Mutex::new(Arc::new(StartedAt::now())),
+            shutdown,
+            connections: Arc::new(ConnectionCounters::default()),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+            socket_mode: DEFAULT_SOCKET_MODE,
+            socket_group: None,
+            ready_file: None,
+            ready_stdout: false,
+            backlog: None,
+            connection_threads: None,
+            pid_file: None,
+            state_file: None,
+            inherited_listener: Mutex::new(None),
+            log_guard: Mutex::new(None),
+            lifecycle_hook: None,
+            audit_sink: None,
+            cache: None,
+            options: Arc::new(ServerOptions::default()),
+        })
+    }
+
+    /// Create a server on the socket path given by the environment.
+    ///
+    /// In order of precedence: `FGP_SOCKET`, the standard path of the service named by
+    /// `FGP_SERVICE`, then the standard path for `service.name()`
+    /// (`~/.fgp/services/<name>/daemon.sock`). See
+    /// [`socket_path_from_env`](crate::lifecycle::socket_path_from_env).
+    pub fn from_env(service: S) -> Result<Self> {
+        let socket_path = crate::lifecycle::socket_path_from_env()
+            .unwrap_or_else(|| crate::lifecycle::service_socket_path(service.name()));
+        Self::new(service, socket_path)
+    }
+
+    /// Create a server that uses the listener passed in by systemd socket activation.
+    ///
+    /// When `LISTEN_PID` names this process and `LISTEN_FDS` is at least 1, the first
+    /// passed descriptor is used as the listening socket and its bound path becomes the
+    /// server's socket path. `serve` then neither binds nor removes the socket file, and
+    /// leaves its permissions to the `.socket` unit. The activation variables are
+    /// removed from the environment so child processes don't inherit them.
+    ///
+    /// Without socket activation this behaves like [`new`](Self::new) with
+    /// `socket_path`.
+    pub fn from_systemd(service: S, socket_path: impl AsRef<Path>) -> Result<Self> {
+        let Some(listener) = systemd_listener()? else {
+            return Self::new(service, socket_path);
+        };
+
+        let addr = listener
+            .local_addr()
+            .context("Socket-activated descriptor is not a UNIX socket")?;
+        let path = match addr.as_pathname() {
+            Some(path) => path.to_path_buf(),
+            None => match abstract_name(&addr) {
+                Some(name) => PathBuf::from(OsStr::from_bytes(&[b"@", name].concat())),
+                None => bail!("Socket-activated listener has no address"),
+            },
+        };
+
+        let server = Self::new(service, &path)?;
+        *server
+            .inherited_listener
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(listener);
+        info!(socket = %path.display(), "Using socket-activated listener");
+        Ok(server)
+    }
+
+    /// Create a server at the service's standard location.
+    ///
+    /// The socket path is `~/.fgp/services/<name>/daemon.sock` (see
+    /// [`service_socket_path`](crate::lifecycle::service_socket_path)), and
+    /// [`serve`](Self::serve) writes the PID to `daemon.pid` next to it so
+    /// [`stop_service`](crate::lifecycle::stop_service) can find the process. Starts are
+    /// recorded in `state.json` there (see [`with_state_file`](Self::with_state_file)).
+    pub fn for_service(service: S) -> Result<Self> {
+        let name = service.name().to_string();
+        let mut server = Self::new(service, crate::lifecycle::service_socket_path(&name))?;
+        server.pid_file = Some(crate::lifecycle::service_pid_path(&name));
+        server.state_file = Some(crate::lifecycle::service_state_path(&name));
+        Ok(server)
+    }
+
+    /// Enable the `logs` built-in method.
+    ///
+    /// When enabled, clients can fetch the tail of the daemon's log file
+    /// (`~/.fgp/services/<name>/logs/daemon.log`) without shell access to the host.
+    /// Disabled by default since logs may contain sensitive information.
+    pub fn with_logs_method(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.options).logs_enabled = enabled;
+        self
+    }
+
+    /// Enable the `reload` built-in method.
+    ///
+    /// When enabled, clients can call `reload` to run [`FgpService::reload`], e.g. to
+    /// pick up rotated credentials without dropping the socket. Disabled by default
+    /// since anyone who can connect could trigger it.
+    pub fn with_reload_method(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.options).reload_enabled = enabled;
+        self
+    }
+
+    /// Parse string params into the types their method's schema declares.
+    ///
+    /// LLM clients often send `"10"` for an integer or `"true"` for a boolean. When
+    /// enabled, such values are converted before dispatch, including calls inside
+    /// `bundle` (see [`schema::coerce_params`](crate::schema::coerce_params)).
+    /// Coercion only applies to top-level params whose type the method's schema (or
+    /// `params` list) in [`FgpService::method_list`] declares; anything else reaches
+    /// the service unchanged. Disabled by default.
+    pub fn with_param_coercion(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.options).param_coercion = enabled;
+        self
+    }
+
+    /// Report request fields the protocol doesn't define.
+    ///
+    /// Unknown fields (such as a misspelled `parms`) are always ignored. When enabled,
+    /// each request carrying any is logged as a warning and answered with a
+    /// `meta.warnings` entry naming them, so client bugs where params silently vanish
+    /// show up. Disabled by default.
+    pub fn with_unknown_field_warnings(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.options).unknown_field_warnings = enabled;
+        self
+    }
+
+    /// Write every result with its object keys in sorted order.
+    ///
+    /// Makes responses byte-for-byte stable, for hashing them or for snapshot tests.
+    /// Costs a copy of each result, and raw results from
+    /// [`FgpService::dispatch_raw`] are parsed instead of written verbatim. Error
+    /// responses and `meta` are left as they are. Disabled by default.
+    pub fn with_sorted_output(mut self, enabled: bool) -> Self {
+        Arc::make_mut(&mut self.options).sorted_output = enabled;
+        self
+    }
+
+    /// Close each connection after it has served `max` requests.
+    ///
+    /// Bounds the lifetime of long-lived persistent connections. The last response
+    /// carries `meta.connection_close`, and persistent [`FgpClient`](crate::FgpClient)s
+    /// reconnect transparently. Unlimited by default; `0` is treated as `1`.
+    pub fn with_max_requests_per_connection(mut self, max: usize) -> Self {
+        Arc::make_mut(&mut self.options).max_requests_per_connection = Some(max.max(1));
+        self
+    }
+
+    /// Delimit messages with `framing` instead of NDJSON lines.
+    ///
+    /// Clients must be configured to match with
+    /// [`FgpClient::with_framing`](crate::FgpClient::with_framing).
+    /// [`FramingMode::LengthPrefixed`] avoids scanning large payloads for line breaks,
+    /// at the cost of no longer being able to talk to the daemon with `nc`.
+    pub fn with_framing(mut self, framing: FramingMode) -> Self {
+        Arc::make_mut(&mut self.options).framing = framing;
+        self
+    }
+
+    /// Override the permission bits applied to the socket after bind.
+    ///
+    /// Defaults to [`DEFAULT_SOCKET_MODE`] (`0o600`), which only lets the daemon's own
+    /// user connect. Use `0o660` together with [`with_socket_group`](Self::with_socket_group)
+    /// when clients run as a different user in a shared group.
+    ///
+    /// Anyone who can connect to the socket can call every method the service exposes,
+    /// including `stop`, so grant group access only to trusted groups. World-writable
+    /// modes are rejected here; see
+    /// [`with_world_writable_socket_mode`](Self::with_world_writable_socket_mode).
+    pub fn with_socket_mode(self, mode: u32) -> Result<Self> {
+        if mode & 0o002 != 0 {
+            bail!(
+                "Socket mode {:#o} is world-writable; use with_world_writable_socket_mode to allow it",
+                mode
+            );
+        }
+        self.with_world_writable_socket_mode(mode)
+    }
+
+    /// Like [`with_socket_mode`](Self::with_socket_mode), but permits world-writable modes.
+    ///
+    /// A world-writable socket lets any local user call the daemon. Only use this when
+    /// the service is designed to be safe for untrusted local callers.
+    pub fn with_world_writable_socket_mode(mut self, mode: u32) -> Result<Self> {
+        if mode & !0o777 != 0 {
+            bail!(
+                "Invalid socket mode {:#o}: only permission bits (0o777) are allowed",
+                mode
+            );
+        }
+        if mode & 0o600 != 0o600 {
+            bail!(
+                "Invalid socket mode {:#o}: the owner must be able to read and write the socket",
+                mode
+            );
+        }
+        self.socket_mode = mode;
+        Ok(self)
+    }
+
+    /// Change the socket's group after bind (the owner is left unchanged).
+    ///
+    /// The daemon's user must be a member of the group (or be root) for this to succeed.
+    /// Has no effect on access unless the socket mode grants group permissions.
+    pub fn with_socket_group(mut self, gid: u32) -> Self {
+        self.socket_group = Some(gid);
+        self
+    }
+
+    /// Write a readiness marker file once the socket is bound and accepting.
+    ///
+    /// The file contains a single JSON object (`{"event":"ready","socket":...,"pid":...}`)
+    /// and is removed when the server stops. Use
+    /// [`service_ready_path`](crate::lifecycle::service_ready_path) for the standard location,
+    /// which [`start_service`](crate::lifecycle::start_service) waits on.
+    pub fn with_ready_file(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        self.ready_file = Some(expand_path(path.as_ref())?);
+        Ok(self)
+    }
+
+    /// Write the daemon's PID to `path` on start and remove it on clean shutdown.
+    ///
+    /// [`for_service`](Self::for_service) sets this to the standard `daemon.pid` location,
+    /// which [`stop_service`](crate::lifecycle::stop_service) uses to find the process.
+    pub fn with_pid_file(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        self.pid_file = Some(expand_path(path.as_ref())?);
+        Ok(self)
+    }
+
+    /// Record each start in a state file at `path` that persists across restarts.
+    ///
+    /// [`serve`](Self::serve) bumps its `restart_count` (see
+    /// [`record_start`](crate::lifecycle::record_start)), and `health` reports
+    /// `restart_count` and `first_started_at`, so a daemon stuck in a crash loop stands
+    /// out even though each fresh process looks healthy. Use
+    /// [`service_state_path`](crate::lifecycle::service_state_path) for the standard
+    /// location, which [`for_service`](Self::for_service) sets. A state file that can't
+    /// be written is logged and otherwise ignored.
+    pub fn with_state_file(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        self.state_file = Some(expand_path(path.as_ref())?);
+        Ok(self)
+    }
+
+    /// Print a `{"event":"ready",...}` line to stdout once the socket is accepting.
+    ///
+    /// Useful for supervisors that launch the daemon as a child and read its stdout.
+    pub fn with_ready_stdout(mut self) -> Self {
+        self.ready_stdout = true;
+        self
+    }
+
+    /// Set the listen backlog (pending connections queued before `accept`).
+    ///
+    /// By default the standard library's backlog is used (128 on most platforms).
+    /// Raise it if many clients connect at once, e.g. right after the daemon starts,
+    /// and see spurious `ECONNREFUSED`/`EAGAIN` errors.
+    ///
+    /// The kernel silently caps the value: on Linux at `net.core.somaxconn`, on macOS
+    /// at `kern.ipc.somaxconn`. Raise those sysctls too if you need a larger queue.
+    pub fn with_backlog(mut self, backlog: u32) -> Self {
+        self.backlog = Some(backlog.min(i32::MAX as u32) as i32);
+        self
+    }
 
-        // Create parent directory if needed
-        if let Some(parent) = socket_path.parent() {
-            std::fs::create_dir_all(parent)?;
+    /// Handle connections on a pool of `threads` threads instead of a thread each.
+    ///
+    /// Bounds the daemon's threads when many clients connect. Connections beyond the
+    /// pool's size wait for a free thread and are reported as `queued_connections` in
+    /// the health metrics. A connection holds its thread until the client disconnects,
+    /// so size the pool above the number of persistent clients you expect. Unlimited
+    /// by default; `0` is treated as `1`.
+    pub fn with_connection_threads(mut self, threads: usize) -> Self {
+        self.connection_threads = Some(threads.max(1));
+        self
+    }
+
+    /// Set how long shutdown waits for in-flight requests to finish.
+    ///
+    /// Defaults to [`DEFAULT_DRAIN_TIMEOUT`] (30 seconds).
+    pub fn with_drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = timeout;
+        self
+    }
+
+    /// Observe the server's lifecycle: bind, each accepted connection, and shutdown.
+    ///
+    /// Unlike the service's `on_start` / `on_stop`, this is for infrastructure wrapping
+    /// the server and works with any service. The hook runs on the accept thread, so
+    /// keep it quick. Setting a new hook replaces the previous one.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use fgp_daemon::server::LifecycleEventKind;
+    ///
+    /// let server = FgpServer::new(MyService, "~/.fgp/services/test/daemon.sock")?
+    ///     .with_lifecycle_hook(|event| {
+    ///         if event.kind != LifecycleEventKind::ConnectionAccepted {
+    ///             eprintln!("{:?} at {}", event.kind, event.at);
+    ///         }
+    ///     });
+    /// ```
+    pub fn with_lifecycle_hook(
+        mut self,
+        hook: impl Fn(LifecycleEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.lifecycle_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Record every handled request in an audit trail, separate from the log.
+    ///
+    /// The sink is called on the connection's thread after each response is written
+    /// (or the client has gone), with who called what and the outcome. Params are left
+    /// out unless enabled with [`with_audit_params`](Self::with_audit_params). Requests
+    /// that can't be parsed have no method and are not audited. Setting a new sink
+    /// replaces the previous one.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// use std::io::Write;
+    /// use std::sync::Mutex;
+    ///
+    /// let trail = Mutex::new(std::fs::File::create("/var/log/gmail-audit.jsonl")?);
+    /// let server = FgpServer::new(MyService, "~/.fgp/services/test/daemon.sock")?
+    ///     .with_audit_sink(move |record| {
+    ///         let line = serde_json::to_string(record).unwrap();
+    ///         let _ = writeln!(trail.lock().unwrap(), "{}", line);
+    ///     });
+    /// ```
+    pub fn with_audit_sink(mut self, sink: impl Fn(&AuditRecord) + Send + Sync + 'static) -> Self {
+        self.audit_sink = Some(Arc::new(sink));
+        self
+    }
+
+    /// Serve repeated calls to pure methods from an in-memory cache.
+    ///
+    /// Successful results of the `config.methods` are kept for `config.ttl`, keyed by
+    /// method and params (compared with object keys sorted, so key order doesn't
+    /// matter). A call with the same params within the TTL gets the cached result
+    /// without calling `dispatch`, marked with `meta.cached`. Errors are never cached.
+    /// Hits and misses are counted in the `health` metrics. Only use this for methods
+    /// whose result depends on nothing but their params.
+    pub fn with_cache(mut self, config: CacheConfig) -> Self {
+        self.cache = Some(Arc::new(ResponseCache::new(config)));
+        self
+    }
+
+    /// Include request params in audit records, replacing the values of the `redact`
+    /// keys (top-level only) with `"[REDACTED]"`.
+    pub fn with_audit_params(mut self, redact: &[&str]) -> Self {
+        Arc::make_mut(&mut self.options).audit_params =
+            Some(redact.iter().map(|key| key.to_string()).collect());
+        self
+    }
+
+    /// Hold the logging guard until the server shuts down.
+    ///
+    /// The guard is dropped after [`on_stop`](FgpService::on_stop) and the final log
+    /// line, so everything logged during shutdown is flushed. Combine with
+    /// [`serve_with_signals`](Self::serve_with_signals) so a `SIGTERM` also shuts down
+    /// through this path.
+    pub fn with_log_guard(self, guard: LogGuard) -> Self {
+        *self.log_guard.lock().unwrap_or_else(|e| e.into_inner()) = Some(guard);
+        self
+    }
+
+    /// Get the socket path.
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    /// Start serving requests (blocking).
+    ///
+    /// This method blocks until `stop()` is called or the process receives a signal.
+    /// Connections are handled concurrently using threads for parallel request processing.
+    pub fn serve(&self) -> Result<()> {
+        // Call service on_start hook
+        self.service.on_start()?;
+
+        let (listener, owns_socket_file) = match self.listen() {
+            Ok(listening) => listening,
+            Err(e) => {
+                // The service has started, so let it clean up
+                let _ = self.service.on_stop_with_reason(StopReason::Error);
+                return Err(e);
+            }
+        };
+
+        // Accept connections and hand each to its own thread, or to the pool
+        let server = Arc::new(self.context());
+        let pool = self
+            .connection_threads
+            .map(|threads| Self::spawn_connection_pool(threads, &server));
+        for stream in listener.incoming() {
+            if !self.shutdown.is_running() {
+                break;
+            }
+
+            match stream {
+                Ok(stream) => {
+                    self.emit(LifecycleEventKind::ConnectionAccepted);
+                    let connection = OpenConnection::new(&self.connections);
+                    match &pool {
+                        Some(pool) => {
+                            // Only fails once every pool thread has died
+                            let _ = pool.send((stream, connection));
+                        }
+                        None => {
+                            let server = Arc::clone(&server);
+                            thread::spawn(move || {
+                                Self::run_connection(stream, connection, &server)
+                            });
+                        }
+                    }
+                }
+                Err(e) => {
+                    warn!(error = %e, "Accept error");
+                }
+            }
         }
 
-        Ok(Self {
-            service: Arc::new(service),
-            socket_path,
-            started_at: Arc::new(Instant::now()),
-            started_at_iso,
-            running: Arc::new(AtomicBool::new(false)),
-        })
+        // Pool threads exit once the connections already queued are handled
+        drop(pool);
+
+        // Let in-flight requests finish before tearing down
+        self.drain();
+
+        // Call service on_stop hook
+        let reason = self
+            .shutdown
+            .reason
+            .get()
+            .copied()
+            .unwrap_or(StopReason::MethodRequest);
+        let _ = self.service.on_stop_with_reason(reason);
+
+        self.remove_files(owns_socket_file);
+
+        info!(service = self.service.name(), "FGP daemon stopped");
+        self.emit(LifecycleEventKind::Stopped);
+        // Flush buffered log lines
+        *self.log_guard.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        Ok(())
     }
 
-    /// Get the socket path.
-    pub fn socket_path(&self) -> &Path {
-        &self.socket_path
+    /// Handle one connection, counting it as no longer queued.
+    fn run_connection(stream: UnixStream, connection: OpenConnection, server: &ServerContext<S>) {
+        // Dropped on every exit path, including panics
+        let mut connection = connection;
+        connection.start();
+        if let Err(e) = Self::handle_connection_static(stream, server) {
+            error!(error = %e, "Connection error");
+        }
     }
 
-    /// Start serving requests (blocking).
+    /// Start `threads` connection threads, returning the queue that feeds them.
+    fn spawn_connection_pool(
+        threads: usize,
+        server: &Arc<ServerContext<S>>,
+    ) -> mpsc::Sender<(UnixStream, OpenConnection)> {
+        let (tx, rx) = mpsc::channel::<(UnixStream, OpenConnection)>();
+        let rx = Arc::new(Mutex::new(rx));
+        for _ in 0..threads {
+            let rx = Arc::clone(&rx);
+            let server = Arc::clone(server);
+            thread::spawn(move || loop {
+                let next = rx.lock().unwrap_or_else(|e| e.into_inner()).recv();
+                let Ok((stream, connection)) = next else {
+                    break;
+                };
+                // Keep the thread in the pool if handling the connection panics
+                let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+                    Self::run_connection(stream, connection, &server)
+                }));
+            });
+        }
+        tx
+    }
+
+    /// Bind (or take over) the listener and announce that the daemon is ready.
     ///
-    /// This method blocks until `stop()` is called or the process receives a signal.
-    /// Connections are handled concurrently using threads for parallel request processing.
-    pub fn serve(&self) -> Result<()> {
-        // Call service on_start hook
-        self.service.on_start()?;
+    /// Returns the listener and whether the daemon owns the socket file.
+    fn listen(&self) -> Result<(UnixListener, bool)> {
+        let inherited = self
+            .inherited_listener
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        // Neither a socket-activated listener (owned by systemd) nor an abstract
+        // socket has a file for us to manage
+        let owns_socket_file = inherited.is_none()
+            && crate::lifecycle::abstract_socket_name(&self.socket_path).is_none();
+
+        let listener = match inherited {
+            Some(listener) => listener,
+            None => {
+                // Clean up stale socket
+                if owns_socket_file {
+                    let _ = std::fs::remove_file(&self.socket_path);
+                }
+                self.bind_listener()?
+            }
+        };
+
+        if let Err(e) = self.announce(owns_socket_file) {
+            // Don't leave a socket behind that nothing will accept on
+            self.shutdown.running.store(false, Ordering::SeqCst);
+            self.remove_files(owns_socket_file);
+            return Err(e);
+        }
 
-        // Clean up stale socket
-        let _ = std::fs::remove_file(&self.socket_path);
+        Ok((listener, owns_socket_file))
+    }
 
-        let listener = UnixListener::bind(&self.socket_path)?;
+    /// Record the start, apply socket permissions, write the PID file and signal
+    /// readiness, once the listener is bound.
+    fn announce(&self, owns_socket_file: bool) -> Result<()> {
+        let history = self.state_file.as_ref().and_then(|path| {
+            crate::lifecycle::record_start(path)
+                .map_err(|e| warn!(error = %e, "Failed to record start in state file"))
+                .ok()
+        });
+        // Uptime counts from when the socket accepts connections, not from `new`
+        *self.started_at.lock().unwrap_or_else(|e| e.into_inner()) = Arc::new(StartedAt {
+            history,
+            ..StartedAt::now()
+        });
 
-        // Set permissions to owner-only (0600)
+        // Set permissions (owner-only 0600 unless configured otherwise); a
+        // socket-activated listener keeps the ones from its `.socket` unit
         #[cfg(unix)]
-        {
+        if owns_socket_file {
             use std::os::unix::fs::PermissionsExt;
-            std::fs::set_permissions(&self.socket_path, std::fs::Permissions::from_mode(0o600))?;
+            std::fs::set_permissions(
+                &self.socket_path,
+                std::fs::Permissions::from_mode(self.socket_mode),
+            )?;
+
+            if let Some(gid) = self.socket_group {
+                std::os::unix::fs::chown(&self.socket_path, None, Some(gid))
+                    .with_context(|| format!("Failed to set socket group to {}", gid))?;
+            }
         }
 
-        self.running.store(true, Ordering::SeqCst);
+        if let Some(pid_file) = &self.pid_file {
+            crate::lifecycle::write_pid_file(pid_file)?;
+        }
+
+        self.shutdown.running.store(true, Ordering::SeqCst);
 
         info!(
             service = self.service.name(),
@@ -106,89 +1415,208 @@ impl<S: FgpService + 'static> FgpServer<S> {
             "FGP daemon started (concurrent mode)"
         );
 
-        // Accept connections and spawn thread for each (concurrent)
-        for stream in listener.incoming() {
-            if !self.running.load(Ordering::SeqCst) {
-                break;
+        self.signal_ready()?;
+        self.emit(LifecycleEventKind::Started);
+
+        Ok(())
+    }
+
+    /// Remove the socket file (unless systemd owns it), the ready file and the PID
+    /// file.
+    fn remove_files(&self, owns_socket_file: bool) {
+        if owns_socket_file {
+            let _ = std::fs::remove_file(&self.socket_path);
+        }
+        if let Some(ready_file) = &self.ready_file {
+            let _ = std::fs::remove_file(ready_file);
+        }
+        // Leave the PID file alone if another instance has since taken it over
+        if let Some(pid_file) = &self.pid_file {
+            if crate::lifecycle::read_pid_file(pid_file) == Some(std::process::id()) {
+                let _ = std::fs::remove_file(pid_file);
             }
+        }
+    }
 
-            match stream {
-                Ok(stream) => {
-                    // Clone Arcs for the spawned thread
-                    let service = Arc::clone(&self.service);
-                    let started_at = Arc::clone(&self.started_at);
-                    let started_at_iso = Arc::clone(&self.started_at_iso);
-                    let running = Arc::clone(&self.running);
-
-                    thread::spawn(move || {
-                        if let Err(e) = Self::handle_connection_static(
-                            stream,
-                            &service,
-                            &started_at,
-                            &started_at_iso,
-                            &running,
-                        ) {
-                            error!(error = %e, "Connection error");
-                        }
-                    });
-                }
-                Err(e) => {
-                    warn!(error = %e, "Accept error");
-                }
+    /// Like [`serve`](Self::serve), but `SIGTERM` and `SIGINT` stop the server gracefully.
+    ///
+    /// In-flight requests are drained, `on_stop` runs and the socket, PID and ready
+    /// files are cleaned up, as with the `stop` method. The signals' default handling
+    /// is restored when this returns.
+    pub fn serve_with_signals(&self) -> Result<()> {
+        let (read_end, write_end) = signal_pipe()?;
+        SIGNAL_PIPE.store(write_end.as_raw_fd(), Ordering::SeqCst);
+        for signal in SHUTDOWN_SIGNALS {
+            set_signal_handler(
+                signal,
+                on_shutdown_signal as *const () as libc::sighandler_t,
+            )?;
+        }
+
+        let shutdown = Arc::clone(&self.shutdown);
+        let watcher = thread::spawn(move || {
+            let mut signalled = [0u8; 1];
+            // Returns 0 once the write end is closed below
+            if let Ok(1) = io::Read::read(&mut std::fs::File::from(read_end), &mut signalled) {
+                info!("Received shutdown signal");
+                shutdown.initiate(StopReason::Signal);
             }
+        });
+
+        let result = self.serve();
+
+        for signal in SHUTDOWN_SIGNALS {
+            let _ = set_signal_handler(signal, libc::SIG_DFL);
         }
+        SIGNAL_PIPE.store(-1, Ordering::SeqCst);
+        drop(write_end);
+        let _ = watcher.join();
+        result
+    }
 
-        // Call service on_stop hook
-        let _ = self.service.on_stop();
+    /// Bind the listening socket, applying the configured backlog if any.
+    fn bind_listener(&self) -> Result<UnixListener> {
+        let Some(backlog) = self.backlog else {
+            return Ok(crate::lifecycle::bind_socket(&self.socket_path)?);
+        };
+
+        // A leading NUL byte makes the address abstract
+        let address = match crate::lifecycle::abstract_socket_name(&self.socket_path) {
+            Some(name) => SockAddr::unix(OsStr::from_bytes(&[b"\0", name].concat()))?,
+            None => SockAddr::unix(&self.socket_path)?,
+        };
+        let socket = Socket::new(Domain::UNIX, Type::STREAM, None)?;
+        socket.bind(&address)?;
+        socket
+            .listen(backlog)
+            .with_context(|| format!("Failed to listen with backlog {}", backlog))?;
+        Ok(UnixListener::from(OwnedFd::from(socket)))
+    }
+
+    /// Pass a lifecycle event to the hook, if one is set.
+    fn emit(&self, kind: LifecycleEventKind) {
+        if let Some(hook) = &self.lifecycle_hook {
+            hook(LifecycleEvent {
+                kind,
+                socket_path: self.socket_path.clone(),
+                at: Utc::now(),
+            });
+        }
+    }
+
+    /// Emit the configured readiness signals.
+    fn signal_ready(&self) -> Result<()> {
+        if self.ready_file.is_none() && !self.ready_stdout {
+            return Ok(());
+        }
 
-        // Cleanup
-        let _ = std::fs::remove_file(&self.socket_path);
+        let event = serde_json::json!({
+            "event": "ready",
+            "service": self.service.name(),
+            "socket": self.socket_path.display().to_string(),
+            "pid": std::process::id(),
+        });
+
+        if let Some(ready_file) = &self.ready_file {
+            if let Some(parent) = ready_file.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            // Write then rename so readers never observe a partial file
+            let tmp = ready_file.with_extension("ready.tmp");
+            std::fs::write(&tmp, format!("{}\n", event))?;
+            std::fs::rename(&tmp, ready_file).context("Failed to write readiness file")?;
+        }
+
+        if self.ready_stdout {
+            let mut stdout = io::stdout().lock();
+            writeln!(stdout, "{}", event)?;
+            stdout.flush()?;
+        }
 
-        info!(service = self.service.name(), "FGP daemon stopped");
         Ok(())
     }
 
     /// Stop the server gracefully.
+    ///
+    /// New connections are refused, further requests on open connections get a
+    /// `SERVICE_UNAVAILABLE` error with a retry hint, and `serve` returns once in-flight
+    /// requests have finished (or the drain timeout elapses).
     pub fn stop(&self) {
-        self.running.store(false, Ordering::SeqCst);
+        self.shutdown.initiate(StopReason::MethodRequest);
+    }
+
+    /// Wait for in-flight requests, and dispatches that outlived their request's time
+    /// budget, to finish, up to the drain timeout.
+    fn drain(&self) {
+        let deadline = Instant::now() + self.drain_timeout;
+        loop {
+            let in_flight = self.shutdown.in_flight.load(Ordering::SeqCst);
+            let workers = self.shutdown.workers.load(Ordering::SeqCst);
+            if in_flight == 0 && workers == 0 {
+                return;
+            }
+            if Instant::now() >= deadline {
+                warn!(
+                    in_flight,
+                    workers, "Drain timed out with requests still in flight"
+                );
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
     }
 
     /// Handle a single client connection (instance method - calls static version).
     #[allow(dead_code)]
     fn handle_connection(&self, stream: UnixStream) -> Result<()> {
-        Self::handle_connection_static(
-            stream,
-            &self.service,
-            &self.started_at,
-            &self.started_at_iso,
-            &self.running,
-        )
+        Self::handle_connection_static(stream, &self.context())
+    }
+
+    /// Snapshot of the state connections need, shared with connection threads.
+    fn context(&self) -> ServerContext<S> {
+        ServerContext {
+            service: Arc::clone(&self.service),
+            started_at: Arc::clone(&self.started_at.lock().unwrap_or_else(|e| e.into_inner())),
+            shutdown: Arc::clone(&self.shutdown),
+            connections: Arc::clone(&self.connections),
+            options: Arc::clone(&self.options),
+            audit_sink: self.audit_sink.clone(),
+            cache: self.cache.clone(),
+            method_index: OnceLock::new(),
+        }
     }
 
     /// Handle a single client connection (static version for thread spawning).
-    fn handle_connection_static(
-        stream: UnixStream,
-        service: &Arc<S>,
-        started_at: &Arc<Instant>,
-        started_at_iso: &Arc<String>,
-        running: &Arc<AtomicBool>,
-    ) -> Result<()> {
+    fn handle_connection_static(stream: UnixStream, server: &ServerContext<S>) -> Result<()> {
         let writer_stream = stream.try_clone()?;
-        let mut reader = BufReader::new(&stream);
+        let framing = server.options.framing;
+        let peer_uid = server.audit_sink.as_ref().and_then(|_| peer_uid(&stream));
+        let mut reader = NdjsonReader::new(&stream).with_framing(framing);
         let mut writer = writer_stream;
+        // Dropped when this connection ends
+        let connection_state = server.service.new_connection_state();
 
-        // Read NDJSON requests (one line at a time)
-        let mut line = String::new();
+        // Read requests (one line or frame at a time)
+        let mut served = 0usize;
         loop {
-            line.clear();
-            let bytes = reader.read_line(&mut line)?;
-            if bytes == 0 {
-                return Ok(()); // Client disconnected
-            }
-
-            if line.trim().is_empty() {
-                continue;
-            }
+            let line = match reader.next_line() {
+                Ok(Some(line)) => line,
+                Ok(None) => return Ok(()), // Client disconnected
+                Err(e) if is_client_gone(&e) => {
+                    debug!(error = %e, "Client disconnected");
+                    return Ok(());
+                }
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                    // Oversized or non-UTF-8 line; it has been skipped
+                    let response =
+                        Response::error("null", error_codes::INVALID_REQUEST, e.to_string(), 0.0);
+                    if !write_response(&mut writer, &response.into(), framing)? {
+                        return Ok(());
+                    }
+                    continue;
+                }
+                Err(e) => return Err(e.into()),
+            };
 
             let start = Instant::now();
 
@@ -196,143 +1624,743 @@ impl<S: FgpService + 'static> FgpServer<S> {
             let request = match protocol::Request::from_ndjson_line(&line) {
                 Ok(req) => req,
                 Err(e) => {
+                    // Echo the id if the JSON is intact enough to find it
+                    let id = protocol::Request::recover_id(&line);
                     let response = Response::error(
-                        "null",
+                        id.as_deref().unwrap_or("null"),
                         error_codes::INVALID_REQUEST,
                         format!("Failed to parse request: {}", e),
                         start.elapsed().as_secs_f64() * 1000.0,
                     );
-                    let response_line = response.to_ndjson_line()?;
-                    writer.write_all(response_line.as_bytes())?;
-                    writer.flush()?;
+                    if !write_response(&mut writer, &response.into(), framing)? {
+                        return Ok(());
+                    }
                     continue;
                 }
             };
 
-            if request.v != crate::PROTOCOL_VERSION {
-                let response = Response::error(
+            // Groups this request's events, including those from its dispatch worker
+            let _span =
+                tracing::info_span!("fgp.request", method = %request.method, id = %request.id)
+                    .entered();
+
+            let audited = server.audit_sink.as_ref().map(|_| AuditedRequest {
+                method: request.method.clone(),
+                peer_uid,
+                params: server
+                    .options
+                    .audit_params
+                    .as_ref()
+                    .map(|redact| redact_params(&request.params, redact)),
+            });
+
+            // Requests that arrive once draining has begun, including further requests
+            // on connections opened before it, are refused
+            let Some(_in_flight) = server.shutdown.begin_request() else {
+                let response = Response::error_with_details(
                     &request.id,
-                    error_codes::INVALID_REQUEST,
-                    format!(
-                        "Unsupported protocol version: {} (expected {})",
-                        request.v,
-                        crate::PROTOCOL_VERSION
-                    ),
+                    error_codes::SERVICE_UNAVAILABLE,
+                    "Daemon is shutting down",
+                    serde_json::json!({
+                        "retriable": true,
+                        "retry_after_ms": DRAINING_RETRY_AFTER.as_millis() as u64,
+                    }),
                     start.elapsed().as_secs_f64() * 1000.0,
-                );
-                let response_line = response.to_ndjson_line()?;
-                writer.write_all(response_line.as_bytes())?;
-                writer.flush()?;
-                continue;
+                )
+                .with_trace_id(request.trace_id.clone());
+                let reply = Reply::from(response);
+                write_response(&mut writer, &reply, framing)?;
+                server.audit(audited.as_ref(), &reply.response);
+                return Ok(());
+            };
+
+            let method = request.method.clone();
+            let mut reply =
+                Self::handle_request_static(request, start, server, connection_state.as_ref());
+            if let Some(stream) = reply.stream.take() {
+                if !Self::write_stream(&mut writer, &mut reply.response, stream, server, start)? {
+                    server.audit(audited.as_ref(), &reply.response);
+                    return Ok(());
+                }
             }
 
-            let method = request.method.as_str();
-            let service_prefix = format!("{}.", service.name());
-            let is_namespaced_for_service = method.starts_with(&service_prefix);
-            let action = if is_namespaced_for_service {
-                &method[service_prefix.len()..]
-            } else {
-                method
-            };
+            // Tell the client before closing a connection that has hit its request limit
+            served += 1;
+            let limit_reached = server
+                .options
+                .max_requests_per_connection
+                .is_some_and(|max| served >= max);
+            reply.response.meta.connection_close = limit_reached;
+            reply.response.meta.req_bytes = Some(line.len() as u64);
+            record_response_size(&mut reply);
+
+            // Send NDJSON response. If the client is gone, stop processing this
+            // connection rather than dispatching any further pipelined requests.
+            let delivered = write_response(&mut writer, &reply, framing)?;
+            server.audit(audited.as_ref(), &reply.response);
+            if !delivered {
+                return Ok(());
+            }
 
             debug!(
-                method = %request.method,
-                id = %request.id,
-                "Handling request"
+                method = %method,
+                id = %reply.response.id,
+                trace_id = reply.response.meta.trace_id.as_deref(),
+                server_ms = reply.response.meta.server_ms,
+                "Request complete"
+            );
+
+            if limit_reached {
+                debug!(served, "Closing connection after request limit");
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handle one parsed request: built-ins, namespacing and service dispatch.
+    ///
+    /// Shared by the socket loop and [`FgpTestHarness`](crate::testing::FgpTestHarness).
+    /// `connection_state` is the state of the connection the request arrived on.
+    pub(crate) fn handle_request_static(
+        request: protocol::Request,
+        start: Instant,
+        server: &ServerContext<S>,
+        connection_state: Option<&ConnectionState>,
+    ) -> Reply {
+        let supported = server.service.supported_versions();
+        let Some(version) = negotiate_version(&supported, request.v) else {
+            let supported: Vec<String> = supported.iter().map(u8::to_string).collect();
+            return Response::error(
+                &request.id,
+                error_codes::INVALID_REQUEST,
+                format!(
+                    "Unsupported protocol version: {} (supported: {})",
+                    request.v,
+                    supported.join(", ")
+                ),
+                start.elapsed().as_secs_f64() * 1000.0,
+            )
+            .with_trace_id(request.trace_id.clone())
+            .into();
+        };
+
+        let unknown_fields = server.unknown_fields_warning(&request);
+
+        // Remaining time budget from the client's timeout/deadline (if any)
+        let budget = request.time_budget();
+        if budget == Some(Duration::ZERO) {
+            let mut response = Response::error_with_details(
+                &request.id,
+                error_codes::TIMEOUT,
+                "Request deadline already passed on arrival",
+                arrival_timeout_details(&request),
+                start.elapsed().as_secs_f64() * 1000.0,
             );
+            response.meta.protocol_v = version;
+            return response.with_trace_id(request.trace_id.clone()).into();
+        }
+
+        let method = request.method.as_str();
+        let namespaces = server.service.namespaces();
+        let namespaced_action = strip_namespace(server.service.name(), &namespaces, method);
+        let is_namespaced_for_service = namespaced_action.is_some();
+        let action = namespaced_action.unwrap_or(method);
+
+        debug!(
+            method = %request.method,
+            id = %request.id,
+            trace_id = request.trace_id.as_deref(),
+            "Handling request"
+        );
 
-            // Dispatch to service or handle built-in methods. Built-ins may be called as either:
-            // - "health" / "methods" / "stop" (preferred)
-            // - "<service>.health" / "<service>.methods" / "<service>.stop" (accepted for compatibility)
-            let response = match action {
-                "health" if method == "health" || is_namespaced_for_service => {
-                    Self::handle_health_static(
+        // Built-ins the service has taken over go straight to dispatch
+        let builtin = if server.service.builtin_override(action) {
+            ""
+        } else {
+            action
+        };
+
+        // Dispatch to service or handle built-in methods. Built-ins may be called as either:
+        // - "health" / "ping" / "methods" / "stop" / "schema" / "bundle" (preferred)
+        // - "<service>.health" / "<service>.methods" / "<service>.stop" (accepted for compatibility)
+        let mut raw_result = None;
+        let mut stream = None;
+        let response = match builtin {
+            "health" if method == "health" || is_namespaced_for_service => {
+                let deep = match request.params.get("deep") {
+                    None => Some(false),
+                    Some(deep) => deep.as_bool(),
+                };
+                match deep {
+                    Some(deep) => Self::handle_health_static(
                         &request.id,
                         start,
-                        service,
-                        started_at,
-                        started_at_iso,
-                    )
-                }
-                "stop" if method == "stop" || is_namespaced_for_service => {
-                    running.store(false, Ordering::SeqCst);
-                    Response::success(
+                        &server.service,
+                        &server.started_at,
+                        server.metrics(),
+                        deep,
+                    ),
+                    None => Response::error(
                         &request.id,
-                        serde_json::json!({"message": "Shutting down"}),
+                        error_codes::INVALID_PARAMS,
+                        "Parameter 'deep' must be a boolean",
                         start.elapsed().as_secs_f64() * 1000.0,
-                    )
-                }
-                "methods" if method == "methods" || is_namespaced_for_service => {
-                    Self::handle_methods_static(&request.id, start, service)
-                }
-                "schema" if method == "schema" || is_namespaced_for_service => {
-                    Self::handle_schema_static(&request.id, start, service, request.params)
+                    ),
                 }
-                _ => {
-                    if method.contains('.') && !is_namespaced_for_service {
-                        Response::error(
-                            &request.id,
-                            error_codes::INVALID_REQUEST,
-                            format!(
-                                "Method namespace must match service '{}': got '{}'",
-                                service.name(),
-                                method
-                            ),
-                            start.elapsed().as_secs_f64() * 1000.0,
-                        )
-                    } else {
-                        // Normalize to fully-qualified method names for the service dispatch.
-                        let dispatch_method = if is_namespaced_for_service {
-                            request.method.clone()
-                        } else if method.contains('.') {
-                            // Already handled mismatch above, so this is unreachable.
-                            request.method.clone()
-                        } else {
-                            format!("{}{}", service_prefix, method)
-                        };
+            }
+            "ping" if method == "ping" || is_namespaced_for_service => Response::success(
+                &request.id,
+                serde_json::json!({
+                    "pong": true,
+                    "timestamp": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+                }),
+                start.elapsed().as_secs_f64() * 1000.0,
+            ),
+            "stop" if method == "stop" || is_namespaced_for_service => {
+                server.shutdown.initiate(StopReason::MethodRequest);
+                Response::success(
+                    &request.id,
+                    serde_json::json!({"message": "Shutting down"}),
+                    start.elapsed().as_secs_f64() * 1000.0,
+                )
+            }
+            "methods" if method == "methods" || is_namespaced_for_service => {
+                Self::handle_methods_static(
+                    &request.id,
+                    start,
+                    &server.service,
+                    &server.options,
+                    &request.params,
+                )
+            }
+            "schema" if method == "schema" || is_namespaced_for_service => {
+                Self::handle_schema_static(&request.id, start, &server.service, request.params)
+            }
+            "logs"
+                if (method == "logs" || is_namespaced_for_service)
+                    && server.options.logs_enabled =>
+            {
+                Self::handle_logs_static(&request.id, start, &server.service, request.params)
+            }
+            "reload"
+                if (method == "reload" || is_namespaced_for_service)
+                    && server.options.reload_enabled =>
+            {
+                Self::handle_reload_static(&request.id, start, &server.service)
+            }
+            "bundle" if method == "bundle" || is_namespaced_for_service => {
+                let ctx = RequestContext {
+                    id: request.id.clone(),
+                    trace_id: request.trace_id.clone(),
+                    connection_state: connection_state.cloned(),
+                };
+                Self::handle_bundle_static(&request.id, start, server, request.params, &ctx, budget)
+            }
+            _ => match qualify_method(server.service.name(), &namespaces, method) {
+                None => Response::error(
+                    &request.id,
+                    error_codes::INVALID_REQUEST,
+                    format!(
+                        "Method namespace must match service '{}': got '{}'",
+                        server.service.name(),
+                        method
+                    ),
+                    start.elapsed().as_secs_f64() * 1000.0,
+                ),
+                Some(dispatch_method) => {
+                    debug!(
+                        request_method = %request.method,
+                        dispatch_method = %dispatch_method,
+                        id = %request.id,
+                        trace_id = request.trace_id.as_deref(),
+                        "Dispatching request"
+                    );
+
+                    let ctx = RequestContext {
+                        id: request.id.clone(),
+                        trace_id: request.trace_id.clone(),
+                        connection_state: connection_state.cloned(),
+                    };
+                    let warning = server.deprecation_warning(&dispatch_method);
+                    let mut params = request.params;
+                    server.coerce_params(&dispatch_method, &mut params);
 
-                        debug!(
-                            request_method = %request.method,
-                            dispatch_method = %dispatch_method,
-                            id = %request.id,
-                            "Dispatching request"
+                    if server.service.streams(&dispatch_method) {
+                        let (mut response, chunks) = Self::start_stream(
+                            &request.id,
+                            start,
+                            server,
+                            dispatch_method,
+                            params,
+                            &ctx,
+                            budget,
                         );
+                        stream = chunks;
+                        response.meta.warnings.extend(warning);
+                        response
+                    } else {
+                        let (result, is_cached) =
+                            Self::dispatch_cached(server, dispatch_method, params, ctx, budget);
 
-                        match service.dispatch(&dispatch_method, request.params) {
-                            Ok(result) => Response::success(
+                        let mut response = match result {
+                            Some(Ok(RawJson(RawJsonInner::Value(result)))) => Response::success(
                                 &request.id,
                                 result,
                                 start.elapsed().as_secs_f64() * 1000.0,
                             ),
-                            Err(e) => Response::error(
+                            Some(Ok(RawJson(RawJsonInner::Raw(raw)))) => {
+                                // Written in place of `result` by `Reply::to_json`
+                                raw_result = Some(raw);
+                                let mut response = Response::success(
+                                    &request.id,
+                                    serde_json::Value::Null,
+                                    start.elapsed().as_secs_f64() * 1000.0,
+                                );
+                                response.result = None;
+                                response
+                            }
+                            Some(Err(e)) => {
+                                let mut response = dispatch_error_response(&request.id, &e, start);
+                                if let Some(error) = &mut response.error {
+                                    warn_if_undeclared(
+                                        &*server.service,
+                                        &request.method,
+                                        &error.code,
+                                    );
+                                    let unknown = error.code == error_codes::UNKNOWN_METHOD
+                                        || (error.code == error_codes::INTERNAL_ERROR
+                                            && error.message.starts_with("Unknown method"));
+                                    if let Some(name) = unknown
+                                        .then(|| server.did_you_mean(&request.method))
+                                        .flatten()
+                                    {
+                                        let details = error
+                                            .details
+                                            .get_or_insert_with(|| serde_json::json!({}));
+                                        if let serde_json::Value::Object(details) = details {
+                                            details.insert("did_you_mean".into(), name.into());
+                                        }
+                                    }
+                                }
+                                response
+                            }
+                            None => Response::error_with_details(
                                 &request.id,
-                                error_codes::INTERNAL_ERROR,
-                                e.to_string(),
+                                error_codes::TIMEOUT,
+                                format!(
+                                    "Method '{}' exceeded its deadline of {} ms",
+                                    request.method,
+                                    budget.unwrap_or_default().as_millis()
+                                ),
+                                timeout_details(budget.unwrap_or_default()),
                                 start.elapsed().as_secs_f64() * 1000.0,
                             ),
-                        }
+                        };
+                        response.meta.warnings.extend(warning);
+                        response.meta.cached = is_cached;
+                        response
+                    }
+                }
+            },
+        };
+        let mut response = response.with_trace_id(request.trace_id.clone());
+        response.meta.protocol_v = version;
+        response.meta.warnings.extend(unknown_fields);
+        let mut reply = Reply {
+            response,
+            raw_result,
+            stream,
+        };
+        if server.options.sorted_output {
+            reply.sort_result();
+        }
+        reply
+    }
+
+    /// Start a streamed result with [`FgpService::dispatch_stream`].
+    ///
+    /// Returns the frame that will end the stream (an empty array, or the error if
+    /// the stream couldn't be started) and the chunks to send before it.
+    fn start_stream(
+        id: &str,
+        start: Instant,
+        server: &ServerContext<S>,
+        method: String,
+        params: HashMap<String, serde_json::Value>,
+        ctx: &RequestContext,
+        budget: Option<Duration>,
+    ) -> (Response, Option<ResultStream>) {
+        let chunks = catching_panics(&method, || {
+            server.service.dispatch_stream(&method, params, ctx)
+        });
+        match chunks {
+            Ok(chunks) => (
+                Response::success(
+                    id,
+                    serde_json::json!([]),
+                    start.elapsed().as_secs_f64() * 1000.0,
+                ),
+                Some(ResultStream {
+                    chunks,
+                    method,
+                    budget: budget.map(|budget| (budget, start + budget)),
+                }),
+            ),
+            Err(e) => {
+                let response = dispatch_error_response(id, &e, start);
+                if let Some(error) = &response.error {
+                    warn_if_undeclared(&*server.service, &method, &error.code);
+                }
+                (response, None)
+            }
+        }
+    }
+
+    /// Write the chunks of a streamed result as `partial` frames.
+    ///
+    /// Afterwards `response` is the frame that ends the stream: an error if the stream
+    /// failed, with `server_ms` covering the whole stream. Returns `Ok(false)` if the
+    /// client disconnected.
+    fn write_stream(
+        writer: &mut impl Write,
+        response: &mut Response,
+        mut stream: ResultStream,
+        server: &ServerContext<S>,
+        start: Instant,
+    ) -> Result<bool> {
+        loop {
+            let items = match stream.next_chunk() {
+                Ok(Some(items)) => items,
+                Ok(None) => {
+                    let last = Response::success(
+                        "",
+                        serde_json::json!([]),
+                        start.elapsed().as_secs_f64() * 1000.0,
+                    );
+                    end_stream(response, last);
+                    return Ok(true);
+                }
+                Err(e) => {
+                    end_stream(response, dispatch_error_response("", &e, start));
+                    return Ok(true);
+                }
+            };
+
+            let mut frame = Response::success(
+                &response.id,
+                items.into(),
+                start.elapsed().as_secs_f64() * 1000.0,
+            );
+            frame.meta.protocol_v = response.meta.protocol_v;
+            frame.meta.trace_id = response.meta.trace_id.clone();
+            frame.meta.partial = true;
+            let mut frame = Reply::from(frame);
+            if server.options.sorted_output {
+                frame.sort_result();
+            }
+            record_response_size(&mut frame);
+            if !write_response(writer, &frame, server.options.framing)? {
+                return Ok(false);
+            }
+        }
+    }
+
+    /// [`dispatch_with_budget`](Self::dispatch_with_budget), serving cached methods
+    /// from the response cache.
+    ///
+    /// Also returns whether the result came from the cache.
+    fn dispatch_cached(
+        server: &ServerContext<S>,
+        method: String,
+        params: HashMap<String, serde_json::Value>,
+        ctx: RequestContext,
+        budget: Option<Duration>,
+    ) -> (Option<Result<RawJson>>, bool) {
+        let cache = server.cache.as_ref();
+        let cache_key = cache.and_then(|cache| cache.key(&method, &params));
+        if let Some(result) = cache
+            .zip(cache_key.as_ref())
+            .and_then(|(cache, key)| cache.get(key))
+        {
+            return (Some(Ok(result)), true);
+        }
+
+        let result = Self::dispatch_with_budget(server, method, params, ctx, budget);
+        if let (Some(cache), Some(key), Some(Ok(result))) = (cache, cache_key, &result) {
+            cache.insert(key, result.clone());
+        }
+        (result, false)
+    }
+
+    /// Dispatch to the service, honoring the request's time budget.
+    ///
+    /// Without a budget the call runs inline on the connection thread. With a budget
+    /// the call runs on a worker thread and `None` is returned if it doesn't finish in
+    /// time. The worker is not cancelled; its late result is discarded, and shutdown
+    /// waits for it. Too many such workers and the call is refused (see
+    /// [`MAX_DETACHED_WORKERS`]).
+    fn dispatch_with_budget(
+        server: &ServerContext<S>,
+        method: String,
+        params: HashMap<String, serde_json::Value>,
+        ctx: RequestContext,
+        budget: Option<Duration>,
+    ) -> Option<Result<RawJson>> {
+        let Some(budget) = budget else {
+            return Some(dispatch_catching_panics(
+                &*server.service,
+                &method,
+                params,
+                &ctx,
+            ));
+        };
+
+        let Some(worker) = DispatchWorker::start(&server.shutdown) else {
+            let error = protocol::ServiceError::new(
+                error_codes::SERVICE_UNAVAILABLE,
+                "Too many timed-out requests are still running",
+            )
+            .with_details(serde_json::json!({
+                "retriable": true,
+                "retry_after_ms": DETACHED_RETRY_AFTER.as_millis() as u64,
+            }));
+            return Some(Err(error.into()));
+        };
+
+        let service = Arc::clone(&server.service);
+        let (tx, rx) = mpsc::channel();
+        let span = tracing::Span::current();
+        let thread_worker = Arc::clone(&worker);
+        thread::spawn(move || {
+            let _span = span.entered();
+            let _ = tx.send(dispatch_catching_panics(&*service, &method, params, &ctx));
+            thread_worker.finish();
+        });
+
+        match rx.recv_timeout(budget) {
+            Ok(result) => Some(result),
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                worker.detach();
+                None
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => Some(Err(anyhow::anyhow!(
+                "Dispatch worker exited without a result"
+            ))),
+        }
+    }
+
+    /// Handle the `bundle` built-in method (static version).
+    ///
+    /// Runs each call in `params.calls` against the service's dispatch, in order,
+    /// and returns one entry per call. A failing call doesn't fail the bundle.
+    ///
+    /// # Parameters
+    /// * `calls` - Array of `{"method": "...", "params": {...}}` objects
+    fn handle_bundle_static(
+        id: &str,
+        start: Instant,
+        server: &ServerContext<S>,
+        params: HashMap<String, serde_json::Value>,
+        ctx: &RequestContext,
+        budget: Option<Duration>,
+    ) -> Response {
+        let service = &server.service;
+        #[derive(serde::Deserialize)]
+        struct BundleCall {
+            method: String,
+            #[serde(default)]
+            params: HashMap<String, serde_json::Value>,
+        }
+
+        let calls: Vec<BundleCall> = match params
+            .get("calls")
+            .map(|v| serde_json::from_value(v.clone()))
+        {
+            Some(Ok(calls)) => calls,
+            Some(Err(e)) => {
+                return Response::error(
+                    id,
+                    error_codes::INVALID_PARAMS,
+                    format!("Invalid 'calls': {}", e),
+                    start.elapsed().as_secs_f64() * 1000.0,
+                )
+            }
+            None => {
+                return Response::error(
+                    id,
+                    error_codes::INVALID_PARAMS,
+                    "Missing required parameter: calls",
+                    start.elapsed().as_secs_f64() * 1000.0,
+                )
+            }
+        };
+
+        let call_error = |method: &str, code: &str, message: String| {
+            serde_json::json!({
+                "method": method,
+                "ok": false,
+                "error": protocol::ErrorInfo {
+                    code: code.to_string(),
+                    message,
+                    details: None,
+                },
+            })
+        };
+        let call_timeout = |method: &str, message: &str| {
+            serde_json::json!({
+                "method": method,
+                "ok": false,
+                "error": protocol::ErrorInfo {
+                    code: error_codes::TIMEOUT.to_string(),
+                    message: message.to_string(),
+                    details: Some(timeout_details(budget.unwrap_or_default())),
+                },
+            })
+        };
+
+        let namespaces = service.namespaces();
+        let mut results = Vec::with_capacity(calls.len());
+        for call in calls {
+            let action =
+                strip_namespace(service.name(), &namespaces, &call.method).unwrap_or(&call.method);
+            if BUILTIN_METHODS.contains(&action) && !service.builtin_override(action) {
+                results.push(call_error(
+                    &call.method,
+                    error_codes::INVALID_REQUEST,
+                    format!("Built-in method '{}' cannot be bundled", call.method),
+                ));
+                continue;
+            }
+
+            let Some(dispatch_method) = qualify_method(service.name(), &namespaces, &call.method)
+            else {
+                results.push(call_error(
+                    &call.method,
+                    error_codes::INVALID_REQUEST,
+                    format!(
+                        "Method namespace must match service '{}': got '{}'",
+                        service.name(),
+                        call.method
+                    ),
+                ));
+                continue;
+            };
+
+            // Every call shares the bundle's overall time budget
+            let remaining = budget.map(|b| b.saturating_sub(start.elapsed()));
+            if remaining == Some(Duration::ZERO) {
+                results.push(call_timeout(
+                    &call.method,
+                    "Bundle deadline exceeded before call was dispatched",
+                ));
+                continue;
+            }
+
+            let mut params = call.params;
+            server.coerce_params(&dispatch_method, &mut params);
+            let (result, _) = if service.streams(&dispatch_method) {
+                let stream = catching_panics(&dispatch_method, || {
+                    service.dispatch_stream(&dispatch_method, params, ctx)
+                });
+                let collected = stream.and_then(|chunks| {
+                    ResultStream {
+                        chunks,
+                        method: dispatch_method,
+                        budget: budget.map(|budget| (budget, start + budget)),
                     }
+                    .collect()
+                });
+                (Some(collected.map(RawJson::from)), false)
+            } else {
+                Self::dispatch_cached(server, dispatch_method, params, ctx.clone(), remaining)
+            };
+            let entry = match result.map(|result| result.and_then(RawJson::into_value)) {
+                Some(Ok(result)) => serde_json::json!({
+                    "method": call.method,
+                    "ok": true,
+                    "result": result,
+                }),
+                Some(Err(e)) => {
+                    let error = protocol::ErrorInfo::from(&e);
+                    warn_if_undeclared(&**service, &call.method, &error.code);
+                    serde_json::json!({
+                        "method": call.method,
+                        "ok": false,
+                        "error": error,
+                    })
                 }
+                None => call_timeout(&call.method, "Bundle deadline exceeded"),
             };
+            results.push(entry);
+        }
 
-            // Send NDJSON response
-            let response_line = response.to_ndjson_line()?;
-            writer.write_all(response_line.as_bytes())?;
-            writer.flush()?;
-
-            debug!(
-                method = %request.method,
-                id = %request.id,
-                server_ms = response.meta.server_ms,
-                "Request complete"
-            );
+        Response::success(
+            id,
+            serde_json::json!({ "results": results }),
+            start.elapsed().as_secs_f64() * 1000.0,
+        )
+    }
 
-            if !running.load(Ordering::SeqCst) {
-                break;
+    /// Handle the `reload` built-in method (static version).
+    ///
+    /// Runs [`FgpService::reload`]; its error, if any, becomes the response error.
+    fn handle_reload_static(id: &str, start: Instant, service: &Arc<S>) -> Response {
+        info!(service = service.name(), "Reloading service");
+        match service.reload() {
+            Ok(()) => Response::success(
+                id,
+                serde_json::json!({"message": "Reloaded"}),
+                start.elapsed().as_secs_f64() * 1000.0,
+            ),
+            Err(e) => {
+                error!(service = service.name(), error = %e, "Reload failed");
+                dispatch_error_response(id, &e, start)
             }
         }
+    }
 
-        Ok(())
+    /// Handle the `logs` built-in method (static version).
+    ///
+    /// Returns the tail of the service's log file. Only reads a bounded window from
+    /// the end of the file, so huge logs don't blow up memory.
+    ///
+    /// # Parameters
+    /// * `lines` - Number of trailing lines to return (default 100, max 10000)
+    /// * `offset` - Byte offset from a previous call; returns the first `lines` lines
+    ///   written since (poll with the returned `offset` to follow the log)
+    fn handle_logs_static(
+        id: &str,
+        start: Instant,
+        service: &Arc<S>,
+        params: HashMap<String, serde_json::Value>,
+    ) -> Response {
+        let path = crate::logging::log_file_path(service.name());
+        let max_lines = params
+            .get("lines")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(100)
+            .clamp(1, 10_000) as usize;
+        let offset = params.get("offset").and_then(|v| v.as_u64());
+
+        match crate::logging::tail_file(&path, max_lines, offset) {
+            Ok(tail) => Response::success(
+                id,
+                serde_json::json!({
+                    "path": path.display().to_string(),
+                    "lines": tail.lines,
+                    "offset": tail.offset,
+                }),
+                start.elapsed().as_secs_f64() * 1000.0,
+            ),
+            Err(e) => Response::error(
+                id,
+                error_codes::NOT_FOUND,
+                format!("Cannot read log file {}: {}", path.display(), e),
+                start.elapsed().as_secs_f64() * 1000.0,
+            ),
+        }
     }
 
     /// Handle the `health` built-in method (instance version).
@@ -342,65 +2370,102 @@ impl<S: FgpService + 'static> FgpServer<S> {
             id,
             start,
             &self.service,
-            &self.started_at,
-            &self.started_at_iso,
+            &self.context().started_at,
+            self.context().metrics(),
+            true,
         )
     }
 
     /// Handle the `health` built-in method (static version).
+    ///
+    /// Only a `deep` check runs [`FgpService::health_check`]; otherwise the report is
+    /// basic liveness with no dependency results.
     fn handle_health_static(
         id: &str,
         start: Instant,
         service: &Arc<S>,
-        started_at: &Arc<Instant>,
-        started_at_iso: &Arc<String>,
+        started_at: &StartedAt,
+        metrics: ServerMetrics,
+        deep: bool,
     ) -> Response {
-        let uptime = started_at.elapsed().as_secs();
-        let services = service.health_check();
-
-        // Determine overall status
-        let status = if services.values().all(|s| s.ok) {
-            "healthy"
-        } else if services.values().any(|s| s.ok) {
-            "degraded"
-        } else if services.is_empty() {
-            "healthy"
+        let services = if deep {
+            service.health_check()
         } else {
-            "unhealthy"
+            HashMap::new()
+        };
+        let report = HealthReport {
+            status: HealthState::from_services(&services),
+            pid: std::process::id(),
+            started_at: started_at.iso.clone(),
+            version: service.version().to_string(),
+            sdk_version: crate::SDK_VERSION.to_string(),
+            protocol_version: crate::PROTOCOL_VERSION,
+            git_sha: crate::GIT_SHA.map(str::to_string),
+            uptime_seconds: started_at.instant.elapsed().as_secs(),
+            restart_count: started_at.history.as_ref().map(|h| h.restart_count),
+            first_started_at: started_at
+                .history
+                .as_ref()
+                .map(|h| h.first_started_at.clone()),
+            metrics,
+            services,
         };
+        let result = serde_json::to_value(report).unwrap_or_default();
 
-        Response::success(
-            id,
-            serde_json::json!({
-                "status": status,
-                "pid": std::process::id(),
-                "started_at": started_at_iso.as_str(),
-                "version": service.version(),
-                "uptime_seconds": uptime,
-                "services": services,
-            }),
-            start.elapsed().as_secs_f64() * 1000.0,
-        )
+        Response::success(id, result, start.elapsed().as_secs_f64() * 1000.0)
     }
 
     /// Handle the `methods` built-in method (instance version).
     #[allow(dead_code)]
     fn handle_methods(&self, id: &str, start: Instant) -> Response {
-        Self::handle_methods_static(id, start, &self.service)
+        Self::handle_methods_static(id, start, &self.service, &self.options, &HashMap::new())
     }
 
     /// Handle the `methods` built-in method (static version).
-    fn handle_methods_static(id: &str, start: Instant, service: &Arc<S>) -> Response {
+    ///
+    /// With `limit` and/or `offset` params, returns one page of methods and their
+    /// `total` count; see [`paginate`].
+    fn handle_methods_static(
+        id: &str,
+        start: Instant,
+        service: &Arc<S>,
+        options: &ServerOptions,
+        params: &HashMap<String, serde_json::Value>,
+    ) -> Response {
         let mut methods: Vec<MethodInfo> = vec![
             MethodInfo {
                 name: "health".into(),
                 description: "Returns daemon health and status".into(),
+                params: vec![ParamInfo {
+                    name: "deep".into(),
+                    param_type: "boolean".into(),
+                    description: Some(
+                        "Also run the service's dependency checks (readiness, not just liveness)"
+                            .into(),
+                    ),
+                    required: false,
+                    default: Some(serde_json::json!(false)),
+                    ..Default::default()
+                }],
+                schema: None,
+                returns: None,
+                examples: vec![],
+                errors: vec![],
+                deprecated: false,
+                replaced_by: None,
+                strict_params: false,
+            },
+            MethodInfo {
+                name: "ping".into(),
+                description: "Returns immediately; the cheapest liveness/keep-alive probe".into(),
                 params: vec![],
                 schema: None,
                 returns: None,
                 examples: vec![],
                 errors: vec![],
                 deprecated: false,
+                replaced_by: None,
+                strict_params: false,
             },
             MethodInfo {
                 name: "stop".into(),
@@ -411,55 +2476,153 @@ impl<S: FgpService + 'static> FgpServer<S> {
                 examples: vec![],
                 errors: vec![],
                 deprecated: false,
+                replaced_by: None,
+                strict_params: false,
             },
             MethodInfo {
                 name: "methods".into(),
                 description: "Lists available methods".into(),
-                params: vec![],
+                params: page_param_info(),
+                schema: None,
+                returns: None,
+                examples: vec![],
+                errors: vec![],
+                deprecated: false,
+                replaced_by: None,
+                strict_params: false,
+            },
+            MethodInfo {
+                name: "bundle".into(),
+                description: "Runs multiple service methods in one request".into(),
+                params: vec![ParamInfo {
+                    name: "calls".into(),
+                    param_type: "array".into(),
+                    description: Some(
+                        "Calls to run in order, each {\"method\": ..., \"params\": {...}}".into(),
+                    ),
+                    required: true,
+                    items_type: Some("object".into()),
+                    ..Default::default()
+                }],
                 schema: None,
                 returns: None,
                 examples: vec![],
                 errors: vec![],
                 deprecated: false,
+                replaced_by: None,
+                strict_params: false,
             },
             MethodInfo {
                 name: "schema".into(),
-                description: "Returns JSON Schema for methods with format conversion support".into(),
+                description: "Returns JSON Schema for methods with format conversion support"
+                    .into(),
                 params: vec![
                     ParamInfo {
                         name: "format".into(),
                         param_type: "string".into(),
                         required: false,
                         default: Some(serde_json::json!("json-schema")),
+                        ..Default::default()
                     },
                     ParamInfo {
                         name: "methods".into(),
                         param_type: "array".into(),
                         required: false,
                         default: None,
+                        items_type: Some("string".into()),
+                        ..Default::default()
                     },
-                ],
+                    ParamInfo {
+                        name: "examples".into(),
+                        param_type: "boolean".into(),
+                        required: false,
+                        default: Some(serde_json::json!(false)),
+                        ..Default::default()
+                    },
+                ]
+                .into_iter()
+                .chain(page_param_info())
+                .collect(),
                 schema: None,
                 returns: None,
                 examples: vec![],
                 errors: vec![],
                 deprecated: false,
+                replaced_by: None,
+                strict_params: false,
             },
         ];
 
-        let service_prefix = format!("{}.", service.name());
-        for mut method_info in service.method_list() {
-            if !method_info.name.contains('.') {
-                method_info.name = format!("{}{}", service_prefix, method_info.name);
-            }
-            methods.push(method_info);
+        if options.reload_enabled {
+            methods.push(MethodInfo {
+                name: "reload".into(),
+                description: "Reloads the service's configuration without restarting".into(),
+                params: vec![],
+                schema: None,
+                returns: None,
+                examples: vec![],
+                errors: vec![],
+                deprecated: false,
+                replaced_by: None,
+                strict_params: false,
+            });
         }
 
-        Response::success(
-            id,
-            serde_json::json!({"methods": methods}),
-            start.elapsed().as_secs_f64() * 1000.0,
-        )
+        if options.logs_enabled {
+            methods.push(MethodInfo {
+                name: "logs".into(),
+                description: "Returns the tail of the daemon log file".into(),
+                params: vec![
+                    ParamInfo {
+                        name: "lines".into(),
+                        param_type: "integer".into(),
+                        description: Some("Number of trailing lines (max 10000)".into()),
+                        required: false,
+                        default: Some(serde_json::json!(100)),
+                        ..Default::default()
+                    },
+                    ParamInfo {
+                        name: "offset".into(),
+                        param_type: "integer".into(),
+                        description: Some(
+                            "Offset from a previous call; returns only newer lines".into(),
+                        ),
+                        required: false,
+                        ..Default::default()
+                    },
+                ],
+                schema: None,
+                returns: None,
+                examples: vec![],
+                errors: vec![],
+                deprecated: false,
+                replaced_by: None,
+                strict_params: false,
+            });
+        }
+
+        methods.extend(qualified_methods(&**service));
+
+        let mut capabilities = service.capabilities();
+        capabilities.batching = true;
+        capabilities.max_request_bytes = Some(
+            capabilities
+                .max_request_bytes
+                .map_or(protocol::MAX_LINE_BYTES, |max| {
+                    max.min(protocol::MAX_LINE_BYTES)
+                }),
+        );
+
+        let total = paginate(&mut methods, params);
+        let mut result = serde_json::json!({
+            "methods": methods,
+            "capabilities": capabilities,
+            "error_codes": error_vocabulary(&**service),
+        });
+        if let Some(total) = total {
+            result["total"] = total.into();
+        }
+        Response::success(id, result, start.elapsed().as_secs_f64() * 1000.0)
     }
 
     /// Handle the `schema` built-in method (static version).
@@ -467,34 +2630,35 @@ impl<S: FgpService + 'static> FgpServer<S> {
     /// Returns JSON Schema for methods with optional format conversion.
     ///
     /// # Parameters
-    /// * `format` - Output format: "json-schema" (default), "openai", "anthropic", "mcp"
+    /// * `format` - Output format: "json-schema" (default), "json-schema-doc", "openai",
+    ///   "anthropic", "mcp", "typescript" (declarations returned as a string)
     /// * `methods` - Optional array of method names to filter
+    /// * `examples` - Append each method's first example to its description in the
+    ///   "openai" and "anthropic" formats (default false)
+    /// * `limit` / `offset` - Optional page of the (filtered) methods; adds a `total`
+    ///   count to object results (see [`paginate`])
     fn handle_schema_static(
         id: &str,
         start: Instant,
         service: &Arc<S>,
-        params: std::collections::HashMap<String, serde_json::Value>,
+        params: HashMap<String, serde_json::Value>,
     ) -> Response {
         let format = params
             .get("format")
             .and_then(|v| v.as_str())
             .unwrap_or("json-schema");
+        let examples = params
+            .get("examples")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
 
         let method_filter: Option<Vec<String>> = params
             .get("methods")
             .and_then(|v| serde_json::from_value(v.clone()).ok());
 
         // Get service methods (excluding built-ins for schema output)
-        let service_prefix = format!("{}.", service.name());
-        let methods: Vec<MethodInfo> = service
-            .method_list()
+        let mut methods: Vec<MethodInfo> = qualified_methods(&**service)
             .into_iter()
-            .map(|mut m| {
-                if !m.name.contains('.') {
-                    m.name = format!("{}{}", service_prefix, m.name);
-                }
-                m
-            })
             .filter(|m| {
                 method_filter
                     .as_ref()
@@ -503,10 +2667,22 @@ impl<S: FgpService + 'static> FgpServer<S> {
             })
             .collect();
 
-        let result = match format {
+        // Requested names that matched nothing, so clients can spot typos
+        let not_found: Vec<&String> = method_filter
+            .iter()
+            .flatten()
+            .filter(|name| !methods.iter().any(|m| &m.name == *name))
+            .collect();
+        let total = paginate(&mut methods, &params);
+
+        let mut result = match format {
+            "openai" if examples => schema::to_openai_with_examples(&methods),
             "openai" => schema::to_openai(&methods),
+            "anthropic" if examples => schema::to_anthropic_with_examples(&methods),
             "anthropic" => schema::to_anthropic(&methods),
             "mcp" => serde_json::to_value(schema::to_mcp(&methods)).unwrap_or_default(),
+            "json-schema-doc" => schema::to_json_schema_document(&methods),
+            "typescript" => serde_json::Value::String(schema::to_typescript(&methods)),
             _ => {
                 // Default: json-schema format with full metadata
                 serde_json::json!({
@@ -514,14 +2690,603 @@ impl<S: FgpService + 'static> FgpServer<S> {
                     "version": service.version(),
                     "protocol": "fgp@1",
                     "methods": methods,
+                    "error_codes": error_vocabulary(&**service),
                 })
             }
         };
 
-        Response::success(id, result, start.elapsed().as_secs_f64() * 1000.0)
+        if let (Some(total), serde_json::Value::Object(result)) = (total, &mut result) {
+            result.insert("total".into(), total.into());
+        }
+        if not_found.is_empty() {
+            return Response::success(id, result, start.elapsed().as_secs_f64() * 1000.0);
+        }
+        // The MCP format is a bare array; the warning still reaches those clients
+        if let serde_json::Value::Object(result) = &mut result {
+            result.insert("not_found".into(), serde_json::json!(not_found));
+        }
+        let mut response = Response::success(id, result, start.elapsed().as_secs_f64() * 1000.0);
+        response.meta.warnings.extend(
+            not_found
+                .iter()
+                .map(|name| format!("Unknown method in 'methods' filter: {}", name)),
+        );
+        response
+    }
+}
+
+/// The service's `method_list` with bare names prefixed by the service name.
+///
+/// A name listed more than once (easy to do in a generated list) would break tool
+/// registration in clients, so only its first entry is kept and the duplicates are
+/// logged as a warning.
+fn qualified_methods<S: FgpService + ?Sized>(service: &S) -> Vec<MethodInfo> {
+    let mut seen = HashSet::new();
+    let mut duplicates = Vec::new();
+    let mut methods = Vec::new();
+    for mut method in service.method_list() {
+        if !method.name.contains('.') {
+            method.name = format!("{}.{}", service.name(), method.name);
+        }
+        if seen.insert(method.name.clone()) {
+            methods.push(method);
+        } else {
+            duplicates.push(method.name);
+        }
+    }
+    if !duplicates.is_empty() {
+        warn!(
+            service = service.name(),
+            "Duplicate methods in method_list, keeping the first of each: {}",
+            duplicates.join(", ")
+        );
+    }
+    methods
+}
+
+/// The highest supported protocol version not above the `requested` one.
+fn negotiate_version(supported: &[u8], requested: u8) -> Option<u8> {
+    supported.iter().copied().filter(|&v| v <= requested).max()
+}
+
+/// Keep one page of a built-in's list, per the request's `limit` and `offset` params.
+///
+/// Returns the list's full length, or `None` (leaving the list untouched) when
+/// neither param is given, so unpaginated callers see the same output as before.
+fn paginate<T>(items: &mut Vec<T>, params: &HashMap<String, serde_json::Value>) -> Option<usize> {
+    let limit = params.get("limit").and_then(|v| v.as_u64());
+    let offset = params.get("offset").and_then(|v| v.as_u64());
+    if limit.is_none() && offset.is_none() {
+        return None;
+    }
+    let total = items.len();
+    items.drain(..(offset.unwrap_or(0) as usize).min(total));
+    if let Some(limit) = limit {
+        items.truncate(limit as usize);
+    }
+    Some(total)
+}
+
+/// `limit` and `offset` param descriptions for the paginated built-ins.
+fn page_param_info() -> Vec<ParamInfo> {
+    vec![
+        ParamInfo {
+            name: "limit".into(),
+            param_type: "integer".into(),
+            description: Some("Maximum number of methods to return".into()),
+            required: false,
+            ..Default::default()
+        },
+        ParamInfo {
+            name: "offset".into(),
+            param_type: "integer".into(),
+            description: Some("Number of methods to skip".into()),
+            required: false,
+            ..Default::default()
+        },
+    ]
+}
+
+/// The abstract name of a socket address, if it has one (Linux only).
+fn abstract_name(addr: &std::os::unix::net::SocketAddr) -> Option<&[u8]> {
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::linux::net::SocketAddrExt;
+        addr.as_abstract_name()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = addr;
+        None
+    }
+}
+
+/// Signals [`FgpServer::serve_with_signals`] turns into a graceful stop.
+const SHUTDOWN_SIGNALS: [libc::c_int; 2] = [libc::SIGTERM, libc::SIGINT];
+
+/// Write end of the pipe the signal handler uses to wake the watcher thread (-1 if none).
+static SIGNAL_PIPE: AtomicI32 = AtomicI32::new(-1);
+
+extern "C" fn on_shutdown_signal(_signal: libc::c_int) {
+    // Only async-signal-safe calls here: the watcher thread does the actual work
+    let fd = SIGNAL_PIPE.load(Ordering::SeqCst);
+    if fd >= 0 {
+        unsafe { libc::write(fd, [1u8].as_ptr().cast(), 1) };
     }
 }
 
+/// A close-on-exec pipe whose write end never blocks (for use from a signal handler).
+fn signal_pipe() -> Result<(OwnedFd, OwnedFd)> {
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } == -1 {
+        return Err(io::Error::last_os_error()).context("Failed to create signal pipe");
+    }
+    // SAFETY: both descriptors were just created and are owned by nothing else
+    let (read_end, write_end) =
+        unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+    for fd in fds {
+        unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) };
+    }
+    unsafe { libc::fcntl(fds[1], libc::F_SETFL, libc::O_NONBLOCK) };
+    Ok((read_end, write_end))
+}
+
+/// Install `handler` for `signal`, restarting interrupted system calls.
+fn set_signal_handler(signal: libc::c_int, handler: libc::sighandler_t) -> Result<()> {
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        if libc::sigaction(signal, &action, std::ptr::null_mut()) == -1 {
+            return Err(io::Error::last_os_error())
+                .with_context(|| format!("Failed to set handler for signal {}", signal));
+        }
+    }
+    Ok(())
+}
+
+/// First descriptor passed by systemd socket activation (`SD_LISTEN_FDS_START`).
+const SD_LISTEN_FDS_START: std::os::fd::RawFd = 3;
+
+/// The listener passed in by systemd socket activation, if any.
+///
+/// Follows `sd_listen_fds(3)`: descriptors are only taken when `LISTEN_PID` matches
+/// this process. The activation variables are unset afterwards.
+fn systemd_listener() -> Result<Option<UnixListener>> {
+    let listen_pid = std::env::var("LISTEN_PID").ok();
+    let listen_fds = std::env::var("LISTEN_FDS").ok();
+
+    if listen_pid.and_then(|pid| pid.parse::<u32>().ok()) != Some(std::process::id()) {
+        return Ok(None);
+    }
+    for var in ["LISTEN_PID", "LISTEN_FDS", "LISTEN_FDNAMES"] {
+        std::env::remove_var(var);
+    }
+
+    let count: usize = listen_fds
+        .as_deref()
+        .unwrap_or("0")
+        .parse()
+        .context("Invalid LISTEN_FDS")?;
+    if count == 0 {
+        return Ok(None);
+    }
+    if count > 1 {
+        warn!(
+            count,
+            "Multiple socket-activated descriptors passed; using the first"
+        );
+    }
+
+    let fd = SD_LISTEN_FDS_START;
+    // Passed descriptors don't have close-on-exec set
+    if unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+        return Err(io::Error::last_os_error()).context("Socket-activated descriptor is not open");
+    }
+
+    // SAFETY: systemd passed this descriptor to us and nothing else owns it
+    Ok(Some(unsafe { UnixListener::from_raw_fd(fd) }))
+}
+
+/// Names of the methods handled by the server itself.
+const BUILTIN_METHODS: &[&str] = &[
+    "health", "ping", "stop", "methods", "schema", "bundle", "logs", "reload",
+];
+
+/// The method name without its namespace, if it is namespaced for this service.
+///
+/// `namespaces` are the service's accepted prefixes (see [`FgpService::namespaces`]);
+/// the service name itself is always accepted.
+fn strip_namespace<'a>(
+    service_name: &str,
+    namespaces: &[&str],
+    method: &'a str,
+) -> Option<&'a str> {
+    std::iter::once(service_name)
+        .chain(namespaces.iter().copied())
+        .find_map(|namespace| method.strip_prefix(namespace)?.strip_prefix('.'))
+}
+
+/// Normalize a request method to the fully-qualified name passed to `dispatch`.
+///
+/// Bare names (`"echo"`) are prefixed with the service name, and names under an alias
+/// namespace are rewritten to it. Returns `None` when the method is namespaced for a
+/// different service.
+fn qualify_method(service_name: &str, namespaces: &[&str], method: &str) -> Option<String> {
+    if let Some(action) = strip_namespace(service_name, namespaces, method) {
+        Some(format!("{}.{}", service_name, action))
+    } else if method.contains('.') {
+        None
+    } else {
+        Some(format!("{}.{}", service_name, method))
+    }
+}
+
+/// `params` as JSON text with object keys sorted at every level, so equal params
+/// always give the same text.
+fn canonical_params(params: &HashMap<String, serde_json::Value>) -> String {
+    let mut entries: Vec<_> = params.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    let object = entries
+        .into_iter()
+        .map(|(key, value)| (key.clone(), canonicalize(value)))
+        .collect();
+    serde_json::Value::Object(object).to_string()
+}
+
+/// A copy of `value` with every object's keys in sorted order.
+fn canonicalize(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            serde_json::Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.clone(), canonicalize(value)))
+                    .collect(),
+            )
+        }
+        serde_json::Value::Array(items) => {
+            serde_json::Value::Array(items.iter().map(canonicalize).collect())
+        }
+        other => other.clone(),
+    }
+}
+
+/// `ErrorInfo.details` for `TIMEOUT` errors: the limit hit and a retry hint.
+fn timeout_details(limit: Duration) -> serde_json::Value {
+    serde_json::json!({
+        "timeout_ms": limit.as_millis() as u64,
+        "retriable": true,
+    })
+}
+
+/// [`timeout_details`] for a request whose budget was already spent on arrival.
+///
+/// That is a `timeout_ms` of 0 or a `deadline_ms` in the past; the one that applied
+/// is reported.
+fn arrival_timeout_details(request: &protocol::Request) -> serde_json::Value {
+    match (request.timeout_ms, request.deadline_ms) {
+        (Some(0), _) | (_, None) => timeout_details(Duration::ZERO),
+        (_, Some(deadline_ms)) => serde_json::json!({
+            "deadline_ms": deadline_ms,
+            "retriable": true,
+        }),
+    }
+}
+
+/// Dispatch to the service, turning a panic into an `INTERNAL_ERROR`.
+///
+/// The panic message, when it is a string, is returned in the error's details as
+/// `{"panic": ...}`. The connection stays usable for later requests.
+fn dispatch_catching_panics<S: FgpService>(
+    service: &S,
+    method: &str,
+    params: HashMap<String, serde_json::Value>,
+    ctx: &RequestContext,
+) -> Result<RawJson> {
+    catching_panics(method, || service.dispatch_raw(method, params, ctx))
+}
+
+/// Run a service call for `method`, turning a panic into an `INTERNAL_ERROR`.
+fn catching_panics<T>(method: &str, call: impl FnOnce() -> Result<T>) -> Result<T> {
+    panic::catch_unwind(AssertUnwindSafe(call)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned());
+        error!(method, panic = message.as_deref(), "Dispatch panicked");
+
+        let mut error = protocol::ServiceError::new(
+            error_codes::INTERNAL_ERROR,
+            format!("Method '{}' panicked", method),
+        );
+        if let Some(message) = message {
+            error = error.with_details(serde_json::json!({ "panic": message }));
+        }
+        Err(error.into())
+    })
+}
+
+/// The chunks of a streamed result still to be sent; see [`FgpService::dispatch_stream`].
+struct ResultStream {
+    chunks: ResultChunks,
+    method: String,
+    /// The request's time budget and when it runs out
+    budget: Option<(Duration, Instant)>,
+}
+
+impl ResultStream {
+    /// The next chunk, `None` once the stream is done.
+    fn next_chunk(&mut self) -> Result<Option<Vec<serde_json::Value>>> {
+        if let Some((budget, deadline)) = self.budget {
+            if Instant::now() >= deadline {
+                return Err(protocol::ServiceError::new(
+                    error_codes::TIMEOUT,
+                    format!(
+                        "Method '{}' exceeded its deadline while streaming",
+                        self.method
+                    ),
+                )
+                .with_details(timeout_details(budget))
+                .into());
+            }
+        }
+        let chunks = &mut self.chunks;
+        catching_panics(&self.method, || chunks.next().transpose())
+    }
+
+    /// Every remaining item, as one array.
+    fn collect(mut self) -> Result<serde_json::Value> {
+        let mut items = Vec::new();
+        while let Some(chunk) = self.next_chunk()? {
+            items.extend(chunk);
+        }
+        Ok(items.into())
+    }
+}
+
+/// Replace the final frame of a stream, keeping its meta but for `server_ms`.
+fn end_stream(response: &mut Response, last: Response) {
+    let meta = std::mem::take(&mut response.meta);
+    let server_ms = last.meta.server_ms;
+    *response = Response {
+        id: std::mem::take(&mut response.id),
+        ..last
+    };
+    response.meta = protocol::ResponseMeta { server_ms, ..meta };
+}
+
+/// Error response for a failed dispatch; see [`protocol::ServiceError`].
+fn dispatch_error_response(id: &str, error: &anyhow::Error, start: Instant) -> Response {
+    let info = protocol::ErrorInfo::from(error);
+    let server_ms = start.elapsed().as_secs_f64() * 1000.0;
+    match info.details {
+        Some(details) => {
+            Response::error_with_details(id, &info.code, info.message, details, server_ms)
+        }
+        None => Response::error(id, &info.code, info.message, server_ms),
+    }
+}
+
+/// Edit distance between two method names.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Standard error codes followed by the service's declared ones.
+fn error_vocabulary<S: FgpService>(service: &S) -> Vec<&str> {
+    let mut codes = error_codes::STANDARD.to_vec();
+    for code in service.error_codes() {
+        if !codes.contains(&code) {
+            codes.push(code);
+        }
+    }
+    codes
+}
+
+/// Log a warning when a dispatch fails with a code the service didn't declare.
+///
+/// Only checked when [`FgpService::error_codes`] is non-empty.
+fn warn_if_undeclared<S: FgpService>(service: &S, method: &str, code: &str) {
+    let declared = service.error_codes();
+    if declared.is_empty() || error_codes::STANDARD.contains(&code) || declared.contains(&code) {
+        return;
+    }
+    warn!(
+        method = %method,
+        code = %code,
+        "Method returned an error code not declared in FgpService::error_codes"
+    );
+}
+
+/// A response whose `result` may still be raw JSON from [`FgpService::dispatch_raw`].
+pub(crate) struct Reply {
+    pub(crate) response: Response,
+    /// Replaces `response.result` when serialized
+    raw_result: Option<Box<serde_json::value::RawValue>>,
+    /// Chunks of a streamed result, written before `response` ends the stream
+    stream: Option<ResultStream>,
+}
+
+impl Reply {
+    /// The response with any raw result parsed into `result`, and any streamed
+    /// result collected into one array.
+    pub(crate) fn into_response(self) -> Response {
+        let mut response = self.response;
+        if let Some(raw) = self.raw_result {
+            response.result = serde_json::from_str(raw.get()).ok();
+        }
+        if let Some(stream) = self.stream {
+            let start = Instant::now();
+            match stream.collect() {
+                Ok(items) => response.result = Some(items),
+                Err(e) => end_stream(&mut response, dispatch_error_response("", &e, start)),
+            }
+        }
+        response
+    }
+
+    /// Sort the result's object keys, parsing a raw result first.
+    fn sort_result(&mut self) {
+        if let Some(raw) = self.raw_result.take() {
+            match serde_json::from_str(raw.get()) {
+                Ok(result) => self.response.result = Some(result),
+                Err(_) => {
+                    self.raw_result = Some(raw);
+                    return;
+                }
+            }
+        }
+        if let Some(result) = &mut self.response.result {
+            *result = canonicalize(result);
+        }
+    }
+
+    /// Serialize the response, writing a raw result verbatim.
+    fn to_json(&self) -> serde_json::Result<String> {
+        #[derive(serde::Serialize)]
+        struct RawResponse<'a> {
+            id: &'a str,
+            ok: bool,
+            result: &'a serde_json::value::RawValue,
+            meta: &'a protocol::ResponseMeta,
+        }
+
+        match &self.raw_result {
+            Some(raw) => serde_json::to_string(&RawResponse {
+                id: &self.response.id,
+                ok: self.response.ok,
+                result: raw,
+                meta: &self.response.meta,
+            }),
+            None => serde_json::to_string(&self.response),
+        }
+    }
+}
+
+impl From<Response> for Reply {
+    fn from(response: Response) -> Self {
+        Self {
+            response,
+            raw_result: None,
+            stream: None,
+        }
+    }
+}
+
+/// Set `meta.resp_bytes` to the length of the response's own NDJSON line.
+///
+/// The count is part of the line it measures, so its digits are included.
+fn record_response_size(reply: &mut Reply) {
+    reply.response.meta.resp_bytes = Some(0);
+    let Ok(json) = reply.to_json() else {
+        return;
+    };
+    // Length without the placeholder digit, then grow until the digits fit
+    let base = json.len() as u64 - 1;
+    let digits = |n: u64| n.to_string().len() as u64;
+    let mut size = base + 1;
+    while base + digits(size) != size {
+        size = base + digits(size);
+    }
+    reply.response.meta.resp_bytes = Some(size);
+}
+
+/// Write a response as an NDJSON line (or frame).
+///
+/// Returns `Ok(false)` if the client disconnected before the response could be
+/// delivered, which is a normal occurrence and not treated as an error.
+fn write_response(writer: &mut impl Write, reply: &Reply, framing: FramingMode) -> Result<bool> {
+    let response = &reply.response;
+    let json = reply.to_json()?;
+    match framing
+        .write_frame(writer, &json)
+        .and_then(|_| writer.flush())
+    {
+        Ok(()) => Ok(true),
+        Err(e) if is_client_gone(&e) => {
+            debug!(id = %response.id, error = %e, "Client disconnected before response was sent");
+            Ok(false)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Request params for an audit record, with the `redact` keys' values hidden.
+fn redact_params(
+    params: &HashMap<String, serde_json::Value>,
+    redact: &[String],
+) -> serde_json::Value {
+    params
+        .iter()
+        .map(|(key, value)| {
+            let value = if redact.contains(key) {
+                serde_json::Value::from("[REDACTED]")
+            } else {
+                value.clone()
+            };
+            (key.clone(), value)
+        })
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+/// UID of the process on the other end of a UNIX socket, where the platform reports it.
+fn peer_uid(stream: &UnixStream) -> Option<u32> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut cred: libc::ucred = unsafe { std::mem::zeroed() };
+        let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+        // SAFETY: `cred` and `len` describe a valid, writable ucred buffer
+        let rc = unsafe {
+            libc::getsockopt(
+                stream.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_PEERCRED,
+                (&mut cred as *mut libc::ucred).cast(),
+                &mut len,
+            )
+        };
+        (rc == 0).then_some(cred.uid)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let (mut uid, mut gid) = (0, 0);
+        // SAFETY: both out-pointers are valid for writes
+        let rc = unsafe { libc::getpeereid(stream.as_raw_fd(), &mut uid, &mut gid) };
+        (rc == 0).then_some(uid)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        let _ = stream;
+        None
+    }
+}
+
+/// Whether an I/O error means the peer closed the connection.
+fn is_client_gone(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::BrokenPipe
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+    )
+}
+
 /// Expand `~` in path to home directory.
 fn expand_path(path: &Path) -> Result<PathBuf> {
     let path_str = path.to_string_lossy();
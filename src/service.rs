@@ -2,9 +2,16 @@
 //!
 //! Implement [`FgpService`] to create your daemon's business logic.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use serde_json::value::RawValue;
 use serde_json::Value;
-use std::collections::HashMap;
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::protocol::{error_codes, ServiceError};
 
 /// Trait for FGP daemon services.
 ///
@@ -55,6 +62,7 @@ use std::collections::HashMap;
 ///                     param_type: "integer".into(),
 ///                     required: false,
 ///                     default: None,
+///                     ..Default::default()
 ///                 }),
 ///         ]
 ///     }
@@ -81,6 +89,162 @@ pub trait FgpService: Send + Sync {
     /// * `Err(_)` - Error to send back to client
     fn dispatch(&self, method: &str, params: HashMap<String, Value>) -> Result<Value>;
 
+    /// Dispatch a method call with access to per-request context.
+    ///
+    /// Called by the default [`dispatch_raw`](FgpService::dispatch_raw). The default
+    /// implementation ignores the context and forwards to
+    /// [`dispatch`](FgpService::dispatch); override it when a handler needs the request
+    /// ID or trace ID (e.g. to propagate tracing downstream).
+    fn dispatch_with_context(
+        &self,
+        method: &str,
+        params: HashMap<String, Value>,
+        ctx: &RequestContext,
+    ) -> Result<Value> {
+        let _ = ctx;
+        self.dispatch(method, params)
+    }
+
+    /// Dispatch a method call whose result may already be serialized JSON.
+    ///
+    /// The server always calls this method. The default implementation forwards to
+    /// [`dispatch_with_context`](FgpService::dispatch_with_context). Override it for
+    /// methods that proxy an upstream already returning JSON text: a
+    /// [`RawJson::from_string`] result is written into the response's `result`
+    /// without being parsed into a [`Value`] and serialized again.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use fgp_daemon::FgpService;
+    /// # use fgp_daemon::service::{RawJson, RequestContext};
+    /// # use std::collections::HashMap;
+    /// # use serde_json::Value;
+    /// # use anyhow::Result;
+    /// struct Proxy;
+    ///
+    /// impl FgpService for Proxy {
+    ///     fn name(&self) -> &str { "proxy" }
+    ///     fn version(&self) -> &str { "1.0.0" }
+    ///
+    ///     fn dispatch(&self, method: &str, _: HashMap<String, Value>) -> Result<Value> {
+    ///         anyhow::bail!("Unknown method: {}", method)
+    ///     }
+    ///
+    ///     fn dispatch_raw(
+    ///         &self,
+    ///         method: &str,
+    ///         params: HashMap<String, Value>,
+    ///         ctx: &RequestContext,
+    ///     ) -> Result<RawJson> {
+    ///         match method {
+    ///             // e.g. the body of an upstream HTTP response
+    ///             "proxy.fetch" => RawJson::from_string(r#"{"items": [1, 2, 3]}"#.to_string()),
+    ///             _ => self.dispatch_with_context(method, params, ctx).map(RawJson::from),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn dispatch_raw(
+        &self,
+        method: &str,
+        params: HashMap<String, Value>,
+        ctx: &RequestContext,
+    ) -> Result<RawJson> {
+        self.dispatch_with_context(method, params, ctx)
+            .map(RawJson::from)
+    }
+
+    /// Whether `method` streams its result through
+    /// [`dispatch_stream`](Self::dispatch_stream) instead of being dispatched.
+    ///
+    /// Called with the qualified method name. The default is `false` for all.
+    fn streams(&self, method: &str) -> bool {
+        let _ = method;
+        false
+    }
+
+    /// Produce the result of a method listed by [`streams`](Self::streams) in chunks.
+    ///
+    /// Each chunk the iterator yields is sent as soon as it is ready, as a frame
+    /// holding a JSON array of items and flagged
+    /// [`partial`](crate::protocol::ResponseMeta::partial); the stream ends with an
+    /// unflagged frame holding an empty array. An `Err`, from this method or from the
+    /// iterator, ends the stream with an error frame instead, as does running past the
+    /// request's time budget. Clients read streams with
+    /// [`FgpClient::call_collect_stream`](crate::FgpClient::call_collect_stream).
+    ///
+    /// Streamed results are never cached. Inside a `bundle` the chunks are collected
+    /// into one array.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use fgp_daemon::FgpService;
+    /// # use fgp_daemon::service::{RequestContext, ResultChunks};
+    /// # use std::collections::HashMap;
+    /// # use serde_json::{json, Value};
+    /// # use anyhow::Result;
+    /// struct Export;
+    ///
+    /// impl FgpService for Export {
+    ///     fn name(&self) -> &str { "export" }
+    ///     fn version(&self) -> &str { "1.0.0" }
+    ///
+    ///     fn dispatch(&self, method: &str, _: HashMap<String, Value>) -> Result<Value> {
+    ///         anyhow::bail!("Unknown method: {}", method)
+    ///     }
+    ///
+    ///     fn streams(&self, method: &str) -> bool {
+    ///         method == "export.ids"
+    ///     }
+    ///
+    ///     fn dispatch_stream(
+    ///         &self,
+    ///         _method: &str,
+    ///         _params: HashMap<String, Value>,
+    ///         _ctx: &RequestContext,
+    ///     ) -> Result<ResultChunks> {
+    ///         // e.g. one page of an upstream listing per chunk
+    ///         Ok(Box::new((0..3).map(|page| Ok(vec![json!(page * 2), json!(page * 2 + 1)]))))
+    ///     }
+    /// }
+    /// ```
+    fn dispatch_stream(
+        &self,
+        method: &str,
+        params: HashMap<String, Value>,
+        ctx: &RequestContext,
+    ) -> Result<ResultChunks> {
+        let _ = (params, ctx);
+        Err(ServiceError::new(
+            error_codes::UNKNOWN_METHOD,
+            format!("Method '{}' does not stream its result", method),
+        )
+        .into())
+    }
+
+    /// Method namespaces this service accepts.
+    ///
+    /// The server accepts `<namespace>.<method>` for each of these and rewrites the
+    /// prefix to [`name`](Self::name) before dispatch, so `dispatch` only ever sees
+    /// `<name>.<method>`. Override it after renaming a service to keep accepting the
+    /// old prefix from existing clients. The service name is accepted even if it isn't
+    /// listed. The default is `[self.name()]`.
+    fn namespaces(&self) -> Vec<&str> {
+        vec![self.name()]
+    }
+
+    /// Protocol versions this service understands.
+    ///
+    /// Each request is answered at the highest listed version not above the request's
+    /// `v`, and that version is reported in `meta.protocol_v`. A request older than
+    /// every listed version is rejected with `INVALID_REQUEST`. The default is
+    /// `[PROTOCOL_VERSION]`.
+    fn supported_versions(&self) -> Vec<u8> {
+        vec![crate::PROTOCOL_VERSION]
+    }
+
     /// List of methods this service provides.
     ///
     /// Used by the `methods` standard method to advertise available methods.
@@ -103,13 +267,319 @@ pub trait FgpService: Send + Sync {
         Ok(())
     }
 
+    /// Called when the daemon is stopping, with the reason why.
+    ///
+    /// Override instead of [`on_stop`](Self::on_stop) to clean up differently
+    /// depending on urgency, e.g. flushing less after a signal. The server calls this
+    /// rather than `on_stop`; the default calls `on_stop`.
+    fn on_stop_with_reason(&self, reason: StopReason) -> Result<()> {
+        let _ = reason;
+        self.on_stop()
+    }
+
+    /// Called by the `reload` built-in to refresh state without restarting.
+    ///
+    /// Override to re-read configuration or credentials in place, typically behind a
+    /// `RwLock`. The daemon keeps serving other connections while this runs, so it may
+    /// overlap with `dispatch` calls (and with itself, if two clients reload at once):
+    /// build the new state first, then swap it in under a short write lock so requests
+    /// never see it half-updated. On error the caller gets the error and the service
+    /// should keep its current state. The default does nothing.
+    ///
+    /// The built-in is disabled unless the server enables it with
+    /// [`FgpServer::with_reload_method`](crate::FgpServer::with_reload_method).
+    fn reload(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Server-wide capabilities, reported in the `methods` response.
+    ///
+    /// The server fills in what it provides itself: `batching` (via `bundle`) and a
+    /// `max_request_bytes` no larger than its line limit. The default declares nothing else.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
+    /// Error codes this service returns in addition to the standard ones in
+    /// [`error_codes`](crate::protocol::error_codes).
+    ///
+    /// Listed under `error_codes` in the `methods` and `schema` responses. When
+    /// non-empty, the server logs a warning whenever a dispatch fails with a code that
+    /// is neither standard nor declared here, which catches typos. The default declares
+    /// none and disables the check.
+    fn error_codes(&self) -> Vec<&str> {
+        Vec::new()
+    }
+
     /// Custom health check.
     ///
     /// Override to add service-specific health information.
-    /// The default implementation returns an empty map.
+    /// The default implementation returns an empty map. Only called for a deep
+    /// `health` request (`{"deep": true}`), so expensive probes belong here.
     fn health_check(&self) -> HashMap<String, HealthStatus> {
         HashMap::new()
     }
+
+    /// Whether this service handles the built-in `method` itself.
+    ///
+    /// Called with the bare built-in name (`"health"`, `"methods"`, `"stop"`, ...).
+    /// Returning `true` makes the server skip its own handling and pass the request to
+    /// [`dispatch`](Self::dispatch) as `<service>.<method>`. The default is `false` for all.
+    ///
+    /// Overriding `stop` means the server no longer shuts down on a `stop` request, so
+    /// `stop_service` falls back to signalling the process. Only take over `stop` if
+    /// your implementation still ends the process.
+    fn builtin_override(&self, method: &str) -> bool {
+        let _ = method;
+        false
+    }
+
+    /// State for a new client connection.
+    ///
+    /// Called once per connection. Every request on the connection sees the state
+    /// through [`RequestContext::state`] (in
+    /// [`dispatch_with_context`](Self::dispatch_with_context)), and it is dropped when
+    /// the client disconnects. Requests only get shared access, so wrap anything they
+    /// change in a `Mutex`, e.g. a session established by a `login` call. The default
+    /// is `None`.
+    fn new_connection_state(&self) -> Option<ConnectionState> {
+        None
+    }
+}
+
+/// Capabilities reported under `capabilities` in the `methods` response.
+///
+/// Lets clients enable features per daemon without trial and error.
+///
+/// # Example
+///
+/// ```rust
+/// use fgp_daemon::service::Capabilities;
+///
+/// let capabilities = Capabilities {
+///     streaming: true,
+///     ..Default::default()
+/// }
+/// .with_feature("attachments", serde_json::json!({"max_mb": 25}));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Capabilities {
+    /// Results can be streamed
+    #[serde(default)]
+    pub streaming: bool,
+    /// Several calls can be sent in one request (the `bundle` built-in)
+    #[serde(default)]
+    pub batching: bool,
+    /// Payloads can be compressed
+    #[serde(default)]
+    pub compression: bool,
+    /// Largest accepted request line in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_request_bytes: Option<usize>,
+    /// Service-specific features
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub features: BTreeMap<String, Value>,
+}
+
+impl Capabilities {
+    /// Declare a service-specific feature.
+    pub fn with_feature(mut self, name: impl Into<String>, value: Value) -> Self {
+        self.features.insert(name.into(), value);
+        self
+    }
+}
+
+/// Deserialize request params into a typed struct.
+///
+/// Serde handles validation and defaults. On failure the error is a
+/// [`ServiceError`] with code `INVALID_PARAMS`, which the server reports as such.
+///
+/// # Example
+///
+/// ```rust
+/// use fgp_daemon::protocol::{error_codes, ServiceError};
+/// use fgp_daemon::service::from_params;
+/// use serde_json::{json, Value};
+/// use std::collections::HashMap;
+///
+/// #[derive(serde::Deserialize)]
+/// struct SendEmail {
+///     to: String,
+///     #[serde(default)]
+///     cc: Vec<String>,
+/// }
+///
+/// let params: HashMap<String, Value> = HashMap::from([("to".into(), json!("a@example.com"))]);
+/// let args: SendEmail = from_params(params)?;
+/// assert_eq!(args.to, "a@example.com");
+/// assert!(args.cc.is_empty());
+///
+/// let err = from_params::<SendEmail>(HashMap::new()).err().unwrap();
+/// let err = err.downcast_ref::<ServiceError>().unwrap();
+/// assert_eq!(err.code, error_codes::INVALID_PARAMS);
+/// assert_eq!(err.message, "Invalid params: missing field `to`");
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+pub fn from_params<T: serde::de::DeserializeOwned>(params: HashMap<String, Value>) -> Result<T> {
+    serde_json::from_value(Value::Object(params.into_iter().collect())).map_err(|e| {
+        ServiceError::new(
+            error_codes::INVALID_PARAMS,
+            format!("Invalid params: {}", e),
+        )
+        .into()
+    })
+}
+
+/// Extract one typed param; used by [`fgp_params!`](crate::fgp_params).
+///
+/// A missing param is read as `null`, so `Option<T>` params may be left out. Fails
+/// with an `INVALID_PARAMS` [`ServiceError`] naming the param (also under
+/// `details.param`) when it is missing or has the wrong type.
+pub fn param<T: serde::de::DeserializeOwned>(
+    params: &HashMap<String, Value>,
+    name: &str,
+) -> std::result::Result<T, ServiceError> {
+    let value = params.get(name);
+    serde_json::from_value(value.cloned().unwrap_or(Value::Null)).map_err(|e| {
+        let message = match value {
+            None => format!("Missing required parameter: {}", name),
+            Some(_) => format!("Invalid parameter '{}': {}", name, e),
+        };
+        ServiceError::new(error_codes::INVALID_PARAMS, message)
+            .with_details(serde_json::json!({ "param": name }))
+    })
+}
+
+/// Extract several typed params at once.
+///
+/// Expands to a `Result` holding a tuple with one value per `"name": Type` pair, in
+/// order. Each param is read with [`param`](crate::service::param): `Option<T>` params
+/// may be missing, and a missing or mistyped param fails with `INVALID_PARAMS` naming
+/// it, which the server reports as such.
+///
+/// # Example
+///
+/// ```rust
+/// use fgp_daemon::fgp_params;
+/// use fgp_daemon::protocol::{error_codes, ServiceError};
+/// use serde_json::{json, Value};
+/// use std::collections::HashMap;
+///
+/// let params: HashMap<String, Value> = HashMap::from([
+///     ("to".into(), json!("a@example.com")),
+///     ("subject".into(), json!("Hi")),
+/// ]);
+/// let (to, subject, body) =
+///     fgp_params!(params, "to": String, "subject": String, "body": Option<String>)?;
+/// assert_eq!(to, "a@example.com");
+/// assert_eq!(subject, "Hi");
+/// assert_eq!(body, None);
+///
+/// let err = fgp_params!(params, "to": String, "limit": u32).err().unwrap();
+/// assert_eq!(err.code, error_codes::INVALID_PARAMS);
+/// assert_eq!(err.message, "Missing required parameter: limit");
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[macro_export]
+macro_rules! fgp_params {
+    ($params:expr, $($name:literal : $ty:ty),+ $(,)?) => {{
+        let params = &$params;
+        (|| {
+            Ok::<_, $crate::protocol::ServiceError>((
+                $($crate::service::param::<$ty>(params, $name)?,)+
+            ))
+        })()
+    }};
+}
+
+/// State shared by the requests on one connection; see
+/// [`FgpService::new_connection_state`].
+pub type ConnectionState = Arc<dyn Any + Send + Sync>;
+
+/// The chunks of a streamed result; see [`FgpService::dispatch_stream`].
+pub type ResultChunks = Box<dyn Iterator<Item = Result<Vec<Value>>>>;
+
+/// Per-request context passed to [`FgpService::dispatch_with_context`].
+#[derive(Debug, Clone, Default)]
+pub struct RequestContext {
+    /// Request ID (as sent by the client)
+    pub id: String,
+    /// Trace/correlation ID, if the client supplied one
+    pub trace_id: Option<String>,
+    /// State of the connection the request arrived on, if the service keeps any
+    pub connection_state: Option<ConnectionState>,
+}
+
+impl RequestContext {
+    /// The connection's state, if there is one and it is a `T`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use fgp_daemon::service::RequestContext;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// #[derive(Default)]
+    /// struct Session {
+    ///     user: Mutex<Option<String>>,
+    /// }
+    ///
+    /// let ctx = RequestContext {
+    ///     connection_state: Some(Arc::new(Session::default())),
+    ///     ..Default::default()
+    /// };
+    /// *ctx.state::<Session>().unwrap().user.lock().unwrap() = Some("ada".into());
+    /// assert!(ctx.state::<String>().is_none());
+    /// ```
+    pub fn state<T: Any>(&self) -> Option<&T> {
+        self.connection_state.as_deref()?.downcast_ref()
+    }
+}
+
+/// A dispatch result from [`FgpService::dispatch_raw`]: already-serialized JSON text,
+/// or a [`Value`].
+#[derive(Debug, Clone)]
+pub struct RawJson(pub(crate) RawJsonInner);
+
+#[derive(Debug, Clone)]
+pub(crate) enum RawJsonInner {
+    Value(Value),
+    Raw(Box<RawValue>),
+}
+
+impl RawJson {
+    /// Wrap serialized JSON without building a [`Value`].
+    ///
+    /// The text is checked to be valid JSON. Line breaks (which can only be whitespace
+    /// in valid JSON) are replaced by spaces so the result fits on one NDJSON line.
+    pub fn from_string(json: String) -> Result<Self> {
+        let json = if json.contains(['\n', '\r']) {
+            json.replace(['\n', '\r'], " ")
+        } else {
+            json
+        };
+        let raw = RawValue::from_string(json).context("Invalid raw JSON result")?;
+        Ok(Self(RawJsonInner::Raw(raw)))
+    }
+
+    /// Wrap serialized JSON bytes; see [`from_string`](Self::from_string).
+    pub fn from_bytes(json: Vec<u8>) -> Result<Self> {
+        Self::from_string(String::from_utf8(json).context("Raw JSON result is not UTF-8")?)
+    }
+
+    /// The result as a [`Value`], parsing it if it is raw.
+    pub fn into_value(self) -> Result<Value> {
+        match self.0 {
+            RawJsonInner::Value(value) => Ok(value),
+            RawJsonInner::Raw(raw) => Ok(serde_json::from_str(raw.get())?),
+        }
+    }
+}
+
+impl From<Value> for RawJson {
+    fn from(value: Value) -> Self {
+        Self(RawJsonInner::Value(value))
+    }
 }
 
 /// Method information for the `methods` response.
@@ -158,8 +628,19 @@ pub struct MethodInfo {
     pub errors: Vec<String>,
 
     /// Whether this method is deprecated
+    ///
+    /// Calls to a deprecated method still run, with a warning in `meta.warnings`.
     #[serde(default)]
     pub deprecated: bool,
+
+    /// Method to use instead of this deprecated one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaced_by: Option<String>,
+
+    /// Only the declared params are accepted: exported schemas get
+    /// `"additionalProperties": false` unless they set it themselves
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub strict_params: bool,
 }
 
 impl MethodInfo {
@@ -174,9 +655,48 @@ impl MethodInfo {
             examples: vec![],
             errors: vec![],
             deprecated: false,
+            replaced_by: None,
+            strict_params: false,
         }
     }
 
+    /// Create a method info with its parameter and return value schemas.
+    pub fn with_io(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        params_schema: Value,
+        returns_schema: Value,
+    ) -> Self {
+        Self::new(name, description)
+            .schema(params_schema)
+            .returns(returns_schema)
+    }
+
+    /// Create a method info from builders for its parameter and return value schemas.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use fgp_daemon::schema::SchemaBuilder;
+    /// use fgp_daemon::service::MethodInfo;
+    ///
+    /// let method = MethodInfo::from_builders(
+    ///     "gmail.count",
+    ///     "Count unread emails",
+    ///     SchemaBuilder::object().property("label", SchemaBuilder::string()),
+    ///     SchemaBuilder::object().property("count", SchemaBuilder::integer()),
+    /// );
+    /// assert_eq!(method.returns.unwrap()["properties"]["count"]["type"], "integer");
+    /// ```
+    pub fn from_builders(
+        name: impl Into<String>,
+        description: impl Into<String>,
+        params: crate::schema::SchemaBuilder,
+        returns: crate::schema::SchemaBuilder,
+    ) -> Self {
+        Self::with_io(name, description, params.build(), returns.build())
+    }
+
     /// Set the full JSON Schema for parameters.
     pub fn schema(mut self, schema: Value) -> Self {
         self.schema = Some(schema);
@@ -226,6 +746,22 @@ impl MethodInfo {
         self
     }
 
+    /// Declare that the method takes no params beyond those listed.
+    ///
+    /// Schema exports (`schema` built-in, OpenAI/Anthropic/MCP converters) then emit
+    /// `"additionalProperties": false`, so models stick to the declared params.
+    pub fn strict_params(mut self) -> Self {
+        self.strict_params = true;
+        self
+    }
+
+    /// Mark this method as deprecated in favor of `method`.
+    pub fn replaced_by(mut self, method: impl Into<String>) -> Self {
+        self.deprecated = true;
+        self.replaced_by = Some(method.into());
+        self
+    }
+
     /// Add legacy param info (for backward compatibility).
     pub fn param(mut self, param: ParamInfo) -> Self {
         self.params.push(param);
@@ -246,19 +782,33 @@ pub struct MethodExample {
 }
 
 /// Parameter information for method documentation.
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+///
+/// Optional fields can be left out with `..Default::default()`.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct ParamInfo {
     /// Parameter name
     pub name: String,
     /// Parameter type (e.g., "string", "integer", "boolean", "object")
     #[serde(rename = "type")]
     pub param_type: String,
+    /// Human-readable description (used in synthesized schemas)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
     /// Whether this parameter is required
     #[serde(default)]
     pub required: bool,
     /// Default value (if any)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default: Option<Value>,
+    /// Element type for `array` parameters (e.g., "string", "integer", "object")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items_type: Option<String>,
+    /// Full JSON Schema for array elements (takes precedence over `items_type`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items_schema: Option<Value>,
+    /// Allowed values (applied to the elements for `array` parameters)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enum_values: Option<Vec<Value>>,
 }
 
 /// Health status for a dependency.
@@ -272,6 +822,15 @@ pub struct HealthStatus {
     /// Additional status message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    /// Most recent error, kept after the dependency recovers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// When `last_error` occurred (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error_at: Option<String>,
+    /// When the dependency was last probed (RFC 3339)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_checked: Option<String>,
 }
 
 impl HealthStatus {
@@ -281,6 +840,9 @@ impl HealthStatus {
             ok: true,
             latency_ms: None,
             message: None,
+            last_error: None,
+            last_error_at: None,
+            last_checked: None,
         }
     }
 
@@ -290,6 +852,9 @@ impl HealthStatus {
             ok: true,
             latency_ms: Some(latency_ms),
             message: None,
+            last_error: None,
+            last_error_at: None,
+            last_checked: None,
         }
     }
 
@@ -299,6 +864,394 @@ impl HealthStatus {
             ok: false,
             latency_ms: None,
             message: Some(message.into()),
+            last_error: None,
+            last_error_at: None,
+            last_checked: None,
+        }
+    }
+}
+
+/// Records the latest probe of a dependency, for reporting from
+/// [`FgpService::health_check`].
+///
+/// The last error and its time are kept after the dependency recovers, so a flapping
+/// dependency is visible in `health` even while it is currently up.
+///
+/// # Example
+///
+/// ```rust
+/// use fgp_daemon::service::HealthTracker;
+///
+/// let tracker = HealthTracker::new();
+/// tracker.check(|| anyhow::bail!("connection refused"));
+/// tracker.check(|| Ok(()));
+///
+/// let status = tracker.status().unwrap();
+/// assert!(status.ok);
+/// assert_eq!(status.last_error.as_deref(), Some("connection refused"));
+/// ```
+#[derive(Debug, Default)]
+pub struct HealthTracker {
+    status: Mutex<Option<HealthStatus>>,
+}
+
+impl HealthTracker {
+    /// Create a tracker with no probes recorded.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run a probe, recording its outcome and latency.
+    pub fn check(&self, probe: impl FnOnce() -> Result<()>) -> HealthStatus {
+        let start = Instant::now();
+        let status = match probe() {
+            Ok(()) => HealthStatus::healthy_with_latency(start.elapsed().as_secs_f64() * 1000.0),
+            Err(e) => HealthStatus::unhealthy(e.to_string()),
+        };
+        self.record(status)
+    }
+
+    /// Record a probe result, stamping `last_checked` and carrying over the last error.
+    pub fn record(&self, mut status: HealthStatus) -> HealthStatus {
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        let mut current = self.status.lock().unwrap_or_else(|e| e.into_inner());
+
+        if status.ok {
+            if let Some(previous) = current.as_ref() {
+                status.last_error = previous.last_error.clone();
+                status.last_error_at = previous.last_error_at.clone();
+            }
+        } else {
+            status.last_error = status.message.clone();
+            status.last_error_at = Some(now.clone());
         }
+        status.last_checked = Some(now);
+
+        *current = Some(status.clone());
+        status
+    }
+
+    /// The most recently recorded status, or `None` before the first probe.
+    pub fn status(&self) -> Option<HealthStatus> {
+        self.status
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
     }
 }
+
+type Probe = Box<dyn FnOnce() -> Result<()> + Send>;
+
+/// Runs a set of named dependency probes concurrently, for building the map returned
+/// from [`FgpService::health_check`].
+///
+/// Probes run on a small pool of threads. A probe that is still running when its
+/// timeout expires is reported as `unhealthy("timeout")` and left to finish in the
+/// background, so one hung dependency cannot stall the `health` response.
+///
+/// # Example
+///
+/// ```rust
+/// use fgp_daemon::service::HealthProbes;
+/// use std::time::Duration;
+///
+/// let statuses = HealthProbes::new()
+///     .timeout(Duration::from_millis(50))
+///     .probe("cache", || Ok(()))
+///     .probe("database", || anyhow::bail!("connection refused"))
+///     .probe("search", || {
+///         std::thread::sleep(Duration::from_secs(1));
+///         Ok(())
+///     })
+///     .run();
+///
+/// assert!(statuses["cache"].ok);
+/// assert_eq!(statuses["database"].message.as_deref(), Some("connection refused"));
+/// assert_eq!(statuses["search"].message.as_deref(), Some("timeout"));
+/// ```
+pub struct HealthProbes {
+    probes: Vec<(String, Duration, Probe)>,
+    timeout: Duration,
+    threads: usize,
+}
+
+impl Default for HealthProbes {
+    fn default() -> Self {
+        Self {
+            probes: Vec::new(),
+            timeout: Duration::from_secs(2),
+            threads: 4,
+        }
+    }
+}
+
+impl std::fmt::Debug for HealthProbes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HealthProbes")
+            .field(
+                "probes",
+                &self
+                    .probes
+                    .iter()
+                    .map(|(name, ..)| name)
+                    .collect::<Vec<_>>(),
+            )
+            .field("timeout", &self.timeout)
+            .field("threads", &self.threads)
+            .finish()
+    }
+}
+
+impl HealthProbes {
+    /// Create an empty probe set (2 second timeout, 4 threads).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the default per-probe timeout.
+    ///
+    /// Applies to probes added with [`probe`](Self::probe), including ones added earlier.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the maximum number of probes run at once (at least 1).
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    /// Add a probe using the default timeout.
+    pub fn probe(
+        self,
+        name: impl Into<String>,
+        probe: impl FnOnce() -> Result<()> + Send + 'static,
+    ) -> Self {
+        self.probe_with_timeout(name, Duration::ZERO, probe)
+    }
+
+    /// Add a probe with its own timeout.
+    pub fn probe_with_timeout(
+        mut self,
+        name: impl Into<String>,
+        timeout: Duration,
+        probe: impl FnOnce() -> Result<()> + Send + 'static,
+    ) -> Self {
+        self.probes.push((name.into(), timeout, Box::new(probe)));
+        self
+    }
+
+    /// Run every probe and collect the results by name.
+    ///
+    /// A probe's timeout counts from when it starts running, not from when `run` is
+    /// called. Probes that return an error or panic are reported as unhealthy.
+    pub fn run(self) -> HashMap<String, HealthStatus> {
+        enum Event {
+            Started(usize, Instant),
+            Finished(usize, HealthStatus),
+        }
+
+        let default_timeout = self.timeout;
+        let mut names = Vec::with_capacity(self.probes.len());
+        let mut timeouts = Vec::with_capacity(self.probes.len());
+        let queue: VecDeque<(usize, Probe)> = self
+            .probes
+            .into_iter()
+            .enumerate()
+            .map(|(index, (name, timeout, probe))| {
+                names.push(name);
+                timeouts.push(if timeout.is_zero() {
+                    default_timeout
+                } else {
+                    timeout
+                });
+                (index, probe)
+            })
+            .collect();
+        let queue = Arc::new(Mutex::new(queue));
+        let (tx, rx) = mpsc::channel();
+
+        let spawn_worker = || {
+            let queue = Arc::clone(&queue);
+            let tx = tx.clone();
+            std::thread::spawn(move || loop {
+                let next = queue.lock().unwrap_or_else(|e| e.into_inner()).pop_front();
+                let Some((index, probe)) = next else { break };
+                let start = Instant::now();
+                if tx.send(Event::Started(index, start)).is_err() {
+                    break;
+                }
+                let status = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(probe)) {
+                    Ok(Ok(())) => {
+                        HealthStatus::healthy_with_latency(start.elapsed().as_secs_f64() * 1000.0)
+                    }
+                    Ok(Err(e)) => HealthStatus::unhealthy(e.to_string()),
+                    Err(_) => HealthStatus::unhealthy("probe panicked"),
+                };
+                if tx.send(Event::Finished(index, status)).is_err() {
+                    break;
+                }
+            });
+        };
+
+        for _ in 0..self.threads.min(names.len()) {
+            spawn_worker();
+        }
+
+        let mut results: Vec<Option<HealthStatus>> = vec![None; names.len()];
+        let mut deadlines: HashMap<usize, Instant> = HashMap::new();
+        let mut remaining = names.len();
+
+        while remaining > 0 {
+            let next_deadline = deadlines.values().min().copied();
+            let event = match next_deadline {
+                Some(deadline) => {
+                    match rx.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                        Ok(event) => Some(event),
+                        Err(mpsc::RecvTimeoutError::Timeout) => None,
+                        Err(mpsc::RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                None => match rx.recv() {
+                    Ok(event) => Some(event),
+                    Err(_) => break,
+                },
+            };
+
+            match event {
+                Some(Event::Started(index, start)) => {
+                    deadlines.insert(index, start + timeouts[index]);
+                }
+                Some(Event::Finished(index, status)) => {
+                    if deadlines.remove(&index).is_some() {
+                        results[index] = Some(status);
+                        remaining -= 1;
+                    }
+                }
+                None => {
+                    // Give up on every probe past its deadline; each one still occupies
+                    // a worker, so replace it to keep the rest of the queue moving.
+                    let now = Instant::now();
+                    let expired: Vec<usize> = deadlines
+                        .iter()
+                        .filter(|(_, deadline)| **deadline <= now)
+                        .map(|(index, _)| *index)
+                        .collect();
+                    for index in expired {
+                        deadlines.remove(&index);
+                        results[index] = Some(HealthStatus::unhealthy("timeout"));
+                        remaining -= 1;
+                        spawn_worker();
+                    }
+                }
+            }
+        }
+
+        names
+            .into_iter()
+            .zip(results)
+            .map(|(name, status)| {
+                (
+                    name,
+                    status.unwrap_or_else(|| HealthStatus::unhealthy("probe did not run")),
+                )
+            })
+            .collect()
+    }
+}
+
+/// Why the daemon is stopping; see [`FgpService::on_stop_with_reason`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// The `stop` built-in was called, or `FgpServer::stop`
+    MethodRequest,
+    /// `SIGTERM` or `SIGINT` arrived (with `FgpServer::serve_with_signals`)
+    Signal,
+    /// Serving failed after the service had started
+    Error,
+}
+
+/// Overall daemon state reported by the `health` built-in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthState {
+    /// All dependencies are healthy (or there are none)
+    Healthy,
+    /// Some dependencies are unhealthy
+    Degraded,
+    /// Every dependency is unhealthy
+    Unhealthy,
+}
+
+impl HealthState {
+    /// Derive the overall state from per-dependency statuses.
+    pub fn from_services(services: &HashMap<String, HealthStatus>) -> Self {
+        if services.values().all(|s| s.ok) {
+            Self::Healthy
+        } else if services.values().any(|s| s.ok) {
+            Self::Degraded
+        } else {
+            Self::Unhealthy
+        }
+    }
+}
+
+/// Connection and request counters reported in the `health` response.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ServerMetrics {
+    /// Connections accepted since the daemon started
+    pub connections_total: u64,
+    /// Connections currently open
+    pub connections_open: usize,
+    /// Open connections waiting for a thread of the connection pool (see
+    /// `FgpServer::with_connection_threads`); a growing count means the daemon is
+    /// saturated. Without a pool each connection gets a thread straight away, so this
+    /// stays near zero
+    #[serde(default)]
+    pub queued_connections: usize,
+    /// Requests currently being handled
+    pub active_requests: usize,
+    /// Calls answered from the response cache (see `FgpServer::with_cache`)
+    #[serde(default)]
+    pub cache_hits: u64,
+    /// Calls to cached methods that had to be dispatched
+    #[serde(default)]
+    pub cache_misses: u64,
+}
+
+/// Result of the `health` built-in method.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct HealthReport {
+    /// Overall state
+    pub status: HealthState,
+    /// Daemon process ID
+    pub pid: u32,
+    /// Start time (RFC 3339)
+    pub started_at: String,
+    /// Service version
+    pub version: String,
+    /// fgp-daemon SDK version (empty for daemons built before it was reported)
+    #[serde(default)]
+    pub sdk_version: String,
+    /// Protocol version (0 for daemons built before it was reported)
+    #[serde(default)]
+    pub protocol_version: u8,
+    /// Git commit of the SDK build, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_sha: Option<String>,
+    /// Seconds since the daemon started
+    pub uptime_seconds: u64,
+    /// Starts after the first one, from the server's state file (if it has one)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart_count: Option<u64>,
+    /// When the daemon first started (RFC 3339), from the server's state file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_started_at: Option<String>,
+    /// Connection and request counters (zero for daemons built before they were reported)
+    #[serde(default)]
+    pub metrics: ServerMetrics,
+    /// Per-dependency health
+    #[serde(default)]
+    pub services: HashMap<String, HealthStatus>,
+}
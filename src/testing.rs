@@ -0,0 +1,161 @@
+//! In-process test harness for FGP services.
+//!
+//! [`FgpTestHarness`] runs requests through the same built-in handling and method
+//! namespacing as [`FgpServer`](crate::FgpServer), without binding a socket or
+//! spawning connection threads, so service tests are fast and deterministic.
+//!
+//! # Example
+//!
+//! ```rust
+//! use fgp_daemon::testing::FgpTestHarness;
+//! use fgp_daemon::FgpService;
+//! use serde_json::{json, Value};
+//! use std::collections::HashMap;
+//!
+//! struct EchoService;
+//!
+//! impl FgpService for EchoService {
+//!     fn name(&self) -> &str { "echo" }
+//!     fn version(&self) -> &str { "1.0.0" }
+//!
+//!     fn dispatch(&self, method: &str, params: HashMap<String, Value>) -> anyhow::Result<Value> {
+//!         match method {
+//!             "echo.echo" => Ok(json!({"echo": params.get("message")})),
+//!             _ => anyhow::bail!("Unknown method: {}", method),
+//!         }
+//!     }
+//! }
+//!
+//! let harness = FgpTestHarness::new(EchoService);
+//!
+//! let response = harness.call("echo", json!({"message": "hi"}));
+//! assert_eq!(response.result.unwrap()["echo"], "hi");
+//!
+//! let health = harness.call("health", json!({}));
+//! assert!(health.ok);
+//! ```
+
+use anyhow::{bail, Result};
+use std::time::{Duration, Instant};
+
+use crate::protocol::{params_map, Request, Response};
+use crate::schema::validate_schema;
+use crate::server::{FgpServer, ServerContext};
+use crate::service::{ConnectionState, FgpService};
+use crate::FgpClient;
+
+/// Calls a service the way [`FgpServer`] would, without a socket.
+///
+/// Requests without a timeout are dispatched inline on the calling thread. Requests
+/// with `timeout_ms` / `deadline_ms` use a worker thread, as on the real server. All
+/// calls act as if made on one connection, sharing its
+/// [connection state](FgpService::new_connection_state).
+pub struct FgpTestHarness<S> {
+    server: ServerContext<S>,
+    connection_state: Option<ConnectionState>,
+}
+
+impl<S: FgpService + 'static> FgpTestHarness<S> {
+    /// Create a harness around a service (`on_start` is not called).
+    pub fn new(service: S) -> Self {
+        let connection_state = service.new_connection_state();
+        Self {
+            server: ServerContext::detached(service),
+            connection_state,
+        }
+    }
+
+    /// Call a method with JSON params, as `FgpClient::call` would.
+    pub fn call(&self, method: &str, params: serde_json::Value) -> Response {
+        self.send(Request::new(method, params_map(params)))
+    }
+
+    /// Handle a fully-formed request. A streamed result is collected into one array.
+    pub fn send(&self, request: Request) -> Response {
+        FgpServer::handle_request_static(
+            request,
+            Instant::now(),
+            &self.server,
+            self.connection_state.as_ref(),
+        )
+        .into_response()
+    }
+
+    /// The wrapped service, for inspecting its state after calls.
+    pub fn service(&self) -> &S {
+        self.server.service()
+    }
+
+    /// Check every method's `schema` and `returns` with [`validate_schema`].
+    ///
+    /// Intended for a unit test, so schema mistakes fail CI instead of confusing callers:
+    ///
+    /// ```rust,ignore
+    /// #[test]
+    /// fn schemas_are_consistent() {
+    ///     FgpTestHarness::new(MyService::new()).validate_schemas().unwrap();
+    /// }
+    /// ```
+    pub fn validate_schemas(&self) -> Result<()> {
+        let mut problems = Vec::new();
+        for method in self.service().method_list() {
+            for (label, schema) in [("schema", &method.schema), ("returns", &method.returns)] {
+                if let Some(Err(e)) = schema.as_ref().map(validate_schema) {
+                    problems.push(format!("{} {}: {}", method.name, label, e));
+                }
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            bail!("{}", problems.join("\n"))
+        }
+    }
+
+    /// Whether the server would still be running, i.e. `stop` hasn't been called.
+    pub fn is_running(&self) -> bool {
+        self.server.is_running()
+    }
+}
+
+/// Number of timed `health` calls made by [`check_latency_budget`].
+const LATENCY_SAMPLES: usize = 11;
+
+/// Check that a running daemon answers `health` within `budget`.
+///
+/// Makes one warm-up call, then times several more with
+/// [`FgpClient::timed_call`] and compares their median against the budget, so a
+/// single slow call on a busy CI machine doesn't fail the check. Returns the median.
+/// Meant for catching latency regressions in a daemon's own test suite:
+///
+/// ```rust,ignore
+/// #[test]
+/// fn health_is_fast() {
+///     let client = FgpClient::new(&socket_path).unwrap();
+///     check_latency_budget(&client, Duration::from_millis(30)).unwrap();
+/// }
+/// ```
+pub fn check_latency_budget(client: &FgpClient, budget: Duration) -> Result<Duration> {
+    client.health()?;
+
+    let mut samples = Vec::with_capacity(LATENCY_SAMPLES);
+    for _ in 0..LATENCY_SAMPLES {
+        let (response, elapsed) = client.timed_call("health", serde_json::Value::Null)?;
+        if !response.ok {
+            bail!("health call failed: {:?}", response.error);
+        }
+        samples.push(elapsed);
+    }
+    samples.sort();
+
+    let median = samples[samples.len() / 2];
+    if median > budget {
+        bail!(
+            "health round trip took {:?} (median of {}), over the {:?} budget",
+            median,
+            samples.len(),
+            budget
+        );
+    }
+    Ok(median)
+}
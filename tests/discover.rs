@@ -0,0 +1,96 @@
+//! Service discovery tests.
+//!
+//! Kept in their own test binary because they point `HOME` at a temporary
+//! directory to install fake services under `~/.fgp/services`.
+
+use anyhow::Result;
+use fgp_daemon::client::discover_all;
+use fgp_daemon::lifecycle::{list_services, service_socket_path};
+use fgp_daemon::service::MethodInfo;
+use fgp_daemon::{FgpClient, FgpServer, FgpService};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::thread;
+use std::time::Duration;
+use tempfile::TempDir;
+
+struct EchoService;
+
+impl FgpService for EchoService {
+    fn name(&self) -> &str {
+        "echo"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn dispatch(&self, _method: &str, params: HashMap<String, Value>) -> Result<Value> {
+        Ok(serde_json::to_value(params)?)
+    }
+
+    fn method_list(&self) -> Vec<MethodInfo> {
+        vec![MethodInfo::new("echo.say", "Echo the params back")]
+    }
+}
+
+/// Install a service whose entrypoint exits straight away.
+fn install(name: &str) {
+    let service_dir = fgp_daemon::fgp_services_dir().join(name);
+    fs::create_dir_all(&service_dir).unwrap();
+    fs::write(
+        service_dir.join("manifest.json"),
+        r#"{"daemon": {"entrypoint": "daemon.sh"}}"#,
+    )
+    .unwrap();
+    let entrypoint = service_dir.join("daemon.sh");
+    fs::write(&entrypoint, "#!/bin/sh\nexit 1\n").unwrap();
+    fs::set_permissions(&entrypoint, fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[test]
+fn test_discover_all() {
+    let home = TempDir::new().unwrap();
+    std::env::set_var("HOME", home.path());
+
+    // No services directory yet
+    assert!(list_services().unwrap().is_empty());
+    assert!(discover_all(false).unwrap().is_empty());
+
+    install("echo");
+    install("broken");
+    // Not a service: no manifest
+    fs::create_dir_all(fgp_daemon::fgp_services_dir().join("notes")).unwrap();
+    assert_eq!(list_services().unwrap(), ["broken", "echo"]);
+
+    let server = FgpServer::new(EchoService, service_socket_path("echo")).unwrap();
+    thread::spawn(move || server.serve());
+    for _ in 0..50 {
+        if fgp_daemon::is_service_running("echo") {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    for auto_start in [false, true] {
+        let catalog = discover_all(auto_start).unwrap();
+        assert_eq!(catalog.len(), 2);
+
+        // The service that can't be reached (or started) doesn't stop discovery
+        let (name, broken) = &catalog[0];
+        assert_eq!(name, "broken");
+        assert!(broken.is_err());
+
+        let (name, methods) = &catalog[1];
+        assert_eq!(name, "echo");
+        let methods = methods.as_ref().unwrap();
+        assert!(methods.iter().any(|m| m.name == "echo.say"));
+    }
+
+    FgpClient::new(service_socket_path("echo"))
+        .unwrap()
+        .stop()
+        .unwrap();
+}
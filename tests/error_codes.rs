@@ -71,6 +71,7 @@ fn test_invalid_request_response() {
         meta: ResponseMeta {
             server_ms: 0.1,
             protocol_v: 1,
+            ..Default::default()
         },
     };
 
@@ -94,6 +95,7 @@ fn test_unknown_method_response() {
         meta: ResponseMeta {
             server_ms: 0.2,
             protocol_v: 1,
+            ..Default::default()
         },
     };
 
@@ -116,6 +118,7 @@ fn test_invalid_params_response() {
         meta: ResponseMeta {
             server_ms: 0.3,
             protocol_v: 1,
+            ..Default::default()
         },
     };
 
@@ -138,6 +141,7 @@ fn test_internal_error_response() {
         meta: ResponseMeta {
             server_ms: 100.0,
             protocol_v: 1,
+            ..Default::default()
         },
     };
 
@@ -159,6 +163,7 @@ fn test_not_found_response() {
         meta: ResponseMeta {
             server_ms: 5.0,
             protocol_v: 1,
+            ..Default::default()
         },
     };
 
@@ -180,6 +185,7 @@ fn test_unauthorized_response() {
         meta: ResponseMeta {
             server_ms: 1.0,
             protocol_v: 1,
+            ..Default::default()
         },
     };
 
@@ -201,6 +207,7 @@ fn test_timeout_response() {
         meta: ResponseMeta {
             server_ms: 30000.0,
             protocol_v: 1,
+            ..Default::default()
         },
     };
 
@@ -222,6 +229,7 @@ fn test_service_unavailable_response() {
         meta: ResponseMeta {
             server_ms: 0.5,
             protocol_v: 1,
+            ..Default::default()
         },
     };
 
@@ -271,6 +279,7 @@ fn test_error_response_full_serialization() {
         meta: ResponseMeta {
             server_ms: 1.5,
             protocol_v: 1,
+            ..Default::default()
         },
     };
 
@@ -411,6 +420,7 @@ fn test_success_response_has_no_error() {
         meta: ResponseMeta {
             server_ms: 5.0,
             protocol_v: 1,
+            ..Default::default()
         },
     };
 
@@ -433,6 +443,7 @@ fn test_error_response_has_no_result() {
         meta: ResponseMeta {
             server_ms: 5.0,
             protocol_v: 1,
+            ..Default::default()
         },
     };
 
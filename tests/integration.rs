@@ -7,15 +7,24 @@
 //! 01/14/2026 - Initial implementation (Claude)
 
 use anyhow::Result;
-use fgp_daemon::protocol::{error_codes, Request, Response};
-use fgp_daemon::service::{HealthStatus, MethodInfo, ParamInfo};
-use fgp_daemon::{FgpServer, FgpService};
+use fgp_daemon::protocol::{error_codes, NdjsonReader, Request, Response, ServiceError};
+use fgp_daemon::server::{AuditRecord, CacheConfig, LifecycleEventKind};
+use fgp_daemon::service::{
+    Capabilities, ConnectionState, HealthProbes, HealthState, HealthStatus, MethodInfo, ParamInfo,
+    RawJson, RequestContext, ResultChunks, StopReason,
+};
+use fgp_daemon::testing::check_latency_budget;
+use fgp_daemon::{
+    CompositeService, FgpClient, FgpClientBuilder, FgpServer, FgpServerBuilder, FgpService,
+    FgpTestHarness, FramingMode,
+};
 use serde_json::{json, Value};
-use std::collections::HashMap;
-use std::io::{BufRead, BufReader, Write};
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Read, Write};
 use std::os::unix::net::UnixStream;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
 use std::time::Duration;
 use tempfile::TempDir;
@@ -68,9 +77,23 @@ impl FgpService for TestService {
                     .ok_or_else(|| anyhow::anyhow!("Missing parameter: b"))?;
                 Ok(json!({ "sum": a + b }))
             }
+            "test.shout" => self.dispatch("test.echo", params),
             "test.error" | "error" => {
                 anyhow::bail!("Intentional error for testing");
             }
+            "test.panic" => panic!("boom"),
+            "test.params" => Ok(Value::Object(params.into_iter().collect())),
+            "test.unavailable" => Err(ServiceError::new(
+                error_codes::SERVICE_UNAVAILABLE,
+                "Upstream is down",
+            )
+            .with_details(json!({ "retry_after_ms": 50 }))
+            .into()),
+            "test.sync" => Err(ServiceError::new(
+                error_codes::SERVICE_UNAVAILABLE,
+                "Sync backend is down",
+            )
+            .into()),
             "test.slow" | "slow" => {
                 let ms = params.get("ms").and_then(|v| v.as_u64()).unwrap_or(100);
                 thread::sleep(Duration::from_millis(ms));
@@ -83,37 +106,100 @@ impl FgpService for TestService {
         }
     }
 
+    fn dispatch_with_context(
+        &self,
+        method: &str,
+        params: HashMap<String, Value>,
+        ctx: &RequestContext,
+    ) -> Result<Value> {
+        match method {
+            "test.trace" => Ok(json!({ "id": ctx.id, "trace_id": ctx.trace_id })),
+            _ => self.dispatch(method, params),
+        }
+    }
+
+    fn dispatch_raw(
+        &self,
+        method: &str,
+        params: HashMap<String, Value>,
+        ctx: &RequestContext,
+    ) -> Result<RawJson> {
+        match method {
+            "test.raw" => RawJson::from_string("{\n  \"upstream\": [1, 2]\n}".to_string()),
+            "test.raw_unsorted" => RawJson::from_string(
+                r#"{"b": 1, "a": {"d": [{"f": 4, "e": 5}], "c": 3}}"#.to_string(),
+            ),
+            _ => self
+                .dispatch_with_context(method, params, ctx)
+                .map(RawJson::from),
+        }
+    }
+
+    fn streams(&self, method: &str) -> bool {
+        method == "test.items"
+    }
+
+    fn dispatch_stream(
+        &self,
+        _method: &str,
+        params: HashMap<String, Value>,
+        _ctx: &RequestContext,
+    ) -> Result<ResultChunks> {
+        // `count` chunks of two items, then an error if `fail` is set
+        let count = params.get("count").and_then(|v| v.as_u64()).unwrap_or(3);
+        let fail = params
+            .get("fail")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        let chunks = (0..count).map(|i| Ok(vec![json!(i * 2), json!(i * 2 + 1)]));
+        let failure = fail.then(|| {
+            Err(ServiceError::new(error_codes::SERVICE_UNAVAILABLE, "Upstream is down").into())
+        });
+        Ok(Box::new(chunks.chain(failure)))
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default().with_feature("echo", json!(true))
+    }
+
+    fn error_codes(&self) -> Vec<&str> {
+        vec!["RATE_LIMITED"]
+    }
+
     fn method_list(&self) -> Vec<MethodInfo> {
         vec![
-            MethodInfo::new("test.echo", "Echo a message")
-                .param(ParamInfo {
-                    name: "message".into(),
-                    param_type: "string".into(),
-                    required: false,
-                    default: Some(json!("no message")),
-                }),
+            MethodInfo::new("test.echo", "Echo a message").param(ParamInfo {
+                name: "message".into(),
+                param_type: "string".into(),
+                required: false,
+                default: Some(json!("no message")),
+                ..Default::default()
+            }),
             MethodInfo::new("test.add", "Add two numbers")
                 .param(ParamInfo {
                     name: "a".into(),
                     param_type: "integer".into(),
                     required: true,
                     default: None,
+                    ..Default::default()
                 })
                 .param(ParamInfo {
                     name: "b".into(),
                     param_type: "integer".into(),
                     required: true,
                     default: None,
+                    ..Default::default()
                 }),
             MethodInfo::new("test.error", "Always returns an error"),
-            MethodInfo::new("test.slow", "Sleep for specified milliseconds")
-                .param(ParamInfo {
-                    name: "ms".into(),
-                    param_type: "integer".into(),
-                    required: false,
-                    default: Some(json!(100)),
-                }),
+            MethodInfo::new("test.slow", "Sleep for specified milliseconds").param(ParamInfo {
+                name: "ms".into(),
+                param_type: "integer".into(),
+                required: false,
+                default: Some(json!(100)),
+                ..Default::default()
+            }),
             MethodInfo::new("test.count", "Return total call count"),
+            MethodInfo::new("test.shout", "Echo a message (old name)").replaced_by("test.echo"),
         ]
     }
 
@@ -159,12 +245,9 @@ fn send_request(socket_path: &PathBuf, request: &Request) -> Result<Response> {
     writeln!(stream, "{}", request_json)?;
     stream.flush()?;
 
-    let mut reader = BufReader::new(stream);
-    let mut response_line = String::new();
-    reader.read_line(&mut response_line)?;
-
-    let response: Response = serde_json::from_str(&response_line)?;
-    Ok(response)
+    NdjsonReader::new(stream)
+        .read_response()?
+        .ok_or_else(|| anyhow::anyhow!("Connection closed without a response"))
 }
 
 /// Send raw JSON and get raw response.
@@ -203,6 +286,7 @@ fn test_health_check() {
         v: 1,
         method: "health".to_string(),
         params: HashMap::new(),
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -213,6 +297,602 @@ fn test_health_check() {
     let result = response.result.unwrap();
     assert_eq!(result["status"], "healthy");
     assert!(result["services"].is_object());
+    assert_eq!(result["sdk_version"], fgp_daemon::SDK_VERSION);
+    assert_eq!(result["protocol_version"], fgp_daemon::PROTOCOL_VERSION);
+}
+
+#[test]
+fn test_ping() {
+    let (socket_path, _handle) = start_test_server();
+
+    let client = FgpClient::new(&socket_path).unwrap();
+    let rtt = client.ping().unwrap();
+    assert!(rtt < Duration::from_secs(1));
+
+    let result = client.call_value("test.ping", json!({})).unwrap();
+    assert_eq!(result["pong"], true);
+    assert!(result["timestamp"].as_str().unwrap().ends_with('Z'));
+}
+
+#[test]
+fn test_socket_path_from_env() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("env.sock");
+
+    std::env::set_var("FGP_SOCKET", &socket_path);
+    let server = FgpServer::from_env(TestService::new()).unwrap();
+    assert_eq!(server.socket_path(), socket_path);
+    thread::spawn(move || server.serve());
+    thread::sleep(Duration::from_millis(100));
+    assert!(FgpClient::from_env().unwrap().health().unwrap().ok);
+
+    // Falls back to the standard path of FGP_SERVICE
+    std::env::set_var("FGP_SOCKET", "");
+    std::env::set_var("FGP_SERVICE", "env-test");
+    assert_eq!(
+        fgp_daemon::lifecycle::socket_path_from_env(),
+        Some(fgp_daemon::service_socket_path("env-test"))
+    );
+
+    std::env::remove_var("FGP_SERVICE");
+    assert!(FgpClient::from_env().is_err());
+    std::env::remove_var("FGP_SOCKET");
+}
+
+#[test]
+fn test_restart_count_in_health() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("restarts.sock");
+    let state_path = temp_dir.path().join("state.json");
+
+    // A previous run of the daemon
+    let first = fgp_daemon::lifecycle::record_start(&state_path).unwrap();
+
+    let server = FgpServerBuilder::new()
+        .state_file(&state_path)
+        .build(TestService::new(), &socket_path)
+        .unwrap();
+    thread::spawn(move || server.serve());
+    thread::sleep(Duration::from_millis(100));
+
+    let report = FgpClient::new(&socket_path)
+        .unwrap()
+        .health_report()
+        .unwrap();
+    assert_eq!(report.restart_count, Some(1));
+    assert_eq!(report.first_started_at, Some(first.first_started_at));
+}
+
+#[test]
+fn test_uptime_counts_from_serve() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("uptime.sock");
+
+    let server = FgpServer::new(TestService::new(), &socket_path).unwrap();
+    thread::sleep(Duration::from_millis(1100));
+    thread::spawn(move || server.serve());
+    thread::sleep(Duration::from_millis(100));
+
+    let report = FgpClient::new(&socket_path)
+        .unwrap()
+        .health_report()
+        .unwrap();
+    assert_eq!(report.uptime_seconds, 0);
+}
+
+#[test]
+fn test_health_report() {
+    let (socket_path, _handle) = start_test_server();
+
+    let client = FgpClient::new(&socket_path).unwrap();
+    let report = client.deep_health_report().unwrap();
+
+    assert_eq!(report.status, HealthState::Healthy);
+    assert_eq!(report.pid, std::process::id());
+    assert_eq!(report.version, "1.0.0");
+    assert_eq!(report.sdk_version, fgp_daemon::SDK_VERSION);
+    assert!(report.services["test_service"].ok);
+
+    // Liveness only: dependency checks are skipped
+    let report = client.health_report().unwrap();
+    assert_eq!(report.status, HealthState::Healthy);
+    assert!(report.services.is_empty());
+
+    let response = client.call("health", json!({"deep": "yes"})).unwrap();
+    assert_eq!(response.error.unwrap().code, error_codes::INVALID_PARAMS);
+}
+
+#[test]
+fn test_capabilities() {
+    let (socket_path, _handle) = start_test_server();
+
+    let capabilities = FgpClient::new(&socket_path)
+        .unwrap()
+        .capabilities()
+        .unwrap();
+
+    assert!(capabilities.batching);
+    assert!(!capabilities.streaming);
+    assert_eq!(
+        capabilities.max_request_bytes,
+        Some(fgp_daemon::protocol::MAX_LINE_BYTES)
+    );
+    assert_eq!(capabilities.features["echo"], json!(true));
+}
+
+#[test]
+fn test_declared_error_codes() {
+    let (socket_path, _handle) = start_test_server();
+    let client = FgpClient::new(&socket_path).unwrap();
+
+    for method in ["methods", "schema"] {
+        let codes = client.call(method, json!({})).unwrap().result.unwrap()["error_codes"].clone();
+        let codes: Vec<String> = serde_json::from_value(codes).unwrap();
+        assert!(codes.contains(&error_codes::TIMEOUT.to_string()));
+        assert_eq!(codes.last().map(String::as_str), Some("RATE_LIMITED"));
+    }
+}
+
+#[test]
+fn test_unknown_method_suggestion() {
+    let (socket_path, _handle) = start_test_server();
+    let client = FgpClient::new(&socket_path).unwrap();
+
+    let error = client.call("test.ecoh", json!({})).unwrap().error.unwrap();
+    assert_eq!(error.details, Some(json!({ "did_you_mean": "test.echo" })));
+
+    // Nothing close enough
+    let error = client
+        .call("test.frobnicate", json!({}))
+        .unwrap()
+        .error
+        .unwrap();
+    assert!(error.details.is_none());
+
+    // Only unknown-method errors get a suggestion, not other errors from unlisted methods
+    let error = client.call("test.sync", json!({})).unwrap().error.unwrap();
+    assert_eq!(error.code, error_codes::SERVICE_UNAVAILABLE);
+    assert!(error.details.is_none());
+}
+
+#[test]
+fn test_response_meta_sizes() {
+    let (socket_path, _handle) = start_test_server();
+
+    let mut stream = UnixStream::connect(&socket_path).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    for size in [10, 1000, 100_000] {
+        let request = Request::new(
+            "test.echo",
+            HashMap::from([("message".to_string(), json!("x".repeat(size)))]),
+        );
+        let request_line = serde_json::to_string(&request).unwrap();
+        writeln!(stream, "{}", request_line).unwrap();
+
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let line = line.trim_end();
+        let response: Response = serde_json::from_str(line).unwrap();
+        assert_eq!(response.meta.req_bytes, Some(request_line.len() as u64));
+        assert_eq!(response.meta.resp_bytes, Some(line.len() as u64));
+    }
+}
+
+#[test]
+fn test_client_builder() {
+    let (socket_path, _handle) = start_test_server();
+
+    let client = FgpClientBuilder::new()
+        .timeout(Duration::from_secs(5))
+        .persistent_resilient()
+        .response_id_check(true)
+        .build(&socket_path)
+        .unwrap();
+    for _ in 0..3 {
+        assert!(client.call_idempotent("test.echo", json!({})).unwrap().ok);
+    }
+
+    // Invalid combinations fail at build time
+    let err = FgpClientBuilder::new()
+        .timeout(Duration::ZERO)
+        .build(&socket_path)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("timeout"));
+    assert!(FgpClientBuilder::new()
+        .auto_start("test")
+        .no_auto_start()
+        .build(&socket_path)
+        .is_err());
+}
+
+#[test]
+fn test_dispatch_raw() {
+    let (socket_path, _handle) = start_test_server();
+
+    let mut stream = UnixStream::connect(&socket_path).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    writeln!(
+        stream,
+        "{}",
+        serde_json::to_string(&Request::simple("test.raw")).unwrap()
+    )
+    .unwrap();
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    // Written as is, apart from the line breaks
+    assert!(line.contains(r#""result":{   "upstream": [1, 2] }"#));
+    let response: Response = serde_json::from_str(&line).unwrap();
+    assert_eq!(response.result, Some(json!({ "upstream": [1, 2] })));
+    assert_eq!(response.meta.resp_bytes, Some(line.trim_end().len() as u64));
+
+    // Parsed where a Value is needed
+    let client = FgpClient::new(&socket_path).unwrap();
+    let bundle = client
+        .call_value("bundle", json!({ "calls": [{ "method": "test.raw" }] }))
+        .unwrap();
+    assert_eq!(
+        bundle["results"][0]["result"],
+        json!({ "upstream": [1, 2] })
+    );
+    let harness = FgpTestHarness::new(TestService::new());
+    assert_eq!(
+        harness.call("test.raw", json!({})).result,
+        Some(json!({ "upstream": [1, 2] }))
+    );
+
+    assert!(RawJson::from_string("{not json".to_string()).is_err());
+}
+
+#[test]
+fn test_sorted_output() {
+    let read_line = |socket_path: &PathBuf| {
+        let mut stream = UnixStream::connect(socket_path).unwrap();
+        let request = Request::simple("test.raw_unsorted");
+        writeln!(stream, "{}", serde_json::to_string(&request).unwrap()).unwrap();
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        line
+    };
+
+    // Off by default: the raw result is written as is
+    let (socket_path, _handle) = start_test_server();
+    assert!(read_line(&socket_path).contains(r#""result":{"b": 1, "a""#));
+
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("sorted.sock");
+    let server = FgpServerBuilder::new()
+        .sorted_output(true)
+        .build(TestService::new(), &socket_path)
+        .unwrap();
+    thread::spawn(move || server.serve());
+    thread::sleep(Duration::from_millis(100));
+
+    let line = read_line(&socket_path);
+    assert!(line.contains(r#""result":{"a":{"c":3,"d":[{"e":5,"f":4}]},"b":1}"#));
+    let response: Response = serde_json::from_str(&line).unwrap();
+    assert_eq!(response.meta.resp_bytes, Some(line.trim_end().len() as u64));
+}
+
+#[test]
+fn test_client_call_value() {
+    let (socket_path, _handle) = start_test_server();
+    let client = FgpClient::new(&socket_path).unwrap();
+
+    let result = client
+        .call_value("test.add", json!({"a": 2, "b": 3}))
+        .unwrap();
+    assert_eq!(result, json!({"sum": 5}));
+
+    let err = client
+        .call_value("test.unavailable", Value::Null)
+        .unwrap_err();
+    assert_eq!(err.to_string(), "SERVICE_UNAVAILABLE: Upstream is down");
+    let error = err.downcast_ref::<ServiceError>().unwrap();
+    assert_eq!(error.details, Some(json!({ "retry_after_ms": 50 })));
+}
+
+#[test]
+fn test_call_collect_stream() {
+    let (socket_path, _handle) = start_test_server();
+
+    // Each chunk is its own partial frame, ended by an unflagged one
+    let mut stream = UnixStream::connect(&socket_path).unwrap();
+    let request = Request::new("test.items", HashMap::new());
+    writeln!(stream, "{}", serde_json::to_string(&request).unwrap()).unwrap();
+    let frames: Vec<Response> = BufReader::new(&stream)
+        .lines()
+        .take(4)
+        .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+        .collect();
+    let partial: Vec<bool> = frames.iter().map(|frame| frame.meta.partial).collect();
+    assert_eq!(partial, vec![true, true, true, false]);
+    assert!(frames.iter().all(|frame| frame.id == request.id));
+    assert_eq!(frames[1].result, Some(json!([2, 3])));
+    assert_eq!(frames[3].result, Some(json!([])));
+
+    let client = FgpClient::new(&socket_path).unwrap();
+    let items: Vec<u32> = client.call_collect_stream("test.items", json!({})).unwrap();
+    assert_eq!(items, vec![0, 1, 2, 3, 4, 5]);
+
+    // A single unstreamed frame
+    let err = client
+        .call_collect_stream::<u32>("test.echo", json!({}))
+        .unwrap_err();
+    assert!(err.to_string().contains("did not stream"), "{}", err);
+
+    // An error frame ends the stream
+    let err = client
+        .call_collect_stream::<u32>("test.items", json!({"count": 1, "fail": true}))
+        .unwrap_err();
+    let error = err.downcast_ref::<ServiceError>().unwrap();
+    assert_eq!(error.code, error_codes::SERVICE_UNAVAILABLE);
+
+    // The persistent connection is usable after a stream
+    let client = FgpClient::new(&socket_path).unwrap().persistent();
+    let items: Vec<u32> = client
+        .call_collect_stream("test.items", json!({"count": 1}))
+        .unwrap();
+    assert_eq!(items, vec![0, 1]);
+    assert!(client.call("test.echo", json!({})).unwrap().ok);
+
+    // Bundles and the test harness collect the chunks into one array
+    let bundle = client
+        .call_value("bundle", json!({"calls": [{"method": "test.items"}]}))
+        .unwrap();
+    assert_eq!(bundle["results"][0]["result"], json!([0, 1, 2, 3, 4, 5]));
+    let harness = FgpTestHarness::new(TestService::new());
+    let response = harness.call("test.items", json!({"count": 2}));
+    assert_eq!(response.result, Some(json!([0, 1, 2, 3])));
+}
+
+#[test]
+fn test_client_rejects_malformed_requests_locally() {
+    // No daemon listens here: the request must fail before connecting
+    let client = FgpClient::new("/nonexistent/fgp.sock").unwrap();
+    let err = client.call("", Value::Null).unwrap_err();
+    let error = err.downcast_ref::<ServiceError>().unwrap();
+    assert_eq!(error.code, error_codes::INVALID_REQUEST);
+
+    let mut request = Request::simple("health");
+    request.v = 0;
+    assert!(client.pipeline(vec![request]).is_err());
+}
+
+#[test]
+fn test_reconnect_once_after_reset() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("flaky.sock");
+    let listener = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+    let accepted = Arc::new(AtomicU32::new(0));
+    let counter = Arc::clone(&accepted);
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = stream.unwrap();
+            // Every other connection is closed with the request unread, which resets it
+            if counter.fetch_add(1, Ordering::SeqCst).is_multiple_of(2) {
+                thread::sleep(Duration::from_millis(50));
+                drop(stream);
+                continue;
+            }
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let request: Request = serde_json::from_str(&line).unwrap();
+            let response = Response::success(&request.id, json!({"pong": true}), 0.1);
+            writeln!(&stream, "{}", serde_json::to_string(&response).unwrap()).unwrap();
+        }
+    });
+
+    let client = FgpClient::new(&socket_path).unwrap();
+    assert!(client.call("ping", Value::Null).unwrap().ok);
+    assert_eq!(accepted.load(Ordering::SeqCst), 2);
+
+    let client = FgpClientBuilder::new()
+        .reconnect_once(false)
+        .build(&socket_path)
+        .unwrap();
+    assert!(client.call("ping", Value::Null).is_err());
+    assert_eq!(accepted.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_connect_timeout() {
+    use socket2::{Domain, SockAddr, Socket, Type};
+
+    // A listener that never accepts, with its backlog already full
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("stalled.sock");
+    let listener = Socket::new(Domain::UNIX, Type::STREAM, None).unwrap();
+    listener
+        .bind(&SockAddr::unix(&socket_path).unwrap())
+        .unwrap();
+    listener.listen(0).unwrap();
+    let _pending = UnixStream::connect(&socket_path).unwrap();
+
+    let client = FgpClient::new(&socket_path)
+        .unwrap()
+        .with_connect_timeout(Duration::from_millis(200));
+    let start = std::time::Instant::now();
+    let err = client.call("health", Value::Null).unwrap_err();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    let error = err.downcast_ref::<ServiceError>().unwrap();
+    assert_eq!(error.code, error_codes::TIMEOUT);
+
+    assert!(FgpClientBuilder::new()
+        .connect_timeout(Duration::ZERO)
+        .build(&socket_path)
+        .is_err());
+
+    // Connecting to a daemon that accepts is unaffected
+    let (socket_path, _handle) = start_test_server();
+    let client = FgpClientBuilder::new()
+        .connect_timeout(Duration::from_secs(1))
+        .build(&socket_path)
+        .unwrap();
+    assert!(client.call("health", Value::Null).unwrap().ok);
+}
+
+#[test]
+fn test_latency_budget() {
+    let (socket_path, _handle) = start_test_server();
+    let client = FgpClient::new(&socket_path).unwrap();
+
+    let (response, elapsed) = client.timed_call("health", Value::Null).unwrap();
+    assert!(response.ok);
+    assert!(elapsed.as_secs_f64() * 1000.0 >= response.meta.server_ms);
+
+    let median = check_latency_budget(&client, Duration::from_secs(1)).unwrap();
+    assert!(median < Duration::from_secs(1));
+    let err = check_latency_budget(&client, Duration::ZERO).unwrap_err();
+    assert!(err.to_string().contains("over the 0ns budget"));
+}
+
+#[test]
+fn test_client_params_shapes() {
+    let (socket_path, _handle) = start_test_server();
+    let client = FgpClient::new(&socket_path).unwrap();
+
+    let params = |response: Response| response.result.unwrap();
+    assert_eq!(
+        params(client.call("test.params", json!({"a": 1})).unwrap()),
+        json!({"a": 1})
+    );
+    assert_eq!(
+        params(client.call("test.params", Value::Null).unwrap()),
+        json!({})
+    );
+    assert_eq!(
+        params(client.call("test.params", json!([1, 2])).unwrap()),
+        json!({"value": [1, 2]})
+    );
+    assert_eq!(
+        params(
+            client
+                .call_positional("test.params", vec![json!(1), json!("two")])
+                .unwrap()
+        ),
+        json!({"args": [1, "two"]})
+    );
+}
+
+#[test]
+fn test_client_pipeline() {
+    let (socket_path, _handle) = start_test_server();
+
+    let requests: Vec<Request> = (0..200)
+        .map(|i| {
+            let params = HashMap::from([("a".to_string(), json!(i)), ("b".to_string(), json!(1))]);
+            Request::new("test.add", params)
+        })
+        .collect();
+    let ids: Vec<String> = requests.iter().map(|r| r.id.clone()).collect();
+
+    for client in [
+        FgpClient::new(&socket_path).unwrap(),
+        FgpClient::new(&socket_path).unwrap().persistent(),
+    ] {
+        let responses = client.pipeline(requests.clone()).unwrap();
+        assert_eq!(responses.len(), requests.len());
+        for (i, response) in responses.iter().enumerate() {
+            assert_eq!(response.id, ids[i]);
+            assert_eq!(response.result.as_ref().unwrap()["sum"], i as i64 + 1);
+        }
+    }
+
+    let duplicate = vec![requests[0].clone(), requests[0].clone()];
+    assert!(FgpClient::new(&socket_path)
+        .unwrap()
+        .pipeline(duplicate)
+        .is_err());
+}
+
+#[test]
+fn test_health_metrics() {
+    let (socket_path, _handle) = start_test_server();
+    let client = FgpClient::new(&socket_path).unwrap();
+
+    let idle = UnixStream::connect(&socket_path).unwrap();
+    let metrics = client.health_report().unwrap().metrics;
+    assert_eq!(metrics.connections_open, 2);
+    assert!(metrics.connections_total >= 2);
+    // The health request itself
+    assert_eq!(metrics.active_requests, 1);
+
+    drop(idle);
+    let mut open = usize::MAX;
+    for _ in 0..50 {
+        open = client.health_report().unwrap().metrics.connections_open;
+        if open == 1 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(open, 1);
+    // The only open connection is the one being served
+    let metrics = client.health_report().unwrap().metrics;
+    assert_eq!(metrics.queued_connections, 0);
+}
+
+#[test]
+fn test_connection_pool_queues_connections() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("pool.sock");
+    let server = FgpServerBuilder::new()
+        .connection_threads(1)
+        .build(TestService::new(), &socket_path)
+        .unwrap();
+    thread::spawn(move || server.serve());
+    thread::sleep(Duration::from_millis(100));
+
+    // Health metrics read over an already-open connection
+    let health = serde_json::to_string(&Request::simple("health")).unwrap();
+    let metrics = |mut stream: &UnixStream| {
+        writeln!(stream, "{}", health).unwrap();
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        serde_json::from_str::<Value>(&line).unwrap()["result"]["metrics"].clone()
+    };
+
+    // A persistent connection holds the only pool thread
+    let holding = UnixStream::connect(&socket_path).unwrap();
+    assert_eq!(metrics(&holding)["queued_connections"], 0);
+
+    // So the next connection waits for it
+    let waiting = UnixStream::connect(&socket_path).unwrap();
+    thread::sleep(Duration::from_millis(50));
+    let held = metrics(&holding);
+    assert_eq!(held["queued_connections"], 1);
+    assert_eq!(held["connections_open"], 2);
+
+    // And is served once the thread is free
+    drop(holding);
+    assert_eq!(metrics(&waiting)["queued_connections"], 0);
+}
+
+#[test]
+fn test_health_probes_timeout_frees_worker() {
+    let start = std::time::Instant::now();
+    let statuses = HealthProbes::new()
+        .threads(1)
+        .timeout(Duration::from_secs(5))
+        .probe_with_timeout("hung", Duration::from_millis(50), || {
+            thread::sleep(Duration::from_secs(10));
+            Ok(())
+        })
+        .probe("fast", || Ok(()))
+        .probe("panics", || panic!("boom"))
+        .run();
+
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(statuses.len(), 3);
+    assert_eq!(statuses["hung"].message.as_deref(), Some("timeout"));
+    assert!(statuses["fast"].ok);
+    assert!(statuses["fast"].latency_ms.is_some());
+    assert_eq!(
+        statuses["panics"].message.as_deref(),
+        Some("probe panicked")
+    );
+    assert!(HealthProbes::new().run().is_empty());
 }
 
 #[test]
@@ -224,6 +904,7 @@ fn test_methods_list() {
         v: 1,
         method: "methods".to_string(),
         params: HashMap::new(),
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -243,6 +924,51 @@ fn test_methods_list() {
     assert!(method_names.contains(&"test.add"));
 }
 
+#[test]
+fn test_methods_and_schema_pagination() {
+    let harness = FgpTestHarness::new(TestService::new());
+    let names = |methods: &Value| -> Vec<String> {
+        methods
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    // Unpaginated by default
+    let all = harness.call("methods", json!({})).result.unwrap();
+    assert!(all.get("total").is_none());
+    let all_names = names(&all["methods"]);
+
+    let page = harness
+        .call("methods", json!({"limit": 2, "offset": 1}))
+        .result
+        .unwrap();
+    assert_eq!(page["total"], json!(all_names.len()));
+    assert_eq!(names(&page["methods"]), all_names[1..3]);
+
+    let schema = harness.call("schema", json!({})).result.unwrap();
+    let schema_names = names(&schema["methods"]);
+    let page = harness
+        .call("schema", json!({"offset": schema_names.len() - 1}))
+        .result
+        .unwrap();
+    assert_eq!(page["total"], json!(schema_names.len()));
+    assert_eq!(
+        names(&page["methods"]),
+        schema_names[schema_names.len() - 1..]
+    );
+
+    // Past the end: an empty page
+    let page = harness
+        .call("schema", json!({"offset": 1000, "format": "openai"}))
+        .result
+        .unwrap();
+    assert_eq!(page["functions"], json!([]));
+    assert_eq!(page["total"], json!(schema_names.len()));
+}
+
 // ============================================================================
 // Service Method Tests
 // ============================================================================
@@ -259,6 +985,7 @@ fn test_echo_method() {
         v: 1,
         method: "test.echo".to_string(),
         params,
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -280,6 +1007,7 @@ fn test_add_method() {
         v: 1,
         method: "test.add".to_string(),
         params,
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -300,6 +1028,7 @@ fn test_method_without_prefix() {
         v: 1,
         method: "echo".to_string(), // Without "test." prefix
         params,
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -322,6 +1051,7 @@ fn test_unknown_method_error() {
         v: 1,
         method: "nonexistent".to_string(),
         params: HashMap::new(),
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -344,6 +1074,7 @@ fn test_wrong_service_namespace_error() {
         v: 1,
         method: "other.method".to_string(),
         params: HashMap::new(),
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -362,6 +1093,7 @@ fn test_service_error() {
         v: 1,
         method: "test.error".to_string(),
         params: HashMap::new(),
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -373,164 +1105,1519 @@ fn test_service_error() {
 }
 
 #[test]
-fn test_missing_required_param() {
+fn test_dispatch_panic_keeps_connection() {
     let (socket_path, _handle) = start_test_server();
+    let client = FgpClient::new(&socket_path).unwrap().persistent();
 
-    // test.add requires 'a' and 'b' params
-    let mut params = HashMap::new();
-    params.insert("a".to_string(), json!(10));
-    // Missing 'b'
-
-    let request = Request {
-        id: "missing-param-1".to_string(),
-        v: 1,
-        method: "test.add".to_string(),
-        params,
-    };
-
-    let response = send_request(&socket_path, &request).unwrap();
-
+    let response = client.call("test.panic", json!({})).unwrap();
     assert!(!response.ok);
     let error = response.error.unwrap();
     assert_eq!(error.code, error_codes::INTERNAL_ERROR);
-    assert!(error.message.contains("b"));
+    assert_eq!(error.details, Some(json!({ "panic": "boom" })));
+
+    // Same connection, still served
+    let response = client
+        .call("test.echo", json!({"message": "after"}))
+        .unwrap();
+    assert_eq!(response.result.unwrap()["echo"], "after");
+    let metrics = client.health_report().unwrap().metrics;
+    assert_eq!(metrics.connections_total, 1);
 }
 
-#[test]
-fn test_invalid_json_request() {
-    let (socket_path, _handle) = start_test_server();
+/// Renamed from `gmail`, still accepting the old namespace.
+struct RenamedService;
 
-    let response_str = send_raw(&socket_path, "not valid json").unwrap();
-    let response: Response = serde_json::from_str(&response_str).unwrap();
+impl FgpService for RenamedService {
+    fn name(&self) -> &str {
+        "mail"
+    }
 
-    assert!(!response.ok);
-    let error = response.error.unwrap();
-    assert_eq!(error.code, error_codes::INVALID_REQUEST);
-}
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
 
-// ============================================================================
-// Response Metadata Tests
-// ============================================================================
+    fn namespaces(&self) -> Vec<&str> {
+        vec!["mail", "gmail"]
+    }
+
+    fn dispatch(&self, method: &str, _params: HashMap<String, Value>) -> Result<Value> {
+        Ok(json!({ "dispatched": method }))
+    }
+}
 
 #[test]
-fn test_response_has_server_ms() {
-    let (socket_path, _handle) = start_test_server();
+fn test_namespace_aliases() {
+    let harness = FgpTestHarness::new(RenamedService);
 
-    let request = Request {
-        id: "meta-1".to_string(),
-        v: 1,
-        method: "health".to_string(),
-        params: HashMap::new(),
-    };
+    for method in ["list", "mail.list", "gmail.list"] {
+        let response = harness.call(method, json!({}));
+        assert_eq!(response.result.unwrap()["dispatched"], "mail.list");
+    }
+    assert!(harness.call("gmail.health", json!({})).ok);
 
-    let response = send_request(&socket_path, &request).unwrap();
+    let response = harness.call("gmailx.list", json!({}));
+    assert_eq!(response.error.unwrap().code, error_codes::INVALID_REQUEST);
 
-    assert!(response.meta.server_ms >= 0.0);
-    assert_eq!(response.meta.protocol_v, 1);
+    let response = harness.call("bundle", json!({"calls": [{"method": "gmail.list"}]}));
+    assert_eq!(
+        response.result.unwrap()["results"][0]["result"]["dispatched"],
+        "mail.list"
+    );
 }
 
-#[test]
+/// A service whose generated method list names `dup.list` twice.
+struct DuplicateMethodsService;
+
+impl FgpService for DuplicateMethodsService {
+    fn name(&self) -> &str {
+        "dup"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn dispatch(&self, method: &str, _params: HashMap<String, Value>) -> Result<Value> {
+        Ok(json!({ "dispatched": method }))
+    }
+
+    fn method_list(&self) -> Vec<MethodInfo> {
+        vec![
+            MethodInfo::new("list", "List items"),
+            MethodInfo::new("get", "Get an item"),
+            MethodInfo::new("dup.list", "List items again"),
+        ]
+    }
+}
+
+#[test]
+fn test_duplicate_methods_are_dropped() {
+    let harness = FgpTestHarness::new(DuplicateMethodsService);
+
+    let methods = harness.call("methods", json!({})).result.unwrap();
+    let listed: Vec<&Value> = methods["methods"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|m| m["name"] == "dup.list")
+        .collect();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["description"], "List items");
+
+    let schema = harness
+        .call("schema", json!({"format": "openai"}))
+        .result
+        .unwrap();
+    assert_eq!(schema["functions"].as_array().unwrap().len(), 2);
+}
+
+/// A service whose `login` sets a user for the rest of the connection.
+struct SessionService;
+
+#[derive(Default)]
+struct Session {
+    user: Mutex<Option<String>>,
+}
+
+impl FgpService for SessionService {
+    fn name(&self) -> &str {
+        "session"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn new_connection_state(&self) -> Option<ConnectionState> {
+        Some(Arc::new(Session::default()))
+    }
+
+    fn dispatch(&self, _method: &str, _params: HashMap<String, Value>) -> Result<Value> {
+        anyhow::bail!("Requires a connection")
+    }
+
+    fn dispatch_with_context(
+        &self,
+        method: &str,
+        params: HashMap<String, Value>,
+        ctx: &RequestContext,
+    ) -> Result<Value> {
+        let session = ctx.state::<Session>().unwrap();
+        let mut user = session.user.lock().unwrap();
+        match method {
+            "session.login" => *user = params["user"].as_str().map(String::from),
+            "session.whoami" => {}
+            _ => anyhow::bail!("Unknown method: {}", method),
+        }
+        Ok(json!({ "user": *user }))
+    }
+}
+
+#[test]
+fn test_connection_state() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("session.sock");
+    let server = FgpServer::new(SessionService, &socket_path).unwrap();
+    thread::spawn(move || server.serve());
+    thread::sleep(Duration::from_millis(100));
+
+    let alice = FgpClient::new(&socket_path).unwrap().persistent();
+    alice
+        .call_value("session.login", json!({"user": "alice"}))
+        .unwrap();
+    assert_eq!(
+        alice.call_value("session.whoami", json!({})).unwrap(),
+        json!({"user": "alice"})
+    );
+
+    // Other connections have their own state
+    let other = FgpClient::new(&socket_path).unwrap().persistent();
+    assert_eq!(
+        other.call_value("session.whoami", json!({})).unwrap(),
+        json!({"user": null})
+    );
+
+    // Bundled calls share the connection's state
+    let bundle = alice
+        .call_value("bundle", json!({"calls": [{"method": "session.whoami"}]}))
+        .unwrap();
+    assert_eq!(bundle["results"][0]["result"], json!({"user": "alice"}));
+
+    // The harness acts as one connection, also inside a composite
+    let harness =
+        FgpTestHarness::new(CompositeService::new("app", "1.0.0").with_service(SessionService));
+    harness.call("app.session.login", json!({"user": "bob"}));
+    assert_eq!(
+        harness.call("app.session.whoami", json!({})).result,
+        Some(json!({"user": "bob"}))
+    );
+}
+
+/// A service that records why it was stopped.
+struct StopReasonService {
+    reasons: Arc<Mutex<Vec<StopReason>>>,
+}
+
+impl FgpService for StopReasonService {
+    fn name(&self) -> &str {
+        "stopping"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn dispatch(&self, _method: &str, _params: HashMap<String, Value>) -> Result<Value> {
+        Ok(Value::Null)
+    }
+
+    fn on_stop_with_reason(&self, reason: StopReason) -> Result<()> {
+        self.reasons.lock().unwrap().push(reason);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_stop_reason() {
+    let reasons = Arc::new(Mutex::new(Vec::new()));
+    let service = || StopReasonService {
+        reasons: Arc::clone(&reasons),
+    };
+
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("stopping.sock");
+    let server = FgpServer::new(service(), &socket_path).unwrap();
+    let serving = thread::spawn(move || server.serve());
+    thread::sleep(Duration::from_millis(100));
+    FgpClient::new(&socket_path).unwrap().stop().unwrap();
+    serving.join().unwrap().unwrap();
+    assert_eq!(*reasons.lock().unwrap(), vec![StopReason::MethodRequest]);
+
+    // Startup fails after `on_start` has run: the PID file path is a directory
+    let failing_socket = temp_dir.path().join("failing.sock");
+    let server = FgpServer::new(service(), &failing_socket)
+        .unwrap()
+        .with_pid_file(temp_dir.path())
+        .unwrap();
+    assert!(server.serve().is_err());
+    assert_eq!(
+        *reasons.lock().unwrap(),
+        vec![StopReason::MethodRequest, StopReason::Error]
+    );
+    assert!(!failing_socket.exists());
+
+    // Or the ready file can't be written, after the PID file was
+    let not_a_dir = temp_dir.path().join("not-a-dir");
+    std::fs::write(&not_a_dir, "").unwrap();
+    let pid_file = temp_dir.path().join("failing.pid");
+    let server = FgpServer::new(service(), &failing_socket)
+        .unwrap()
+        .with_pid_file(&pid_file)
+        .unwrap()
+        .with_ready_file(not_a_dir.join("daemon.ready"))
+        .unwrap();
+    assert!(server.serve().is_err());
+    assert!(!failing_socket.exists());
+    assert!(!pid_file.exists());
+}
+
+/// A service that speaks protocol versions 2 and 3.
+struct VersionedService;
+
+impl FgpService for VersionedService {
+    fn name(&self) -> &str {
+        "versioned"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn supported_versions(&self) -> Vec<u8> {
+        vec![2, 3]
+    }
+
+    fn dispatch(&self, method: &str, _params: HashMap<String, Value>) -> Result<Value> {
+        Ok(json!({ "dispatched": method }))
+    }
+}
+
+#[test]
+fn test_protocol_version_negotiation() {
+    let send = |harness: &FgpTestHarness<_>, v| {
+        let mut request = Request::simple("versioned.list");
+        request.v = v;
+        harness.send(request)
+    };
+    let harness = FgpTestHarness::new(VersionedService);
+
+    let response = send(&harness, 2);
+    assert!(response.ok);
+    assert_eq!(response.meta.protocol_v, 2);
+
+    // Newer clients get the highest version the daemon speaks
+    let response = send(&harness, 7);
+    assert!(response.ok);
+    assert_eq!(response.meta.protocol_v, 3);
+
+    let response = send(&harness, 1);
+    assert!(!response.ok);
+    let error = response.error.unwrap();
+    assert_eq!(error.code, error_codes::INVALID_REQUEST);
+    assert_eq!(
+        error.message,
+        "Unsupported protocol version: 1 (supported: 2, 3)"
+    );
+
+    // The default is v1 only
+    let harness = FgpTestHarness::new(TestService::new());
+    let mut request = Request::simple("health");
+    request.v = 2;
+    let response = harness.send(request);
+    assert!(response.ok);
+    assert_eq!(response.meta.protocol_v, 1);
+}
+
+#[test]
+fn test_deprecated_method_warning() {
+    let harness = FgpTestHarness::new(TestService::new());
+
+    let response = harness.call("test.shout", json!({"message": "hi"}));
+    assert!(response.ok);
+    assert_eq!(response.result.unwrap()["echo"], "hi");
+    assert_eq!(
+        response.meta.warnings,
+        vec!["Method 'test.shout' is deprecated, use 'test.echo'"]
+    );
+
+    let response = harness.call("test.echo", json!({}));
+    assert!(response.meta.warnings.is_empty());
+}
+
+#[test]
+fn test_typed_service_error() {
+    let harness = FgpTestHarness::new(TestService::new());
+
+    let response = harness.call("test.unavailable", Value::Null);
+    assert!(!response.ok);
+    let error = response.error.unwrap();
+    assert_eq!(error.code, error_codes::SERVICE_UNAVAILABLE);
+    assert_eq!(error.message, "Upstream is down");
+    assert_eq!(error.details, Some(json!({ "retry_after_ms": 50 })));
+
+    let response = harness.call(
+        "bundle",
+        json!({ "calls": [{ "method": "test.unavailable" }] }),
+    );
+    assert_eq!(
+        response.result.unwrap()["results"][0]["error"]["code"],
+        error_codes::SERVICE_UNAVAILABLE
+    );
+}
+
+#[test]
+fn test_missing_required_param() {
+    let (socket_path, _handle) = start_test_server();
+
+    // test.add requires 'a' and 'b' params
+    let mut params = HashMap::new();
+    params.insert("a".to_string(), json!(10));
+    // Missing 'b'
+
+    let request = Request {
+        id: "missing-param-1".to_string(),
+        v: 1,
+        method: "test.add".to_string(),
+        params,
+        ..Default::default()
+    };
+
+    let response = send_request(&socket_path, &request).unwrap();
+
+    assert!(!response.ok);
+    let error = response.error.unwrap();
+    assert_eq!(error.code, error_codes::INTERNAL_ERROR);
+    assert!(error.message.contains("b"));
+}
+
+#[test]
+fn test_invalid_json_request() {
+    let (socket_path, _handle) = start_test_server();
+
+    let response_str = send_raw(&socket_path, "not valid json").unwrap();
+    let response: Response = serde_json::from_str(&response_str).unwrap();
+
+    assert!(!response.ok);
+    let error = response.error.unwrap();
+    assert_eq!(error.code, error_codes::INVALID_REQUEST);
+}
+
+#[test]
+fn test_invalid_request_keeps_id() {
+    let (socket_path, _handle) = start_test_server();
+
+    let cases = [
+        (
+            r#"{"id":"missing-method","v":1,"params":{}}"#,
+            "missing-method",
+        ),
+        (r#"{"id":"bad-v","v":"one","method":"health"}"#, "bad-v"),
+        (r#"{"id":42,"v":1,"method":"health"}"#, "42"),
+        (r#"{"id":{"nested":true},"v":1}"#, "null"),
+        (r#"{"id":"truncated","#, "null"),
+    ];
+
+    for (line, expected_id) in cases {
+        let response_str = send_raw(&socket_path, line).unwrap();
+        let response: Response = serde_json::from_str(&response_str).unwrap();
+
+        assert!(!response.ok, "{}", line);
+        assert_eq!(response.id, expected_id, "{}", line);
+        assert_eq!(response.error.unwrap().code, error_codes::INVALID_REQUEST);
+    }
+}
+
+#[test]
+fn test_crlf_terminated_request() {
+    let (socket_path, _handle) = start_test_server();
+
+    let response_str = send_raw(
+        &socket_path,
+        "{\"id\":\"crlf\",\"v\":1,\"method\":\"test.echo\",\"params\":{\"message\":\"hi\"}}\r",
+    )
+    .unwrap();
+    let response = Response::from_ndjson_line(&response_str).unwrap();
+
+    assert!(response.ok);
+    assert_eq!(response.id, "crlf");
+    assert_eq!(response.result.unwrap()["echo"], "hi");
+}
+
+#[test]
+fn test_invalid_utf8_line_keeps_connection() {
+    let (socket_path, _handle) = start_test_server();
+
+    let mut stream = UnixStream::connect(&socket_path).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    stream.write_all(b"\xff\xfe\n").unwrap();
+    let health = Request::new("health", HashMap::new());
+    stream
+        .write_all(health.to_ndjson_line().unwrap().as_bytes())
+        .unwrap();
+
+    let mut reader = NdjsonReader::new(stream);
+    let error = reader.read_response().unwrap().unwrap();
+    assert_eq!(error.error.unwrap().code, error_codes::INVALID_REQUEST);
+    let response = reader.read_response().unwrap().unwrap();
+    assert_eq!(response.id, health.id);
+    assert!(response.ok);
+}
+
+#[test]
+fn test_deadline_already_passed() {
+    let (socket_path, _handle) = start_test_server();
+
+    let request = Request::simple("test.echo").with_deadline_ms(1);
+
+    let response = send_request(&socket_path, &request).unwrap();
+
+    assert!(!response.ok);
+    let error = response.error.unwrap();
+    assert_eq!(error.code, error_codes::TIMEOUT);
+    // The deadline is reported, not a made-up `timeout_ms`
+    assert_eq!(
+        error.details,
+        Some(json!({"deadline_ms": 1, "retriable": true}))
+    );
+
+    let request = request.with_timeout(Duration::from_secs(5));
+    let error = send_request(&socket_path, &request).unwrap().error.unwrap();
+    assert_eq!(error.retry_hint().unwrap().timeout_ms, None);
+
+    let request = Request::simple("test.echo").with_timeout(Duration::ZERO);
+    let error = send_request(&socket_path, &request).unwrap().error.unwrap();
+    assert_eq!(error.retry_hint().unwrap().timeout_ms, Some(0));
+}
+
+#[test]
+fn test_timeout_ms_exceeded() {
+    let (socket_path, _handle) = start_test_server();
+
+    let mut params = HashMap::new();
+    params.insert("ms".to_string(), json!(500));
+
+    let request = Request::new("test.slow", params).with_timeout(Duration::from_millis(50));
+
+    let response = send_request(&socket_path, &request).unwrap();
+
+    assert!(!response.ok);
+    // Server should give up near the budget, not wait for the full sleep
+    assert!(response.meta.server_ms < 500.0);
+
+    let error = response.error.unwrap();
+    assert_eq!(error.code, error_codes::TIMEOUT);
+    let hint = error.retry_hint().unwrap();
+    assert!(hint.retriable);
+    assert_eq!(hint.timeout_ms, Some(50));
+}
+
+#[test]
+fn test_timeout_ms_within_budget() {
+    let (socket_path, _handle) = start_test_server();
+
+    let mut params = HashMap::new();
+    params.insert("ms".to_string(), json!(10));
+
+    let request = Request::new("test.slow", params).with_timeout(Duration::from_secs(5));
+
+    let response = send_request(&socket_path, &request).unwrap();
+
+    assert!(response.ok);
+    assert_eq!(response.result.unwrap()["slept_ms"], 10);
+}
+
+#[test]
+fn test_client_timeout_gets_server_timeout() {
+    let (socket_path, _handle) = start_test_server();
+
+    // The client's timeout is sent as the budget; the daemon's TIMEOUT arrives before
+    // the client stops waiting, on both one-shot and persistent connections
+    for persistent in [false, true] {
+        let mut client = FgpClient::new(&socket_path)
+            .unwrap()
+            .with_timeout(Duration::from_millis(100));
+        if persistent {
+            client = client.persistent();
+        }
+
+        let response = client.call("test.slow", json!({"ms": 1000})).unwrap();
+        let error = response.error.unwrap();
+        assert_eq!(error.code, error_codes::TIMEOUT);
+        assert_eq!(error.retry_hint().unwrap().timeout_ms, Some(100));
+
+        // Retried while the daemon says the error is retriable
+        let response = client
+            .call_with_retry("test.slow", json!({"ms": 1000}), 2)
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, error_codes::TIMEOUT);
+
+        // The connection is still usable
+        let response = client.call("test.echo", json!({"n": 1})).unwrap();
+        assert!(response.ok);
+    }
+}
+
+/// A service whose dispatch outlives short budgets, recording when it finishes.
+struct LingeringService {
+    events: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl FgpService for LingeringService {
+    fn name(&self) -> &str {
+        "lingering"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn dispatch(&self, _method: &str, _params: HashMap<String, Value>) -> Result<Value> {
+        thread::sleep(Duration::from_millis(300));
+        self.events.lock().unwrap().push("dispatched");
+        Ok(Value::Null)
+    }
+
+    fn on_stop(&self) -> Result<()> {
+        self.events.lock().unwrap().push("stopped");
+        Ok(())
+    }
+}
+
+#[test]
+fn test_shutdown_waits_for_timed_out_dispatch() {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("lingering.sock");
+    let service = LingeringService {
+        events: Arc::clone(&events),
+    };
+    let server = FgpServer::new(service, &socket_path).unwrap();
+    let serving = thread::spawn(move || server.serve());
+    thread::sleep(Duration::from_millis(100));
+
+    let client = FgpClient::new(&socket_path)
+        .unwrap()
+        .with_timeout(Duration::from_millis(50));
+    let response = client.call("lingering.wait", json!({})).unwrap();
+    assert_eq!(response.error.unwrap().code, error_codes::TIMEOUT);
+
+    // The timed-out dispatch is still running; on_stop waits for it
+    client.stop().unwrap();
+    serving.join().unwrap().unwrap();
+    assert_eq!(*events.lock().unwrap(), vec!["dispatched", "stopped"]);
+}
+
+#[test]
+fn test_timed_out_dispatches_are_capped() {
+    let harness = FgpTestHarness::new(TestService::new());
+    let hung = || {
+        let mut params = HashMap::new();
+        params.insert("ms".to_string(), json!(1500));
+        Request::new("test.slow", params).with_timeout(Duration::from_millis(5))
+    };
+
+    // Each timed-out call leaves its dispatch running, up to a limit
+    let mut refused = None;
+    for _ in 0..100 {
+        let response = harness.send(hung());
+        let error = response.error.unwrap();
+        if error.code != error_codes::TIMEOUT {
+            refused = Some(error);
+            break;
+        }
+    }
+    let refused = refused.expect("timed-out dispatches were never capped");
+    assert_eq!(refused.code, error_codes::SERVICE_UNAVAILABLE);
+    assert!(refused.retry_hint().unwrap().retriable);
+
+    // Calls without a budget run inline and aren't affected
+    assert!(harness.call("test.echo", json!({})).ok);
+
+    // Budgeted calls are accepted again once the dispatches finish
+    thread::sleep(Duration::from_millis(1600));
+    let mut params = HashMap::new();
+    params.insert("ms".to_string(), json!(1));
+    let request = Request::new("test.slow", params).with_timeout(Duration::from_secs(5));
+    assert!(harness.send(request).ok);
+}
+
+/// A service that replaces the built-in `health` with its own.
+struct HealthOverrideService;
+
+impl FgpService for HealthOverrideService {
+    fn name(&self) -> &str {
+        "custom"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn dispatch(&self, method: &str, _params: HashMap<String, Value>) -> Result<Value> {
+        match method {
+            "custom.health" => Ok(json!({"status": "healthy", "queue_depth": 3})),
+            _ => anyhow::bail!("Unknown method: {}", method),
+        }
+    }
+
+    fn builtin_override(&self, method: &str) -> bool {
+        method == "health"
+    }
+}
+
+#[test]
+fn test_builtin_override_dispatches_to_service() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("override.sock");
+    let server_socket = socket_path.clone();
+
+    thread::spawn(move || {
+        let server = FgpServer::new(HealthOverrideService, &server_socket).unwrap();
+        let _ = server.serve();
+    });
+    thread::sleep(Duration::from_millis(100));
+
+    for method in ["health", "custom.health"] {
+        let response = send_request(&socket_path, &Request::simple(method)).unwrap();
+        assert!(response.ok);
+        let result = response.result.unwrap();
+        assert_eq!(result["queue_depth"], 3);
+        assert!(result.get("pid").is_none());
+    }
+
+    // Other built-ins are still handled by the server
+    let response = send_request(&socket_path, &Request::simple("methods")).unwrap();
+    assert!(response.result.unwrap()["methods"].is_array());
+
+    // The overridden built-in can be bundled; the others still can't
+    let mut params = HashMap::new();
+    params.insert(
+        "calls".to_string(),
+        json!([{"method": "health"}, {"method": "custom.health"}, {"method": "methods"}]),
+    );
+    let response = send_request(&socket_path, &Request::new("bundle", params)).unwrap();
+    let results = response.result.unwrap()["results"].clone();
+    assert_eq!(results[0]["result"]["queue_depth"], 3);
+    assert_eq!(results[1]["result"]["queue_depth"], 3);
+    assert_eq!(results[2]["error"]["code"], error_codes::INVALID_REQUEST);
+}
+
+// ============================================================================
+// Bundle Tests
+// ============================================================================
+
+#[test]
+fn test_bundle_runs_calls_in_order() {
+    let (socket_path, _handle) = start_test_server();
+
+    let mut params = HashMap::new();
+    params.insert(
+        "calls".to_string(),
+        json!([
+            {"method": "test.add", "params": {"a": 1, "b": 2}},
+            {"method": "echo", "params": {"message": "hi"}},
+            {"method": "test.error"},
+            {"method": "other.method"},
+            {"method": "health"},
+        ]),
+    );
+
+    let request = Request::new("bundle", params);
+    let response = send_request(&socket_path, &request).unwrap();
+
+    assert!(response.ok);
+    assert_eq!(response.id, request.id);
+
+    let result = response.result.unwrap();
+    let results = result["results"].as_array().unwrap();
+    assert_eq!(results.len(), 5);
+
+    assert_eq!(results[0]["ok"], true);
+    assert_eq!(results[0]["result"]["sum"], 3);
+    assert_eq!(results[1]["ok"], true);
+    assert_eq!(results[1]["result"]["echo"], "hi");
+    // A failing call doesn't fail the bundle
+    assert_eq!(results[2]["ok"], false);
+    assert_eq!(results[2]["error"]["code"], error_codes::INTERNAL_ERROR);
+    assert_eq!(results[3]["error"]["code"], error_codes::INVALID_REQUEST);
+    assert_eq!(results[4]["error"]["code"], error_codes::INVALID_REQUEST);
+}
+
+#[test]
+fn test_bundle_timeout_details() {
+    let harness = FgpTestHarness::new(TestService::new());
+    let mut params = HashMap::new();
+    params.insert(
+        "calls".to_string(),
+        json!([
+            {"method": "test.slow", "params": {"ms": 300}},
+            {"method": "test.echo"},
+        ]),
+    );
+    let request = Request::new("bundle", params).with_timeout(Duration::from_millis(50));
+
+    let result = harness.send(request).result.unwrap();
+    for entry in result["results"].as_array().unwrap() {
+        assert_eq!(entry["error"]["code"], error_codes::TIMEOUT);
+        assert_eq!(
+            entry["error"]["details"],
+            json!({"timeout_ms": 50, "retriable": true})
+        );
+    }
+}
+
+#[test]
+fn test_bundle_missing_calls() {
+    let (socket_path, _handle) = start_test_server();
+
+    let response = send_request(&socket_path, &Request::simple("test.bundle")).unwrap();
+
+    assert!(!response.ok);
+    assert_eq!(response.error.unwrap().code, error_codes::INVALID_PARAMS);
+}
+
+#[test]
+fn test_logs_disabled_by_default() {
+    let (socket_path, _handle) = start_test_server();
+
+    // Without with_logs_method, "logs" is an ordinary service method
+    let response = send_request(&socket_path, &Request::simple("logs")).unwrap();
+    assert!(!response.ok);
+
+    let response = send_request(&socket_path, &Request::simple("methods")).unwrap();
+    let methods = response.result.unwrap()["methods"].clone();
+    assert!(!methods
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|m| m["name"] == "logs"));
+}
+
+// ============================================================================
+// Response Metadata Tests
+// ============================================================================
+
+#[test]
+fn test_response_has_server_ms() {
+    let (socket_path, _handle) = start_test_server();
+
+    let request = Request {
+        id: "meta-1".to_string(),
+        v: 1,
+        method: "health".to_string(),
+        params: HashMap::new(),
+        ..Default::default()
+    };
+
+    let response = send_request(&socket_path, &request).unwrap();
+
+    assert!(response.meta.server_ms >= 0.0);
+    assert_eq!(response.meta.protocol_v, 1);
+}
+
+#[test]
 fn test_slow_method_timing() {
     let (socket_path, _handle) = start_test_server();
 
-    let mut params = HashMap::new();
-    params.insert("ms".to_string(), json!(50));
+    let mut params = HashMap::new();
+    params.insert("ms".to_string(), json!(50));
+
+    let request = Request {
+        id: "slow-1".to_string(),
+        v: 1,
+        method: "test.slow".to_string(),
+        params,
+        ..Default::default()
+    };
+
+    let response = send_request(&socket_path, &request).unwrap();
+
+    assert!(response.ok);
+    // Server timing should be at least 50ms
+    assert!(response.meta.server_ms >= 50.0);
+}
+
+#[test]
+fn test_trace_id_echoed_and_passed_to_dispatch() {
+    let (socket_path, _handle) = start_test_server();
+
+    let request = Request::simple("test.trace").with_trace_id("trace-123");
+
+    let response = send_request(&socket_path, &request).unwrap();
+
+    assert!(response.ok);
+    assert_eq!(response.meta.trace_id.as_deref(), Some("trace-123"));
+    let result = response.result.unwrap();
+    assert_eq!(result["trace_id"], "trace-123");
+    assert_eq!(result["id"], request.id.as_str());
+}
+
+#[test]
+fn test_trace_id_omitted_when_absent() {
+    let (socket_path, _handle) = start_test_server();
+
+    let line = send_raw(
+        &socket_path,
+        r#"{"id":"no-trace","v":1,"method":"health","params":{}}"#,
+    )
+    .unwrap();
+
+    assert!(!line.contains("trace_id"));
+}
+
+// ============================================================================
+// ID Matching Tests
+// ============================================================================
+
+#[test]
+fn test_response_id_matches_request() {
+    let (socket_path, _handle) = start_test_server();
+
+    let request_ids = ["id-aaa", "id-bbb", "id-ccc"];
+
+    for id in request_ids {
+        let request = Request {
+            id: id.to_string(),
+            v: 1,
+            method: "health".to_string(),
+            params: HashMap::new(),
+            ..Default::default()
+        };
+
+        let response = send_request(&socket_path, &request).unwrap();
+        assert_eq!(response.id, id);
+    }
+}
+
+// ============================================================================
+// Concurrent Request Tests
+// ============================================================================
+
+#[test]
+fn test_multiple_sequential_requests() {
+    let (socket_path, _handle) = start_test_server();
+
+    // Send 10 requests sequentially
+    for i in 0..10 {
+        let mut params = HashMap::new();
+        params.insert("message".to_string(), json!(format!("msg-{}", i)));
+
+        let request = Request {
+            id: format!("seq-{}", i),
+            v: 1,
+            method: "test.echo".to_string(),
+            params,
+            ..Default::default()
+        };
+
+        let response = send_request(&socket_path, &request).unwrap();
+        assert!(response.ok);
+        assert_eq!(response.result.unwrap()["echo"], format!("msg-{}", i));
+    }
+}
+
+#[test]
+fn test_multiple_parallel_connections() {
+    let (socket_path, _handle) = start_test_server();
+
+    let mut handles = vec![];
+
+    // Spawn 5 parallel connections
+    for i in 0..5 {
+        let socket_clone = socket_path.clone();
+        let handle = thread::spawn(move || {
+            let mut params = HashMap::new();
+            params.insert("message".to_string(), json!(format!("parallel-{}", i)));
+
+            let request = Request {
+                id: format!("par-{}", i),
+                v: 1,
+                method: "test.echo".to_string(),
+                params,
+                ..Default::default()
+            };
+
+            let response = send_request(&socket_clone, &request).unwrap();
+            assert!(response.ok);
+            response
+        });
+        handles.push(handle);
+    }
+
+    // Wait for all to complete
+    for handle in handles {
+        let response = handle.join().unwrap();
+        assert!(response.ok);
+    }
+}
+
+#[test]
+fn test_client_disconnect_before_response() {
+    let (socket_path, _handle) = start_test_server();
+
+    // Send a slow request and hang up without reading the response
+    {
+        let mut stream = UnixStream::connect(&socket_path).unwrap();
+        writeln!(
+            stream,
+            r#"{{"id":"gone-1","v":1,"method":"test.slow","params":{{"ms":50}}}}"#
+        )
+        .unwrap();
+        writeln!(
+            stream,
+            r#"{{"id":"gone-2","v":1,"method":"test.slow","params":{{"ms":50}}}}"#
+        )
+        .unwrap();
+        stream.flush().unwrap();
+    }
+
+    thread::sleep(Duration::from_millis(150));
+
+    // Server keeps serving other clients
+    let response = send_request(&socket_path, &Request::simple("health")).unwrap();
+    assert!(response.ok);
+}
+
+// ============================================================================
+// Socket Permission Tests
+// ============================================================================
+
+#[test]
+fn test_default_socket_mode() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (socket_path, _handle) = start_test_server();
+
+    let mode = std::fs::metadata(&socket_path)
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[test]
+fn test_custom_socket_mode() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("mode.sock");
+    let server_path = socket_path.clone();
+
+    thread::spawn(move || {
+        let server = FgpServer::new(TestService::new(), &server_path)
+            .unwrap()
+            .with_socket_mode(0o660)
+            .unwrap();
+        let _ = server.serve();
+    });
+    thread::sleep(Duration::from_millis(100));
+
+    let mode = std::fs::metadata(&socket_path)
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o660);
+}
+
+#[test]
+fn test_for_service_uses_standard_socket_path() {
+    let server = FgpServer::for_service(TestService::new()).unwrap();
+    assert_eq!(
+        server.socket_path(),
+        fgp_daemon::service_socket_path("test").as_path()
+    );
+}
+
+#[test]
+fn test_socket_mode_validation() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("invalid.sock");
+
+    let server = FgpServer::new(TestService::new(), &socket_path).unwrap();
+    assert!(server.with_socket_mode(0o666).is_err());
+
+    let server = FgpServer::new(TestService::new(), &socket_path).unwrap();
+    assert!(server.with_socket_mode(0o1600).is_err());
+
+    let server = FgpServer::new(TestService::new(), &socket_path).unwrap();
+    assert!(server.with_socket_mode(0o060).is_err());
+
+    let server = FgpServer::new(TestService::new(), &socket_path).unwrap();
+    assert!(server.with_world_writable_socket_mode(0o666).is_ok());
+}
+
+#[test]
+fn test_custom_backlog_accepts_connection_burst() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("backlog.sock");
+    let server_path = socket_path.clone();
+
+    thread::spawn(move || {
+        let server = FgpServer::new(TestService::new(), &server_path)
+            .unwrap()
+            .with_backlog(256);
+        let _ = server.serve();
+    });
+    thread::sleep(Duration::from_millis(100));
+
+    let streams: Vec<UnixStream> = (0..64)
+        .map(|_| UnixStream::connect(&socket_path).unwrap())
+        .collect();
+    assert_eq!(streams.len(), 64);
+
+    let response = send_request(&socket_path, &Request::simple("health")).unwrap();
+    assert!(response.ok);
+}
+
+// ============================================================================
+// Readiness Tests
+// ============================================================================
+
+#[test]
+fn test_ready_file_written_after_bind() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("ready.sock");
+    let ready_path = temp_dir.path().join("daemon.ready");
+    let server_socket = socket_path.clone();
+    let server_ready = ready_path.clone();
+
+    thread::spawn(move || {
+        let server = FgpServer::new(TestService::new(), &server_socket)
+            .unwrap()
+            .with_ready_file(&server_ready)
+            .unwrap();
+        let _ = server.serve();
+    });
+
+    let mut waited = 0;
+    while !ready_path.exists() && waited < 2000 {
+        thread::sleep(Duration::from_millis(10));
+        waited += 10;
+    }
+
+    let event: Value =
+        serde_json::from_str(&std::fs::read_to_string(&ready_path).unwrap()).unwrap();
+    assert_eq!(event["event"], "ready");
+    assert_eq!(event["socket"], socket_path.to_str().unwrap());
+
+    // Socket is connectable as soon as the ready file exists
+    assert!(UnixStream::connect(&socket_path).is_ok());
+}
+
+#[test]
+fn test_server_builder() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("builder.sock");
+    let pid_path = temp_dir.path().join("daemon.pid");
+
+    assert!(FgpServerBuilder::new()
+        .socket_mode(0o666)
+        .build(TestService::new(), &socket_path)
+        .is_err());
+
+    let server = FgpServerBuilder::new()
+        .socket_mode(0o660)
+        .pid_file(&pid_path)
+        .logs_method(true)
+        .build(TestService::new(), &socket_path)
+        .unwrap();
+    thread::spawn(move || server.serve());
+    thread::sleep(Duration::from_millis(100));
+
+    use std::os::unix::fs::PermissionsExt;
+    let mode = std::fs::metadata(&socket_path)
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o660);
+    assert!(pid_path.exists());
+
+    let methods = send_request(&socket_path, &Request::simple("methods")).unwrap();
+    let names: Vec<String> = methods.result.unwrap()["methods"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["name"].as_str().unwrap().to_string())
+        .collect();
+    assert!(names.contains(&"logs".to_string()));
+}
+
+#[test]
+fn test_pid_file_written_and_removed() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("pid.sock");
+    let pid_path = temp_dir.path().join("daemon.pid");
+    let server_socket = socket_path.clone();
+    let server_pid = pid_path.clone();
+
+    let handle = thread::spawn(move || {
+        let server = FgpServer::new(TestService::new(), &server_socket)
+            .unwrap()
+            .with_pid_file(&server_pid)
+            .unwrap();
+        let _ = server.serve();
+    });
+    thread::sleep(Duration::from_millis(100));
+
+    let pid = std::fs::read_to_string(&pid_path).unwrap();
+    assert_eq!(pid.trim(), std::process::id().to_string());
 
-    let request = Request {
-        id: "slow-1".to_string(),
-        v: 1,
-        method: "test.slow".to_string(),
-        params,
-    };
+    let response = send_request(&socket_path, &Request::simple("stop")).unwrap();
+    assert!(response.ok);
+    // Wake the accept loop so it notices the stop
+    let _ = UnixStream::connect(&socket_path);
+    handle.join().unwrap();
 
-    let response = send_request(&socket_path, &request).unwrap();
+    assert!(!pid_path.exists());
+    assert!(!socket_path.exists());
+}
+
+#[test]
+fn test_stop_drains_in_flight_requests() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("drain.sock");
+    let server_socket = socket_path.clone();
+
+    let server = thread::spawn(move || {
+        let server = FgpServer::new(TestService::new(), &server_socket).unwrap();
+        let _ = server.serve();
+    });
+    thread::sleep(Duration::from_millis(100));
+
+    let slow_socket = socket_path.clone();
+    let slow = thread::spawn(move || {
+        let mut params = HashMap::new();
+        params.insert("ms".to_string(), json!(300));
+        send_request(&slow_socket, &Request::new("test.slow", params)).unwrap()
+    });
+    thread::sleep(Duration::from_millis(50));
 
+    // The stop response doesn't wait for the drain
+    let stop_start = std::time::Instant::now();
+    let response = send_request(&socket_path, &Request::simple("stop")).unwrap();
     assert!(response.ok);
-    // Server timing should be at least 50ms
-    assert!(response.meta.server_ms >= 50.0);
+    assert!(stop_start.elapsed() < Duration::from_millis(200));
+
+    // The in-flight request still completes, then the server exits
+    assert!(slow.join().unwrap().ok);
+    server.join().unwrap();
+    assert!(UnixStream::connect(&socket_path).is_err());
+}
+
+#[test]
+fn test_draining_rejects_requests_on_open_connections() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("draining.sock");
+    let server_socket = socket_path.clone();
+
+    let server = thread::spawn(move || {
+        let server = FgpServer::new(TestService::new(), &server_socket).unwrap();
+        let _ = server.serve();
+    });
+    thread::sleep(Duration::from_millis(100));
+
+    // Opened before shutdown begins
+    let client = FgpClient::new(&socket_path).unwrap().persistent();
+    assert!(client.call("test.echo", json!({})).unwrap().ok);
+
+    // Keeps the drain going while the open connection sends another request
+    let slow_socket = socket_path.clone();
+    let slow = thread::spawn(move || {
+        let mut params = HashMap::new();
+        params.insert("ms".to_string(), json!(300));
+        send_request(&slow_socket, &Request::new("test.slow", params)).unwrap()
+    });
+    thread::sleep(Duration::from_millis(50));
+    assert!(
+        send_request(&socket_path, &Request::simple("stop"))
+            .unwrap()
+            .ok
+    );
+
+    let response = client.call("test.echo", json!({})).unwrap();
+    assert!(!response.ok);
+    let error = response.error.unwrap();
+    assert_eq!(error.code, error_codes::SERVICE_UNAVAILABLE);
+    assert_eq!(error.message, "Daemon is shutting down");
+    let hint = error.retry_hint().unwrap();
+    assert!(hint.retriable);
+    assert_eq!(hint.retry_after_ms, Some(1000));
+
+    assert!(slow.join().unwrap().ok);
+    server.join().unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_abstract_socket() {
+    let name = format!("@fgp-test-{}", std::process::id());
+    for builder in [FgpServerBuilder::new(), FgpServerBuilder::new().backlog(16)] {
+        let server = Arc::new(builder.build(TestService::new(), &name).unwrap());
+        let serving = Arc::clone(&server);
+        let handle = thread::spawn(move || serving.serve().unwrap());
+        thread::sleep(Duration::from_millis(100));
+
+        let client = FgpClient::new(&name).unwrap();
+        assert!(client.call("test.echo", json!({})).unwrap().ok);
+        assert!(!PathBuf::from(&name).exists());
+
+        server.stop();
+        handle.join().unwrap();
+        assert!(FgpClient::new(&name).unwrap().health().is_err());
+    }
+}
+
+#[test]
+fn test_lifecycle_hook() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("lifecycle.sock");
+    let server_socket = socket_path.clone();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&events);
+
+    let server = thread::spawn(move || {
+        FgpServer::new(TestService::new(), &server_socket)
+            .unwrap()
+            .with_lifecycle_hook(move |event| recorded.lock().unwrap().push(event))
+            .serve()
+            .unwrap();
+    });
+    thread::sleep(Duration::from_millis(100));
+
+    assert!(
+        send_request(&socket_path, &Request::simple("health"))
+            .unwrap()
+            .ok
+    );
+    assert!(
+        send_request(&socket_path, &Request::simple("stop"))
+            .unwrap()
+            .ok
+    );
+    server.join().unwrap();
+
+    let events = events.lock().unwrap();
+    let kinds: Vec<LifecycleEventKind> = events.iter().map(|e| e.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            LifecycleEventKind::Started,
+            LifecycleEventKind::ConnectionAccepted,
+            LifecycleEventKind::ConnectionAccepted,
+            LifecycleEventKind::Stopped,
+        ]
+    );
+    assert!(events.iter().all(|e| e.socket_path == socket_path));
+    assert!(events[0].at <= events[3].at);
 }
 
 // ============================================================================
-// ID Matching Tests
+// Persistent Client Tests
 // ============================================================================
 
+/// Start a fake daemon that answers one request per connection and then closes
+/// it, as if the daemon restarted between calls.
+fn start_restarting_daemon() -> PathBuf {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("restart.sock");
+    std::mem::forget(temp_dir);
+
+    let listener = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+    thread::spawn(move || {
+        for (conn, stream) in listener.incoming().enumerate() {
+            let mut stream = stream.unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let request: Request = serde_json::from_str(&line).unwrap();
+            let response = Response::success(&request.id, json!({"conn": conn}), 0.0);
+            writeln!(stream, "{}", serde_json::to_string(&response).unwrap()).unwrap();
+        }
+    });
+
+    socket_path
+}
+
 #[test]
-fn test_response_id_matches_request() {
-    let (socket_path, _handle) = start_test_server();
+fn test_resilient_client_replays_idempotent_call() {
+    let socket_path = start_restarting_daemon();
+    let client = FgpClient::new(&socket_path).unwrap().persistent_resilient();
 
-    let request_ids = ["id-aaa", "id-bbb", "id-ccc"];
+    let first = client.call_idempotent("test.echo", json!({})).unwrap();
+    assert_eq!(first.result.unwrap()["conn"], 0);
 
-    for id in request_ids {
-        let request = Request {
-            id: id.to_string(),
-            v: 1,
-            method: "health".to_string(),
-            params: HashMap::new(),
-        };
+    // The connection was closed; the call is replayed on a new one
+    let second = client.call_idempotent("test.echo", json!({})).unwrap();
+    assert_eq!(second.result.unwrap()["conn"], 1);
+}
 
-        let response = send_request(&socket_path, &request).unwrap();
-        assert_eq!(response.id, id);
+#[test]
+fn test_resilient_client_drops_connection_after_failed_replay() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("replay.sock");
+
+    // The first connection closes after one response; the second answers the replayed
+    // request with the wrong id, then keeps answering
+    let listener = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+    thread::spawn(move || {
+        for (conn, stream) in listener.incoming().enumerate() {
+            let stream = stream.unwrap();
+            thread::spawn(move || {
+                let mut reader = BufReader::new(&stream);
+                let mut line = String::new();
+                for answered in 0.. {
+                    line.clear();
+                    if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                        break;
+                    }
+                    let request: Request = serde_json::from_str(&line).unwrap();
+                    let id = if conn == 1 && answered == 0 {
+                        "someone-else"
+                    } else {
+                        &request.id
+                    };
+                    let response = Response::success(id, json!({"conn": conn}), 0.0);
+                    writeln!(&stream, "{}", serde_json::to_string(&response).unwrap()).unwrap();
+                    if conn == 0 {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    let client = FgpClient::new(&socket_path).unwrap().persistent_resilient();
+    client.call_idempotent("test.echo", json!({})).unwrap();
+    assert!(client.call_idempotent("test.echo", json!({})).is_err());
+
+    // The desynced connection isn't reused
+    let response = client.call_idempotent("test.echo", json!({})).unwrap();
+    assert_eq!(response.result.unwrap()["conn"], 2);
+}
+
+#[test]
+fn test_resilient_client_does_not_replay_non_idempotent_call() {
+    let socket_path = start_restarting_daemon();
+    let client = FgpClient::new(&socket_path).unwrap().persistent_resilient();
+
+    assert!(client.call("test.add", json!({})).is_ok());
+    assert!(client.call("test.add", json!({})).is_err());
+
+    // The next call reconnects
+    let response = client.call("test.add", json!({})).unwrap();
+    assert_eq!(response.result.unwrap()["conn"], 1);
+}
+
+#[test]
+fn test_client_rejects_mismatched_response_id() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("desync.sock");
+
+    // A fake daemon that answers with someone else's id
+    let listener = std::os::unix::net::UnixListener::bind(&socket_path).unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let stream = stream.unwrap();
+            let mut reader = NdjsonReader::new(&stream);
+            while let Ok(Some(_)) = reader.read_request() {
+                let response = Response::success("other", json!({}), 0.0);
+                (&stream)
+                    .write_all(response.to_ndjson_line().unwrap().as_bytes())
+                    .unwrap();
+            }
+        }
+    });
+
+    let client = FgpClient::new(&socket_path).unwrap();
+    let error = client.call("test.echo", json!({})).unwrap_err();
+    assert!(
+        error.to_string().contains("Response id mismatch"),
+        "{}",
+        error
+    );
+    assert!(client.persistent().call("test.echo", json!({})).is_err());
+
+    let unchecked = FgpClient::new(&socket_path)
+        .unwrap()
+        .with_response_id_check(false);
+    assert_eq!(unchecked.call("test.echo", json!({})).unwrap().id, "other");
+}
+
+#[test]
+fn test_max_requests_per_connection() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("max.sock");
+    let server_socket = socket_path.clone();
+
+    thread::spawn(move || {
+        let server = FgpServer::new(TestService::new(), &server_socket)
+            .unwrap()
+            .with_max_requests_per_connection(2);
+        let _ = server.serve();
+    });
+    thread::sleep(Duration::from_millis(100));
+
+    // A raw connection gets two responses, the last flagged, then EOF
+    let mut stream = UnixStream::connect(&socket_path).unwrap();
+    for _ in 0..3 {
+        writeln!(
+            stream,
+            "{}",
+            serde_json::to_string(&Request::simple("health")).unwrap()
+        )
+        .unwrap();
+    }
+    let lines: Vec<String> = BufReader::new(&stream)
+        .lines()
+        .map(|l| l.unwrap())
+        .collect();
+    assert_eq!(lines.len(), 2);
+    let last: Response = serde_json::from_str(&lines[1]).unwrap();
+    assert!(last.meta.connection_close);
+
+    // A persistent client reconnects without failing any calls
+    let client = FgpClient::new(&socket_path).unwrap().persistent();
+    for _ in 0..5 {
+        assert!(client.call("test.add", json!({"a": 1, "b": 1})).unwrap().ok);
     }
 }
 
 // ============================================================================
-// Concurrent Request Tests
+// Test Harness Tests
 // ============================================================================
 
 #[test]
-fn test_multiple_sequential_requests() {
-    let (socket_path, _handle) = start_test_server();
+fn test_harness_dispatch_and_namespacing() {
+    let harness = FgpTestHarness::new(TestService::new());
 
-    // Send 10 requests sequentially
-    for i in 0..10 {
-        let mut params = HashMap::new();
-        params.insert("message".to_string(), json!(format!("msg-{}", i)));
+    let response = harness.call("echo", json!({"message": "hi"}));
+    assert!(response.ok);
+    assert_eq!(response.result.unwrap()["echo"], "hi");
 
-        let request = Request {
-            id: format!("seq-{}", i),
-            v: 1,
-            method: "test.echo".to_string(),
-            params,
-        };
+    let response = harness.call("test.add", json!({"a": 2, "b": 3}));
+    assert_eq!(response.result.unwrap()["sum"], 5);
 
-        let response = send_request(&socket_path, &request).unwrap();
-        assert!(response.ok);
-        assert_eq!(response.result.unwrap()["echo"], format!("msg-{}", i));
-    }
+    let response = harness.call("other.method", json!({}));
+    assert_eq!(response.error.unwrap().code, error_codes::INVALID_REQUEST);
+
+    assert_eq!(harness.service().call_count.load(Ordering::SeqCst), 2);
 }
 
 #[test]
-fn test_multiple_parallel_connections() {
-    let (socket_path, _handle) = start_test_server();
+fn test_harness_builtins() {
+    let harness = FgpTestHarness::new(TestService::new());
 
-    let mut handles = vec![];
+    let response = harness.call("health", Value::Null);
+    assert_eq!(response.result.unwrap()["status"], "healthy");
 
-    // Spawn 5 parallel connections
-    for i in 0..5 {
-        let socket_clone = socket_path.clone();
-        let handle = thread::spawn(move || {
-            let mut params = HashMap::new();
-            params.insert("message".to_string(), json!(format!("parallel-{}", i)));
+    let response = harness.call("test.methods", Value::Null);
+    assert!(response.result.unwrap()["methods"].is_array());
 
-            let request = Request {
-                id: format!("par-{}", i),
-                v: 1,
-                method: "test.echo".to_string(),
-                params,
-            };
+    let request = Request::simple("test.trace").with_trace_id("trace-1");
+    let response = harness.send(request);
+    assert_eq!(response.meta.trace_id.as_deref(), Some("trace-1"));
 
-            let response = send_request(&socket_clone, &request).unwrap();
-            assert!(response.ok);
-            response
-        });
-        handles.push(handle);
-    }
+    assert!(harness.is_running());
+    assert!(harness.call("stop", Value::Null).ok);
+    assert!(!harness.is_running());
+}
 
-    // Wait for all to complete
-    for handle in handles {
-        let response = handle.join().unwrap();
-        assert!(response.ok);
-    }
+#[test]
+fn test_harness_validate_schemas() {
+    FgpTestHarness::new(TestService::new())
+        .validate_schemas()
+        .unwrap();
 }
 
 // ============================================================================
@@ -548,6 +2635,7 @@ fn test_service_maintains_state() {
             v: 1,
             method: "test.echo".to_string(),
             params: HashMap::new(),
+            ..Default::default()
         };
         send_request(&socket_path, &request).unwrap();
     }
@@ -558,6 +2646,7 @@ fn test_service_maintains_state() {
         v: 1,
         method: "test.count".to_string(),
         params: HashMap::new(),
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -580,6 +2669,7 @@ fn test_empty_params() {
         v: 1,
         method: "test.echo".to_string(),
         params: HashMap::new(), // Empty params
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -602,6 +2692,7 @@ fn test_extra_params_ignored() {
         v: 1,
         method: "test.echo".to_string(),
         params,
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -624,6 +2715,7 @@ fn test_large_message() {
         v: 1,
         method: "test.echo".to_string(),
         params,
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -647,6 +2739,7 @@ fn test_unicode_in_params() {
         v: 1,
         method: "test.echo".to_string(),
         params,
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -658,3 +2751,373 @@ fn test_unicode_in_params() {
     assert!(echo.contains("🌍"));
     assert!(echo.contains("مرحبا"));
 }
+
+#[test]
+fn test_length_prefixed_framing() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("framed.sock");
+    let server = FgpServerBuilder::new()
+        .framing(FramingMode::LengthPrefixed)
+        .build(TestService::new(), &socket_path)
+        .unwrap();
+    thread::spawn(move || server.serve());
+    thread::sleep(Duration::from_millis(100));
+
+    // Messages containing newlines survive unescaped framing intact
+    let message = "line one\nline two\n".repeat(1000);
+    for client in [
+        FgpClient::new(&socket_path).unwrap(),
+        FgpClient::new(&socket_path).unwrap().persistent(),
+    ] {
+        let client = client.with_framing(FramingMode::LengthPrefixed);
+        for _ in 0..2 {
+            let result = client
+                .call_value("test.echo", json!({ "message": message }))
+                .unwrap();
+            assert_eq!(result["echo"], json!(message));
+        }
+        let requests = vec![Request::simple("health"), Request::simple("methods")];
+        assert!(client.pipeline(requests).unwrap().iter().all(|r| r.ok));
+    }
+
+    // On the wire: a big-endian length, then the JSON
+    let mut stream = UnixStream::connect(&socket_path).unwrap();
+    let request = serde_json::to_vec(&Request::simple("health")).unwrap();
+    stream
+        .write_all(&(request.len() as u32).to_be_bytes())
+        .unwrap();
+    stream.write_all(&request).unwrap();
+    let mut prefix = [0u8; 4];
+    stream.read_exact(&mut prefix).unwrap();
+    let mut body = vec![0u8; u32::from_be_bytes(prefix) as usize];
+    stream.read_exact(&mut body).unwrap();
+    let response: Response = serde_json::from_slice(&body).unwrap();
+    assert!(response.ok);
+}
+
+/// Serves a greeting read from a shared "config file" on `reload`.
+struct ReloadingService {
+    source: Arc<Mutex<String>>,
+    greeting: RwLock<String>,
+}
+
+impl FgpService for ReloadingService {
+    fn name(&self) -> &str {
+        "reloading"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn dispatch(&self, _method: &str, _params: HashMap<String, Value>) -> Result<Value> {
+        Ok(json!(*self.greeting.read().unwrap()))
+    }
+
+    fn reload(&self) -> Result<()> {
+        let greeting = self.source.lock().unwrap().clone();
+        if greeting.is_empty() {
+            anyhow::bail!("Config is empty");
+        }
+        *self.greeting.write().unwrap() = greeting;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_fgp_params_macro() {
+    let params: HashMap<String, Value> = HashMap::from([
+        ("a".to_string(), json!(2)),
+        ("b".to_string(), json!("three")),
+    ]);
+
+    let (a, c) = fgp_daemon::fgp_params!(params, "a": i64, "c": Option<bool>).unwrap();
+    assert_eq!((a, c), (2, None));
+
+    let err = fgp_daemon::fgp_params!(params, "a": i64, "b": i64)
+        .err()
+        .unwrap();
+    assert_eq!(err.code, error_codes::INVALID_PARAMS);
+    assert!(err
+        .message
+        .starts_with("Invalid parameter 'b': invalid type"));
+    assert_eq!(err.details, Some(json!({"param": "b"})));
+}
+
+#[test]
+fn test_param_coercion() {
+    let temp_dir = TempDir::new().unwrap();
+    let params = json!({"a": "10", "b": 5});
+
+    // Off by default: the string doesn't reach the method as an integer
+    let harness = FgpTestHarness::new(TestService::new());
+    let response = harness.call("test.add", params.clone());
+    assert_eq!(response.error.unwrap().message, "Missing parameter: a");
+
+    let socket_path = temp_dir.path().join("coerce.sock");
+    let server = FgpServerBuilder::new()
+        .param_coercion(true)
+        .build(TestService::new(), &socket_path)
+        .unwrap();
+    thread::spawn(move || server.serve());
+    thread::sleep(Duration::from_millis(100));
+
+    let client = FgpClient::new(&socket_path).unwrap();
+    assert_eq!(
+        client.call_value("add", params.clone()).unwrap(),
+        json!({"sum": 15})
+    );
+    // Bundled calls are coerced too
+    let bundle = client
+        .call_value(
+            "bundle",
+            json!({"calls": [{"method": "test.add", "params": params}]}),
+        )
+        .unwrap();
+    assert_eq!(bundle["results"][0]["result"], json!({"sum": 15}));
+
+    // `message` is declared a string, so it is left alone
+    assert_eq!(
+        client
+            .call_value("test.echo", json!({"message": "42"}))
+            .unwrap(),
+        json!({"echo": "42"})
+    );
+}
+
+#[test]
+fn test_unknown_field_warnings() {
+    let line = r#"{"id":"typo-1","v":1,"method":"test.echo","parms":{"message":"hi"},"x":1}"#;
+    let request: Request = serde_json::from_str(line).unwrap();
+    assert_eq!(request.extra["parms"], json!({"message": "hi"}));
+    assert!(request.params.is_empty());
+
+    // Off by default
+    let harness = FgpTestHarness::new(TestService::new());
+    assert!(harness.send(request.clone()).meta.warnings.is_empty());
+
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("unknown.sock");
+    let server = FgpServerBuilder::new()
+        .unknown_field_warnings(true)
+        .build(TestService::new(), &socket_path)
+        .unwrap();
+    thread::spawn(move || server.serve());
+    thread::sleep(Duration::from_millis(100));
+
+    let response = send_request(&socket_path, &request).unwrap();
+    assert_eq!(
+        response.meta.warnings,
+        vec!["Unknown request fields ignored: parms, x"]
+    );
+    let response = send_request(&socket_path, &Request::simple("health")).unwrap();
+    assert!(response.meta.warnings.is_empty());
+}
+
+#[test]
+fn test_response_cache() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("cache.sock");
+    let server = FgpServerBuilder::new()
+        .cache(CacheConfig {
+            methods: HashSet::from(["test.count".to_string()]),
+            ttl: Duration::from_millis(300),
+            max_entries: 2,
+        })
+        .build(TestService::new(), &socket_path)
+        .unwrap();
+    thread::spawn(move || server.serve());
+    thread::sleep(Duration::from_millis(100));
+    let client = FgpClient::new(&socket_path).unwrap();
+
+    let first = client
+        .call("count", json!({"a": 1, "b": {"x": 1, "y": 2}}))
+        .unwrap();
+    assert!(!first.meta.cached);
+    let again = client
+        .call("test.count", json!({"b": {"y": 2, "x": 1}, "a": 1}))
+        .unwrap();
+    assert!(again.meta.cached);
+    assert_eq!(again.result, first.result);
+
+    // Other params and uncached methods are dispatched
+    let other = client.call("count", json!({"a": 2})).unwrap();
+    assert!(!other.meta.cached);
+    assert_ne!(other.result, first.result);
+    assert!(!client.call("echo", json!({})).unwrap().meta.cached);
+
+    // Expired entries are dispatched again
+    thread::sleep(Duration::from_millis(300));
+    let expired = client.call("count", json!({"a": 2})).unwrap();
+    assert!(!expired.meta.cached);
+
+    // Bundled calls use the cache too
+    let bundle = client
+        .call_value(
+            "bundle",
+            json!({"calls": [{"method": "count", "params": {"a": 2}}]}),
+        )
+        .unwrap();
+    assert_eq!(Some(bundle["results"][0]["result"].clone()), expired.result);
+
+    let metrics = client.health_report().unwrap().metrics;
+    assert_eq!(metrics.cache_hits, 2);
+    assert_eq!(metrics.cache_misses, 3);
+}
+
+#[test]
+fn test_reload_method() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = Arc::new(Mutex::new("hello".to_string()));
+    let service = || ReloadingService {
+        source: Arc::clone(&source),
+        greeting: RwLock::new("hi".into()),
+    };
+
+    // Disabled by default: `reload` goes to dispatch like any other method
+    let harness = FgpTestHarness::new(service());
+    harness.call("reload", json!({}));
+    assert_eq!(harness.call("greet", json!({})).result, Some(json!("hi")));
+
+    let socket_path = temp_dir.path().join("reload.sock");
+    let server = FgpServerBuilder::new()
+        .reload_method(true)
+        .build(service(), &socket_path)
+        .unwrap();
+    thread::spawn(move || server.serve());
+    thread::sleep(Duration::from_millis(100));
+
+    let client = FgpClient::new(&socket_path).unwrap().persistent();
+    assert_eq!(client.call_value("greet", json!({})).unwrap(), json!("hi"));
+    assert!(client.reload().unwrap().ok);
+    assert_eq!(
+        client.call_value("greet", json!({})).unwrap(),
+        json!("hello")
+    );
+
+    // A failed reload keeps the current state and the connection
+    source.lock().unwrap().clear();
+    let response = client.reload().unwrap();
+    assert_eq!(response.error.unwrap().message, "Config is empty");
+    assert_eq!(
+        client.call_value("greet", json!({})).unwrap(),
+        json!("hello")
+    );
+
+    let methods = client.call_value("methods", json!({})).unwrap();
+    assert!(methods["methods"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|m| m["name"] == "reload"));
+}
+
+#[test]
+fn test_composite_service() {
+    let suite = CompositeService::new("suite", "2.0.0")
+        .with_service(TestService::new())
+        .with_service(ReloadingService {
+            source: Arc::new(Mutex::new(String::new())),
+            greeting: RwLock::new("hi".into()),
+        });
+    let harness = FgpTestHarness::new(suite);
+
+    let response = harness.call("suite.test.echo", json!({"message": "hi"}));
+    assert_eq!(response.result.unwrap()["echo"], "hi");
+    let response = harness.call("suite.reloading.greet", json!({}));
+    assert_eq!(response.result, Some(json!("hi")));
+
+    let response = harness.call("suite.calendar.list", json!({}));
+    assert_eq!(response.error.unwrap().code, error_codes::UNKNOWN_METHOD);
+
+    // Deprecations are rewritten to the composite's names
+    let response = harness.call("suite.test.shout", json!({"message": "hi"}));
+    assert_eq!(
+        response.meta.warnings,
+        vec!["Method 'suite.test.shout' is deprecated, use 'suite.test.echo'"]
+    );
+
+    let methods = harness.call("methods", json!({})).result.unwrap();
+    let names: Vec<&str> = methods["methods"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|m| m["name"].as_str())
+        .collect();
+    assert!(names.contains(&"suite.test.add"));
+
+    let health = harness
+        .call("health", json!({"deep": true}))
+        .result
+        .unwrap();
+    assert_eq!(health["version"], "2.0.0");
+    assert_eq!(health["services"]["test.test_service"]["ok"], true);
+
+    // Inner capabilities and error codes are merged
+    assert_eq!(methods["capabilities"]["features"]["test.echo"], true);
+    let codes = methods["error_codes"].as_array().unwrap();
+    assert_eq!(codes.last(), Some(&json!("RATE_LIMITED")));
+}
+
+#[test]
+fn test_audit_sink() {
+    let temp_dir = TempDir::new().unwrap();
+    let serve_audited = |name: &str, with_params: bool| {
+        let records: Arc<Mutex<Vec<AuditRecord>>> = Arc::default();
+        let sink = Arc::clone(&records);
+        let socket_path = temp_dir.path().join(name);
+        let mut server = FgpServer::new(TestService::new(), &socket_path)
+            .unwrap()
+            .with_audit_sink(move |record| sink.lock().unwrap().push(record.clone()));
+        if with_params {
+            server = server.with_audit_params(&["password"]);
+        }
+        thread::spawn(move || server.serve());
+        thread::sleep(Duration::from_millis(100));
+        (FgpClient::new(&socket_path).unwrap(), records)
+    };
+    // Records are emitted after the response is written
+    let wait_for = |records: &Mutex<Vec<AuditRecord>>, count: usize| {
+        for _ in 0..100 {
+            if records.lock().unwrap().len() >= count {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        records.lock().unwrap().clone()
+    };
+
+    let (client, records) = serve_audited("audit.sock", false);
+    let request = Request::new(
+        "test.echo",
+        HashMap::from([("password".to_string(), json!("hunter2"))]),
+    )
+    .with_trace_id("trace-1");
+    client.pipeline(vec![request.clone()]).unwrap();
+    client.call("test.error", json!({})).unwrap();
+
+    let records = wait_for(&records, 2);
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].id, request.id);
+    assert_eq!(records[0].method, "test.echo");
+    assert_eq!(records[0].trace_id.as_deref(), Some("trace-1"));
+    assert!(records[0].ok);
+    assert!(records[0].params.is_none());
+    assert!(!records[1].ok);
+    assert_eq!(
+        records[1].error_code.as_deref(),
+        Some(error_codes::INTERNAL_ERROR)
+    );
+    #[cfg(target_os = "linux")]
+    assert_eq!(records[0].peer_uid, Some(unsafe { libc::getuid() }));
+
+    // Params are opt-in, with redaction
+    let (client, records) = serve_audited("audit-params.sock", true);
+    client
+        .call("test.echo", json!({"message": "hi", "password": "hunter2"}))
+        .unwrap();
+    assert_eq!(
+        wait_for(&records, 1)[0].params,
+        Some(json!({"message": "hi", "password": "[REDACTED]"}))
+    );
+}
@@ -21,6 +21,7 @@ fn test_request_minimal() {
         v: 1,
         method: "echo".to_string(),
         params: HashMap::new(),
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -44,6 +45,7 @@ fn test_request_with_params() {
         v: 1,
         method: "service.action".to_string(),
         params,
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -114,6 +116,7 @@ fn test_response_success() {
         meta: ResponseMeta {
             server_ms: 12.5,
             protocol_v: 1,
+            ..Default::default()
         },
     };
 
@@ -142,6 +145,7 @@ fn test_response_error() {
         meta: ResponseMeta {
             server_ms: 0.5,
             protocol_v: 1,
+            ..Default::default()
         },
     };
 
@@ -190,6 +194,7 @@ fn test_ndjson_single_line() {
         v: 1,
         method: "test".to_string(),
         params: HashMap::new(),
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -209,6 +214,7 @@ fn test_ndjson_with_newlines_in_data() {
         v: 1,
         method: "test".to_string(),
         params,
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -220,18 +226,20 @@ fn test_ndjson_with_newlines_in_data() {
 
 #[test]
 fn test_ndjson_multiple_requests() {
-    let requests = vec![
+    let requests = [
         Request {
             id: "batch-1".to_string(),
             v: 1,
             method: "first".to_string(),
             params: HashMap::new(),
+            ..Default::default()
         },
         Request {
             id: "batch-2".to_string(),
             v: 1,
             method: "second".to_string(),
             params: HashMap::new(),
+            ..Default::default()
         },
     ];
 
@@ -269,6 +277,7 @@ fn test_request_unicode_params() {
         v: 1,
         method: "test".to_string(),
         params,
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -293,6 +302,7 @@ fn test_request_special_characters() {
         v: 1,
         method: "test".to_string(),
         params,
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -316,6 +326,7 @@ fn test_response_large_result() {
         meta: ResponseMeta {
             server_ms: 50.0,
             protocol_v: 1,
+            ..Default::default()
         },
     };
 
@@ -349,6 +360,7 @@ fn test_request_deeply_nested_params() {
         v: 1,
         method: "test".to_string(),
         params,
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -371,6 +383,7 @@ fn test_protocol_version_1() {
         v: 1,
         method: "test".to_string(),
         params: HashMap::new(),
+        ..Default::default()
     };
 
     assert_eq!(request.v, 1);
@@ -383,6 +396,7 @@ fn test_protocol_version_1() {
         meta: ResponseMeta {
             server_ms: 1.0,
             protocol_v: 1,
+            ..Default::default()
         },
     };
 
@@ -400,6 +414,7 @@ fn test_request_id_uuid_format() {
         v: 1,
         method: "test".to_string(),
         params: HashMap::new(),
+        ..Default::default()
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -415,6 +430,7 @@ fn test_request_id_simple_format() {
         v: 1,
         method: "test".to_string(),
         params: HashMap::new(),
+        ..Default::default()
     };
 
     assert_eq!(request.id, "1");
@@ -429,6 +445,7 @@ fn test_response_matches_request_id() {
         v: 1,
         method: "test".to_string(),
         params: HashMap::new(),
+        ..Default::default()
     };
 
     let response = Response {
@@ -439,6 +456,7 @@ fn test_response_matches_request_id() {
         meta: ResponseMeta {
             server_ms: 1.0,
             protocol_v: 1,
+            ..Default::default()
         },
     };
 
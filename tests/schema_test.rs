@@ -101,12 +101,14 @@ impl FgpService for SchemaTestService {
                     param_type: "integer".into(),
                     required: false,
                     default: Some(json!(10)),
+                    ..Default::default()
                 })
                 .param(ParamInfo {
                     name: "offset".into(),
                     param_type: "integer".into(),
                     required: false,
                     default: Some(json!(0)),
+                    ..Default::default()
                 }),
         ]
     }
@@ -260,12 +262,14 @@ fn test_synthesize_from_legacy_params() {
             param_type: "string".into(),
             required: true,
             default: None,
+            ..Default::default()
         })
         .param(ParamInfo {
             name: "limit".into(),
             param_type: "integer".into(),
             required: false,
             default: Some(json!(10)),
+            ..Default::default()
         })];
 
     let result = to_openai(&methods);
@@ -293,6 +297,7 @@ fn test_schema_builtin_default_format() {
         v: 1,
         method: "schema".to_string(),
         params: HashMap::new(),
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -330,6 +335,7 @@ fn test_schema_builtin_openai_format() {
         v: 1,
         method: "schema".to_string(),
         params,
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -348,6 +354,43 @@ fn test_schema_builtin_openai_format() {
     assert!(names.contains(&"schema-test_list_items"));
 }
 
+#[test]
+fn test_schema_builtin_examples_param() {
+    let (socket_path, _handle) = start_schema_test_server();
+
+    let mut params = HashMap::new();
+    params.insert("format".to_string(), json!("anthropic"));
+    params.insert("examples".to_string(), json!(true));
+
+    let request = Request {
+        id: "schema-examples".to_string(),
+        v: 1,
+        method: "schema".to_string(),
+        params,
+        ..Default::default()
+    };
+
+    let response = send_request(&socket_path, &request).unwrap();
+
+    assert!(response["ok"].as_bool().unwrap());
+
+    let tools = response["result"]["tools"].as_array().unwrap();
+    let send_email = tools
+        .iter()
+        .find(|t| t["name"] == "schema-test.send_email")
+        .unwrap();
+    let description = send_email["description"].as_str().unwrap();
+    assert!(description.starts_with("Send an email to a recipient\n\nExample: {"));
+    assert!(description.contains("\"to\":\"alice@example.com\""));
+
+    // No examples to add
+    let list_items = tools
+        .iter()
+        .find(|t| t["name"] == "schema-test.list_items")
+        .unwrap();
+    assert_eq!(list_items["description"], "List items with pagination");
+}
+
 #[test]
 fn test_schema_builtin_anthropic_format() {
     let (socket_path, _handle) = start_schema_test_server();
@@ -360,6 +403,7 @@ fn test_schema_builtin_anthropic_format() {
         v: 1,
         method: "schema".to_string(),
         params,
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -386,6 +430,7 @@ fn test_schema_builtin_mcp_format() {
         v: 1,
         method: "schema".to_string(),
         params,
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -403,6 +448,60 @@ fn test_schema_builtin_mcp_format() {
     assert_eq!(send_email["inputSchema"]["type"], "object");
 }
 
+#[test]
+fn test_schema_builtin_json_schema_doc_format() {
+    let (socket_path, _handle) = start_schema_test_server();
+
+    let mut params = HashMap::new();
+    params.insert("format".to_string(), json!("json-schema-doc"));
+
+    let request = Request {
+        id: "schema-doc".to_string(),
+        v: 1,
+        method: "schema".to_string(),
+        params,
+        ..Default::default()
+    };
+
+    let response = send_request(&socket_path, &request).unwrap();
+
+    assert!(response["ok"].as_bool().unwrap());
+
+    let doc = &response["result"];
+    assert!(doc["$schema"].is_string());
+    assert_eq!(doc["methods"].as_object().unwrap().len(), 2);
+    assert_eq!(
+        doc["methods"]["schema-test.send_email"]["params"]["type"],
+        "object"
+    );
+}
+
+#[test]
+fn test_schema_builtin_typescript_format() {
+    let (socket_path, _handle) = start_schema_test_server();
+
+    let mut params = HashMap::new();
+    params.insert("format".to_string(), json!("typescript"));
+
+    let request = Request {
+        id: "schema-ts".to_string(),
+        v: 1,
+        method: "schema".to_string(),
+        params,
+        ..Default::default()
+    };
+
+    let response = send_request(&socket_path, &request).unwrap();
+
+    assert!(response["ok"].as_bool().unwrap());
+
+    let source = response["result"].as_str().unwrap();
+    assert!(source.contains("export interface SchemaTestSendEmailParams {"));
+    let call = "{ method: \"schema-test.send_email\"; params: SchemaTestSendEmailParams }";
+    assert!(source.contains(call));
+    assert!(source.contains("export type MethodName = MethodCall[\"method\"];"));
+}
+
 #[test]
 fn test_schema_builtin_method_filter() {
     let (socket_path, _handle) = start_schema_test_server();
@@ -418,6 +517,7 @@ fn test_schema_builtin_method_filter() {
         v: 1,
         method: "schema".to_string(),
         params,
+        ..Default::default()
     };
 
     let response = send_request(&socket_path, &request).unwrap();
@@ -427,6 +527,43 @@ fn test_schema_builtin_method_filter() {
     let methods = response["result"]["methods"].as_array().unwrap();
     assert_eq!(methods.len(), 1);
     assert_eq!(methods[0]["name"], "schema-test.send_email");
+    assert!(response["result"].get("not_found").is_none());
+}
+
+#[test]
+fn test_schema_builtin_reports_unknown_filter_methods() {
+    let (socket_path, _handle) = start_schema_test_server();
+
+    for format in ["json-schema", "openai", "mcp"] {
+        let mut params = HashMap::new();
+        params.insert("format".to_string(), json!(format));
+        params.insert(
+            "methods".to_string(),
+            json!(["schema-test.send_email", "schema-test.send_emial"]),
+        );
+
+        let request = Request {
+            id: format!("schema-typo-{}", format),
+            v: 1,
+            method: "schema".to_string(),
+            params,
+            ..Default::default()
+        };
+
+        let response = send_request(&socket_path, &request).unwrap();
+
+        assert!(response["ok"].as_bool().unwrap());
+        assert_eq!(
+            response["meta"]["warnings"],
+            json!(["Unknown method in 'methods' filter: schema-test.send_emial"])
+        );
+        if format != "mcp" {
+            assert_eq!(
+                response["result"]["not_found"],
+                json!(["schema-test.send_emial"])
+            );
+        }
+    }
 }
 
 /// Demo test that prints actual schema outputs - run with --nocapture to see
@@ -445,6 +582,7 @@ fn test_print_schema_formats() {
             v: 1,
             method: "schema".to_string(),
             params,
+            ..Default::default()
         };
 
         let response = send_request(&socket_path, &request).unwrap();
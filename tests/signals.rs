@@ -0,0 +1,57 @@
+//! Signal handling tests for FGP servers.
+//!
+//! Kept in their own test binary because they send `SIGTERM` to the test process.
+
+use anyhow::Result;
+use fgp_daemon::service::StopReason;
+use fgp_daemon::{FgpClient, FgpServer, FgpService};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use tempfile::TempDir;
+
+struct StopTracking {
+    stopped: Arc<Mutex<Option<StopReason>>>,
+}
+
+impl FgpService for StopTracking {
+    fn name(&self) -> &str {
+        "signals"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn dispatch(&self, _method: &str, _params: HashMap<String, Value>) -> Result<Value> {
+        Ok(Value::Null)
+    }
+
+    fn on_stop_with_reason(&self, reason: StopReason) -> Result<()> {
+        *self.stopped.lock().unwrap() = Some(reason);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_serve_with_signals_stops_on_sigterm() {
+    let temp_dir = TempDir::new().unwrap();
+    let socket_path = temp_dir.path().join("daemon.sock");
+    let stopped = Arc::new(Mutex::new(None));
+
+    let service = StopTracking {
+        stopped: Arc::clone(&stopped),
+    };
+    let server = FgpServer::new(service, &socket_path).unwrap();
+    let serving = thread::spawn(move || server.serve_with_signals());
+    thread::sleep(Duration::from_millis(100));
+    assert!(FgpClient::new(&socket_path).unwrap().health().unwrap().ok);
+
+    unsafe { libc::kill(libc::getpid(), libc::SIGTERM) };
+
+    serving.join().unwrap().unwrap();
+    assert_eq!(*stopped.lock().unwrap(), Some(StopReason::Signal));
+    assert!(!socket_path.exists());
+}
@@ -0,0 +1,81 @@
+//! Socket activation tests for FGP servers.
+//!
+//! Kept in their own test binary because they modify the process environment and
+//! file descriptor 3, which systemd uses for the first passed socket.
+
+use anyhow::Result;
+use fgp_daemon::{FgpClient, FgpServer, FgpService};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::os::fd::IntoRawFd;
+use std::os::unix::net::UnixListener;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tempfile::TempDir;
+
+struct EchoService;
+
+impl FgpService for EchoService {
+    fn name(&self) -> &str {
+        "echo"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn dispatch(&self, _method: &str, params: HashMap<String, Value>) -> Result<Value> {
+        Ok(Value::Object(params.into_iter().collect()))
+    }
+}
+
+/// Serve on a background thread and check a call round-trips.
+fn serve_and_call(server: FgpServer<EchoService>) -> Arc<FgpServer<EchoService>> {
+    let server = Arc::new(server);
+    let serving = Arc::clone(&server);
+    thread::spawn(move || serving.serve().unwrap());
+    thread::sleep(Duration::from_millis(100));
+
+    let client = FgpClient::new(server.socket_path()).unwrap();
+    let response = client.call("echo.echo", json!({"x": 1})).unwrap();
+    assert_eq!(response.result, Some(json!({"x": 1})));
+    server
+}
+
+#[test]
+fn test_from_systemd() {
+    let temp_dir = TempDir::new().unwrap();
+
+    // Activated: uses the listener on fd 3 and leaves the socket file in place
+    let activated = temp_dir.path().join("activated.sock");
+    let fd = UnixListener::bind(&activated).unwrap().into_raw_fd();
+    if fd != 3 {
+        if unsafe { libc::fcntl(3, libc::F_GETFD) } != -1 {
+            eprintln!("fd 3 is in use, skipping socket activation check");
+            return;
+        }
+        assert_eq!(unsafe { libc::dup2(fd, 3) }, 3);
+        unsafe { libc::close(fd) };
+    }
+    std::env::set_var("LISTEN_PID", std::process::id().to_string());
+    std::env::set_var("LISTEN_FDS", "1");
+
+    let server = FgpServer::from_systemd(EchoService, temp_dir.path().join("unused.sock")).unwrap();
+    assert_eq!(server.socket_path(), activated);
+    assert!(std::env::var("LISTEN_PID").is_err());
+    assert!(std::env::var("LISTEN_FDS").is_err());
+
+    serve_and_call(server).stop();
+    thread::sleep(Duration::from_millis(100));
+    assert!(activated.exists());
+    assert!(!temp_dir.path().join("unused.sock").exists());
+
+    // Not activated for this process: binds the fallback path
+    std::env::set_var("LISTEN_PID", (std::process::id() + 1).to_string());
+    std::env::set_var("LISTEN_FDS", "1");
+    let fallback = temp_dir.path().join("fallback.sock");
+    let server = FgpServer::from_systemd(EchoService, &fallback).unwrap();
+    assert_eq!(server.socket_path(), fallback);
+    serve_and_call(server).stop();
+}
@@ -0,0 +1,150 @@
+//! Service start-up tests using a stub launcher.
+//!
+//! Kept in their own test binary because they point `HOME` at a temporary
+//! directory to install a fake service under `~/.fgp/services`.
+
+use anyhow::Result;
+use fgp_daemon::lifecycle::{service_ready_path, service_socket_path, start_service_with_launcher};
+use fgp_daemon::{FgpClient, FgpServer, FgpService};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::UnixListener;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+struct StubService;
+
+impl FgpService for StubService {
+    fn name(&self) -> &str {
+        "stub"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn dispatch(&self, _method: &str, _params: HashMap<String, Value>) -> Result<Value> {
+        Ok(Value::Null)
+    }
+}
+
+/// A temporary `HOME` shared by the tests, which run in parallel and install
+/// services under different names.
+fn home() -> &'static Path {
+    static HOME: OnceLock<TempDir> = OnceLock::new();
+    HOME.get_or_init(|| {
+        let home = TempDir::new().unwrap();
+        std::env::set_var("HOME", home.path());
+        home
+    })
+    .path()
+}
+
+/// Install service `name` under the temporary `HOME` with the given manifest and
+/// an executable `daemon.sh` entrypoint running `script`; returns the service dir.
+fn install_service(name: &str, manifest: &str, script: &str) -> PathBuf {
+    let service_dir = home().join(".fgp/services").join(name);
+    fs::create_dir_all(&service_dir).unwrap();
+    fs::write(service_dir.join("manifest.json"), manifest).unwrap();
+    let entrypoint = service_dir.join("daemon.sh");
+    fs::write(&entrypoint, format!("#!/bin/sh\n{}\n", script)).unwrap();
+    fs::set_permissions(&entrypoint, fs::Permissions::from_mode(0o755)).unwrap();
+    service_dir
+}
+
+#[test]
+fn test_start_service_with_launcher() {
+    let service_dir = install_service(
+        "stub",
+        r#"{"daemon": {"entrypoint": "daemon.sh"}}"#,
+        "exit 1",
+    );
+    let entrypoint = service_dir.join("daemon.sh");
+
+    // Serve in-process instead of running the entrypoint
+    let launched: Arc<Mutex<Option<(PathBuf, PathBuf)>>> = Arc::default();
+    let seen = Arc::clone(&launched);
+    start_service_with_launcher("stub", move |entrypoint, service_dir| {
+        *seen.lock().unwrap() = Some((entrypoint.to_path_buf(), service_dir.to_path_buf()));
+        let server = FgpServer::new(StubService, service_socket_path("stub"))?;
+        thread::spawn(move || server.serve());
+        Ok(Command::new("true").spawn()?)
+    })
+    .unwrap();
+
+    assert_eq!(
+        launched.lock().unwrap().clone(),
+        Some((entrypoint, service_dir))
+    );
+    assert!(fgp_daemon::is_service_running("stub"));
+
+    // Already running: the launcher isn't called
+    start_service_with_launcher("stub", |_, _| anyhow::bail!("launched twice")).unwrap();
+
+    FgpClient::new(service_socket_path("stub"))
+        .unwrap()
+        .stop()
+        .unwrap();
+    for _ in 0..50 {
+        if !service_socket_path("stub").exists() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    // Launcher errors are returned as is
+    let err = start_service_with_launcher("stub", |_, _| anyhow::bail!("sandbox refused"));
+    assert_eq!(err.unwrap_err().to_string(), "sandbox refused");
+}
+
+#[test]
+fn test_start_service_waits_for_declared_ready_file() {
+    install_service(
+        "ready",
+        r#"{"daemon": {"entrypoint": "daemon.sh", "ready_file": true}}"#,
+        "exit 0",
+    );
+
+    // The socket accepts connections well before the daemon signals ready
+    let start = Instant::now();
+    start_service_with_launcher("ready", |_, _| {
+        let listener = UnixListener::bind(service_socket_path("ready"))?;
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(300));
+            fs::write(service_ready_path("ready"), "{}").unwrap();
+            drop(listener);
+        });
+        Ok(Command::new("true").spawn()?)
+    })
+    .unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(300));
+    assert!(service_ready_path("ready").exists());
+}
+
+#[test]
+fn test_start_failure_ignores_previous_startup_output() {
+    let service_dir = install_service(
+        "stale",
+        r#"{"daemon": {"entrypoint": "daemon.sh"}}"#,
+        "exit 1",
+    );
+    fs::create_dir_all(service_dir.join("logs")).unwrap();
+    fs::write(
+        fgp_daemon::logging::startup_log_path("stale"),
+        "previous run\n",
+    )
+    .unwrap();
+
+    // The launcher doesn't capture output, so there's none to report
+    let err = start_service_with_launcher("stale", |_, _| Ok(Command::new("false").spawn()?))
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("exited during startup"), "{}", err);
+    assert!(!err.contains("previous run"), "{}", err);
+}
@@ -0,0 +1,134 @@
+//! Service shutdown tests against a real daemon process.
+//!
+//! Kept in their own test binary because they point `HOME` at a temporary
+//! directory to install a fake service under `~/.fgp/services`.
+
+use anyhow::Result;
+use fgp_daemon::lifecycle::{is_process_running, service_pid_path};
+use fgp_daemon::{stop_service_with_timeout, FgpServer, FgpService};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// A temporary `HOME` shared by the tests, which run in parallel and install
+/// services under different names.
+fn home() -> &'static Path {
+    static HOME: OnceLock<TempDir> = OnceLock::new();
+    HOME.get_or_init(|| {
+        let home = TempDir::new().unwrap();
+        std::env::set_var("HOME", home.path());
+        home
+    })
+    .path()
+}
+
+/// Install a service whose entrypoint runs `script`, start it and record its PID.
+fn spawn_daemon(name: &str, script: &str) -> u32 {
+    let service_dir = fgp_daemon::fgp_services_dir().join(name);
+    fs::create_dir_all(&service_dir).unwrap();
+    fs::write(
+        service_dir.join("manifest.json"),
+        r#"{"daemon": {"entrypoint": "daemon.sh"}}"#,
+    )
+    .unwrap();
+    let entrypoint = service_dir.join("daemon.sh");
+    fs::write(&entrypoint, format!("#!/bin/sh\n{}\n", script)).unwrap();
+    fs::set_permissions(&entrypoint, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let mut child = Command::new(&entrypoint).spawn().unwrap();
+    let pid = child.id();
+    fs::write(service_pid_path(name), pid.to_string()).unwrap();
+    // Reap the process so it doesn't linger as a zombie once it exits
+    thread::spawn(move || child.wait());
+    thread::sleep(Duration::from_millis(100));
+    pid
+}
+
+#[test]
+fn test_stop_service_with_timeout() {
+    home();
+
+    // Exits on SIGTERM
+    let pid = spawn_daemon("polite", "while :; do sleep 0.05; done");
+    let forced = stop_service_with_timeout("polite", Duration::from_secs(2)).unwrap();
+    assert!(!forced);
+    assert!(!is_process_running(pid));
+    assert!(!service_pid_path("polite").exists());
+
+    // Ignores SIGTERM, so it's killed once the grace period is over
+    let pid = spawn_daemon("stubborn", "trap '' TERM\nwhile :; do sleep 0.05; done");
+    let forced = stop_service_with_timeout("stubborn", Duration::from_millis(200)).unwrap();
+    assert!(forced);
+    assert!(!is_process_running(pid));
+
+    // Nothing running: nothing to kill
+    assert!(!stop_service_with_timeout("stubborn", Duration::from_millis(200)).unwrap());
+}
+
+struct Unmanifested;
+
+impl FgpService for Unmanifested {
+    fn name(&self) -> &str {
+        "unmanifested"
+    }
+
+    fn version(&self) -> &str {
+        "1.0.0"
+    }
+
+    fn dispatch(&self, _method: &str, _params: HashMap<String, Value>) -> Result<Value> {
+        Ok(Value::Null)
+    }
+}
+
+/// Serves [`Unmanifested`] when run by [`test_stop_service_without_manifest`] in a
+/// child copy of this test binary.
+#[test]
+#[ignore = "run in a child process by test_stop_service_without_manifest"]
+fn serve_unmanifested() {
+    if std::env::var_os("FGP_TEST_SERVE").is_some() {
+        FgpServer::for_service(Unmanifested)
+            .unwrap()
+            .serve()
+            .unwrap();
+    }
+}
+
+#[test]
+fn test_stop_service_without_manifest() {
+    home();
+
+    // Started via `for_service`, so there's a PID file but no manifest to match it to
+    let mut child = Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "serve_unmanifested", "--ignored"])
+        .env("FGP_TEST_SERVE", "1")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    let pid = child.id();
+    thread::spawn(move || child.wait());
+    for _ in 0..100 {
+        if fgp_daemon::is_service_running("unmanifested") {
+            break;
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+    assert!(service_pid_path("unmanifested").exists());
+
+    // The stop request needs no PID check
+    let result = stop_service_with_timeout("unmanifested", Duration::from_secs(2));
+    if result.is_err() {
+        unsafe { libc::kill(pid as i32, libc::SIGKILL) };
+    }
+    let forced = result.unwrap();
+    assert!(!forced);
+    assert!(!is_process_running(pid));
+}